@@ -0,0 +1,316 @@
+//! Optional bearer token authentication for the node API.
+//!
+//! Each endpoint requires a [`Scope`] (see [`required_scope`]). Tokens are
+//! presented via the `Authorization: Bearer <token>` header and are either:
+//!
+//! - **Static** tokens listed in the [`AuthConfig`] along with their scopes.
+//! - **HMAC** tokens of the form `<claims>.<signature>`, where `claims` is the
+//!   base64url encoded JSON [`Claims`] and `signature` is the base64url encoded
+//!   HMAC-SHA256 of the encoded claims. See [`sign`].
+//!
+//! Endpoints requiring the [`Scope::Read`] scope remain open to requests
+//! without a token when [`AuthConfig::public_read`] is enabled.
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeSet, sync::Arc, time::SystemTime};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+/// A permission granted by a token.
+///
+/// Scopes are ordered by privilege, and a token with a scope is also granted
+/// all lesser scopes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Read access to the chain, state and node progress.
+    Read,
+    /// Access to privileged endpoints, e.g. dry run validation.
+    Admin,
+}
+
+/// The authentication configuration, typically loaded from a file.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AuthConfig {
+    /// Whether endpoints requiring [`Scope::Read`] may be accessed without a
+    /// token.
+    #[serde(default = "default_public_read")]
+    pub public_read: bool,
+    /// Static bearer tokens.
+    #[serde(default)]
+    pub tokens: Vec<StaticToken>,
+    /// Hex-encoded secret keys with which HMAC tokens may be signed.
+    ///
+    /// Tokens signed with any of the keys are accepted, allowing keys to be
+    /// rotated.
+    #[serde(default)]
+    pub hmac_keys: Vec<String>,
+}
+
+/// A static bearer token and the scopes it grants.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StaticToken {
+    /// The token.
+    pub token: String,
+    /// The scopes granted to the token.
+    pub scopes: BTreeSet<Scope>,
+}
+
+/// The claims of an HMAC token.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Claims {
+    /// The scopes granted to the token.
+    pub scopes: BTreeSet<Scope>,
+    /// The time after which the token is no longer valid, in seconds since the
+    /// Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+}
+
+/// Verifies tokens against an [`AuthConfig`].
+///
+/// Clones share the same configuration.
+#[derive(Clone)]
+pub struct Auth(Arc<Verifier>);
+
+/// The scopes granted to the token presented with a request.
+///
+/// Inserted into the extensions of each request that passes authentication.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Grant(BTreeSet<Scope>);
+
+/// An invalid [`AuthConfig`].
+#[derive(Debug, Error)]
+pub enum AuthConfigError {
+    /// An HMAC key is not valid hex.
+    #[error("failed to decode HMAC key from hex string: {0}")]
+    HmacKeyHex(#[from] hex::FromHexError),
+    /// An HMAC key is empty.
+    #[error("HMAC keys must not be empty")]
+    EmptyHmacKey,
+    /// A static token is empty.
+    #[error("static tokens must not be empty")]
+    EmptyToken,
+}
+
+/// A request was refused authentication.
+#[derive(Debug, Error)]
+pub enum AuthError {
+    /// No token was presented.
+    #[error("a bearer token is required")]
+    Missing,
+    /// The presented token is not valid.
+    #[error("the bearer token is invalid")]
+    Invalid,
+    /// The presented token has expired.
+    #[error("the bearer token has expired")]
+    Expired,
+    /// The presented token does not grant the required scope.
+    #[error("the bearer token does not grant the {0:?} scope")]
+    Forbidden(Scope),
+}
+
+struct Verifier {
+    public_read: bool,
+    token_digests: Vec<([u8; 32], BTreeSet<Scope>)>,
+    hmac_keys: Vec<Vec<u8>>,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn default_public_read() -> bool {
+    true
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            public_read: default_public_read(),
+            tokens: vec![],
+            hmac_keys: vec![],
+        }
+    }
+}
+
+impl Auth {
+    /// Validate the given configuration.
+    pub fn new(config: AuthConfig) -> Result<Self, AuthConfigError> {
+        let token_digests = config
+            .tokens
+            .into_iter()
+            .map(|StaticToken { token, scopes }| {
+                if token.is_empty() {
+                    return Err(AuthConfigError::EmptyToken);
+                }
+                Ok((Sha256::digest(token.as_bytes()).into(), scopes))
+            })
+            .collect::<Result<_, _>>()?;
+        let hmac_keys = config
+            .hmac_keys
+            .iter()
+            .map(|key| match hex::decode(key)? {
+                key if key.is_empty() => Err(AuthConfigError::EmptyHmacKey),
+                key => Ok(key),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(Arc::new(Verifier {
+            public_read: config.public_read,
+            token_digests,
+            hmac_keys,
+        })))
+    }
+
+    /// Verify the given token, returning the scopes it grants.
+    pub fn verify(&self, token: &str) -> Result<Grant, AuthError> {
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        let mut granted = None;
+        for (token_digest, scopes) in &self.0.token_digests {
+            if bool::from(token_digest.ct_eq(&digest)) {
+                granted = Some(scopes.clone());
+            }
+        }
+        if let Some(scopes) = granted {
+            return Ok(Grant(scopes));
+        }
+        self.verify_hmac(token)
+    }
+
+    /// Whether a request with the given grant may access an endpoint requiring
+    /// the given scope.
+    pub fn permits(&self, grant: Option<&Grant>, scope: Scope) -> bool {
+        (scope == Scope::Read && self.0.public_read) || grant.is_some_and(|g| g.allows(scope))
+    }
+
+    fn verify_hmac(&self, token: &str) -> Result<Grant, AuthError> {
+        let (encoded_claims, encoded_signature) =
+            token.split_once('.').ok_or(AuthError::Invalid)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(encoded_signature)
+            .map_err(|_| AuthError::Invalid)?;
+        let signed = self.0.hmac_keys.iter().any(|key| {
+            let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(encoded_claims.as_bytes());
+            mac.verify_slice(&signature).is_ok()
+        });
+        if !signed {
+            return Err(AuthError::Invalid);
+        }
+        let claims: Claims = URL_SAFE_NO_PAD
+            .decode(encoded_claims)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or(AuthError::Invalid)?;
+        if let Some(exp) = claims.exp {
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            if now >= exp {
+                return Err(AuthError::Expired);
+            }
+        }
+        Ok(Grant(claims.scopes))
+    }
+}
+
+impl Grant {
+    /// Whether the grant includes the given scope.
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.0.iter().any(|granted| *granted >= scope)
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ (AuthError::Missing | AuthError::Invalid | AuthError::Expired) => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                e.to_string(),
+            )
+                .into_response(),
+            e @ AuthError::Forbidden(_) => (StatusCode::FORBIDDEN, e.to_string()).into_response(),
+        }
+    }
+}
+
+/// Sign the given claims with the given secret key, producing an HMAC token.
+pub fn sign(key: &[u8], claims: &Claims) -> String {
+    let claims = serde_json::to_vec(claims).expect("claims serialize to JSON");
+    let encoded_claims = URL_SAFE_NO_PAD.encode(claims);
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(encoded_claims.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+    format!("{encoded_claims}.{signature}")
+}
+
+/// The scope required to access the endpoint at the given route path, or
+/// `None` if the endpoint is always public.
+///
+/// Health and readiness probes are always public. Dry run validation requires
+/// [`Scope::Admin`], as should any future endpoint that is expensive or
+/// mutates the node.
+pub fn required_scope(path: &str) -> Option<Scope> {
+    use crate::endpoint::*;
+    match path {
+        health_check::PATH | ready::PATH => None,
+        validate_block::PATH | validate_solution_set::PATH => Some(Scope::Admin),
+        _ => Some(Scope::Read),
+    }
+}
+
+/// Middleware authenticating each request to a matched endpoint.
+///
+/// Passes all requests through if the [`State`](crate::State) has no
+/// [`Auth`]. Otherwise, requests lacking the [`required_scope`] are refused,
+/// and the [`Grant`] of those accepted is inserted into the request's
+/// extensions. An invalid token is only refused by endpoints that may not be
+/// accessed without one, and is otherwise treated as no token.
+pub async fn authenticate(
+    State(state): State<crate::State>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth) = &state.auth else {
+        return next.run(request).await;
+    };
+    let required = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| required_scope(path.as_str()));
+    let grant = match bearer_token(request.headers()).map(|token| auth.verify(token)) {
+        None => None,
+        Some(Ok(grant)) => Some(grant),
+        Some(Err(err)) => match required {
+            Some(scope) if !auth.permits(None, scope) => return err.into_response(),
+            _ => None,
+        },
+    };
+    if let Some(scope) = required {
+        if !auth.permits(grant.as_ref(), scope) {
+            let err = match grant {
+                None => AuthError::Missing,
+                Some(_) => AuthError::Forbidden(scope),
+            };
+            return err.into_response();
+        }
+    }
+    request.extensions_mut().insert(grant.unwrap_or_default());
+    next.run(request).await
+}
+
+/// The token presented via the `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+}
@@ -1,18 +1,27 @@
 //! Provides a small module for each endpoint with associated `PATH` and `handler`.
 
+use crate::Shutdown;
 use axum::{
-    extract::{Path, Query, State},
+    async_trait,
+    extract::{FromRequestParts, Path, Query, State},
+    http::{header, request::Parts, HeaderMap},
     response::{
         sse::{self, Sse},
         IntoResponse,
     },
     Json,
 };
-use essential_node::db;
-use essential_node_types::{block_notify::BlockRx, Block};
-use essential_types::{convert::word_from_bytes, ContentAddress, Value, Word};
+use essential_node::{
+    db,
+    validation_notify::{ValidatedBlock, ValidationRx},
+};
+use essential_node_types::{block::binary, block_notify::BlockRx, Block};
+use essential_types::{
+    convert::{bytes_from_word, word_from_bytes},
+    ContentAddress, Value, Word,
+};
 use futures::{Stream, StreamExt};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// A range in blocks, used for the `list-blocks` and `list-contracts` endpoints.
@@ -33,6 +42,75 @@ pub struct StartBlock {
     pub start_block: Word,
 }
 
+/// The maximum page size that may be requested from paginated endpoints.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// The type of the final event sent to SSE subscribers when the server shuts
+/// down. Clients may reconnect to another node, resuming from the last event
+/// received.
+pub const CLOSE_EVENT: &str = "close";
+
+/// The type of the event sent to `subscribe-validation` subscribers that fall
+/// too far behind. Its data is a [`Lagged`] carrying the number of skipped
+/// outcomes.
+pub const LAGGED_EVENT: &str = "lagged";
+
+/// A paginated range in time, used for the `list-blocks-by-time` endpoint.
+///
+/// Times are in seconds since the Unix epoch and may be no greater than
+/// `i64::MAX`. The range is non-inclusive of the `end`, i.e. it is equivalent
+/// to `start..end`.
+#[derive(Debug, Deserialize)]
+pub struct TimeRange {
+    /// Start of the range.
+    pub start: u64,
+    /// The end of the range (exclusive).
+    pub end: u64,
+    /// The maximum number of blocks per page.
+    ///
+    /// Defaults to [`MAX_PAGE_SIZE`].
+    pub page_size: Option<u64>,
+    /// The page to return, starting from `0`.
+    pub page: Option<u64>,
+}
+
+/// The data of a [`LAGGED_EVENT`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Lagged {
+    /// The number of outcomes skipped since the previous event.
+    pub skipped: u64,
+}
+
+/// An item of the stream of validated blocks.
+enum Validated {
+    /// The outcome of a validated block.
+    Block(std::sync::Arc<ValidatedBlock>),
+    /// The subscriber fell too far behind and missed some outcomes.
+    Lagged(Lagged),
+}
+
+/// The encoding of blocks within a response.
+///
+/// Negotiated via the `Accept` header. Blocks are encoded as JSON unless the
+/// client accepts the binary [`MEDIA_TYPE`](binary::MEDIA_TYPE).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BlockEncoding {
+    /// JSON, or SSE events with JSON data for subscriptions.
+    #[default]
+    Json,
+    /// A stream of length-prefixed binary frames. See [`binary`].
+    Binary,
+}
+
+/// A list of blocks, encoded as requested by the client.
+#[derive(Clone, Debug)]
+pub struct Blocks {
+    /// The blocks.
+    pub blocks: Vec<Block>,
+    /// The encoding with which to respond.
+    pub encoding: BlockEncoding,
+}
+
 /// Any endpoint error that might occur.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -45,9 +123,41 @@ pub enum Error {
         query_state::HELP_MSG
     )]
     InvalidQueryParameters(query_state::QueryStateParams),
+    #[error("Invalid page size {0}. Must be between 1 and {MAX_PAGE_SIZE}")]
+    InvalidPageSize(u64),
+    #[error("Invalid time {0}. Must be no greater than {}", i64::MAX)]
+    InvalidTime(u64),
+    #[error("Invalid page {0}. Must be no greater than {1} for the page size")]
+    InvalidPage(u64, u64),
+    #[error(
+        "Invalid number of values {0}. Must be no greater than {}",
+        query_state_range::MAX_NUM_VALUES
+    )]
+    InvalidNumValues(usize),
+    #[error("The requested key range overflows the key space")]
+    KeyRangeOverflow,
+    #[error(
+        "Invalid number of keys {0}. Must be no greater than {}",
+        query_state_batch::MAX_KEYS
+    )]
+    TooManyKeys(usize),
+    #[error("failed to decode predicate: {0}")]
+    DecodePredicate(#[from] essential_node::QueryPredicateError),
+    #[error("failed to decode program: {0}")]
+    DecodeProgram(#[from] essential_node::QueryProgramError),
+    #[error("the contract registry entry for contract {0} is malformed")]
+    InvalidContractEntry(ContentAddress),
+    #[error("dry run validation failed: {0}")]
+    Validation(#[from] essential_node::ValidationError),
+    #[error("failed to encode metrics: {0}")]
+    EncodeMetrics(#[from] std::fmt::Error),
+    #[error("failed to encode block: {0}")]
+    EncodeBlock(#[from] binary::Error),
+    #[error("failed to encode block as JSON: {0}")]
+    EncodeBlockJson(#[from] serde_json::Error),
 }
 
-/// An error produced by a subscription endpoint stream.
+/// An error produced by a subscription or streamed endpoint response.
 #[derive(Debug, Error)]
 pub enum SubscriptionError {
     /// An axum error occurred.
@@ -56,6 +166,15 @@ pub enum SubscriptionError {
     /// A DB query failure occurred.
     #[error("DB query failed: {0}")]
     Query(#[from] db::QueryError),
+    /// Failed to acquire a connection and query the DB.
+    #[error("DB query failed: {0}")]
+    ConnPoolQuery(#[from] db::pool::AcquireThenQueryError),
+    /// Failed to encode a block.
+    #[error("failed to encode block: {0}")]
+    Encode(#[from] binary::Error),
+    /// Failed to encode a block as JSON.
+    #[error("failed to encode block as JSON: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
 /// Provides an [`db::AwaitNewBlock`] implementation for the API.
@@ -68,10 +187,80 @@ impl IntoResponse for Error {
             Error::ConnPoolQuery(e) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
             }
+            e @ Error::Validation(_)
+            | e @ Error::DecodePredicate(_)
+            | e @ Error::DecodeProgram(_)
+            | e @ Error::InvalidContractEntry(_)
+            | e @ Error::EncodeMetrics(_)
+            | e @ Error::EncodeBlock(_)
+            | e @ Error::EncodeBlockJson(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            }
             e @ Error::HexDecode(_) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
             e @ Error::InvalidQueryParameters(_) => {
                 (StatusCode::BAD_REQUEST, e.to_string()).into_response()
             }
+            e @ Error::InvalidPageSize(_) => {
+                (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+            }
+            e @ Error::InvalidTime(_) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ Error::InvalidPage(..) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ Error::InvalidNumValues(_) => {
+                (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+            }
+            e @ Error::KeyRangeOverflow => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ Error::TooManyKeys(_) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl BlockEncoding {
+    /// Select the encoding accepted by the given request headers.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let accepts_binary = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|media_range| media_range.split(';').next())
+            .any(|media_type| media_type.trim().eq_ignore_ascii_case(binary::MEDIA_TYPE));
+        if accepts_binary {
+            Self::Binary
+        } else {
+            Self::Json
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for BlockEncoding {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+impl IntoResponse for Blocks {
+    fn into_response(self) -> axum::response::Response {
+        use axum::http::StatusCode;
+        match self.encoding {
+            BlockEncoding::Json => Json(self.blocks).into_response(),
+            BlockEncoding::Binary => {
+                let frames = self
+                    .blocks
+                    .iter()
+                    .map(binary::encode_frame)
+                    .collect::<Result<Vec<_>, _>>();
+                match frames {
+                    Ok(frames) => (
+                        [(header::CONTENT_TYPE, binary::MEDIA_TYPE)],
+                        frames.concat(),
+                    )
+                        .into_response(),
+                    Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+                }
+            }
         }
     }
 }
@@ -91,175 +280,2641 @@ pub mod health_check {
     pub async fn handler() {}
 }
 
-/// The `list-blocks` get endpoint.
+/// The `get-block` get endpoint.
 ///
-/// Takes a range of L2 blocks as a parameter.
-pub mod list_blocks {
+/// Takes a block content address as a path parameter, encoded as hex.
+///
+/// Returns the block along with whether or not it has been finalized and its
+/// validation status, or `None` if no block exists with the given address.
+pub mod get_block {
     use super::*;
-    pub const PATH: &str = "/list-blocks";
+    use serde::Serialize;
+
+    pub const PATH: &str = "/get-block/:block-address";
+
+    /// The validation status of a block.
+    #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ValidationStatus {
+        /// The block has not yet been validated.
+        Pending,
+        /// The block has been validated successfully.
+        Valid,
+        /// The block failed validation.
+        Failed,
+    }
+
+    /// A block along with its finalization and validation status.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct BlockWithStatus {
+        /// The block.
+        pub block: Block,
+        /// Whether or not the block has been finalized.
+        pub finalized: bool,
+        /// The validation status of the block.
+        pub validation: ValidationStatus,
+    }
+
     pub async fn handler(
         State(state): State<crate::State>,
-        Query(block_range): Query<BlockRange>,
-    ) -> Result<Json<Vec<Block>>, Error> {
-        let blocks = state
+        Path(block_address): Path<String>,
+    ) -> Result<Json<Option<BlockWithStatus>>, Error> {
+        let block_address: ContentAddress = block_address.parse()?;
+        let block = state
             .conn_pool
-            .list_blocks(block_range.start..block_range.end)
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| get_block_with_status(tx, &block_address))
+            })
             .await?;
-        Ok(Json(blocks))
+        Ok(Json(block))
+    }
+
+    /// Query the block along with its status within a single transaction.
+    fn get_block_with_status(
+        tx: &rusqlite::Transaction,
+        block_address: &ContentAddress,
+    ) -> Result<Option<BlockWithStatus>, db::QueryError> {
+        let Some(block) = db::get_block(tx, block_address)? else {
+            return Ok(None);
+        };
+        let finalized = db::is_block_finalized(tx, block_address)?;
+        let validation = validation_status(tx, block_address, block.header.number, finalized)?;
+        Ok(Some(BlockWithStatus {
+            block,
+            finalized,
+            validation,
+        }))
+    }
+
+    /// Determine the validation status of the block with the given address and number.
+    ///
+    /// Only the block of the current validation progress and its ancestors are
+    /// considered valid, unless they have been recorded as failed. Blocks on
+    /// other forks remain pending, even below the progress block's number.
+    fn validation_status(
+        tx: &rusqlite::Transaction,
+        block_address: &ContentAddress,
+        block_number: Word,
+        finalized: bool,
+    ) -> Result<ValidationStatus, db::QueryError> {
+        if db::is_block_failed(tx, block_address)? {
+            return Ok(ValidationStatus::Failed);
+        }
+        let Some(mut ancestor) = db::get_validation_progress(tx)? else {
+            return Ok(ValidationStatus::Pending);
+        };
+        // Walk back from the progress block through its unfinalized ancestors.
+        loop {
+            if ancestor == *block_address {
+                return Ok(ValidationStatus::Valid);
+            }
+            let Some(header) = db::get_block_header(tx, &ancestor)? else {
+                return Ok(ValidationStatus::Pending);
+            };
+            // Finalized blocks form a single chain, so any finalized block at or
+            // below a finalized ancestor is also an ancestor.
+            if db::is_block_finalized(tx, &ancestor)? {
+                if finalized && block_number <= header.number {
+                    return Ok(ValidationStatus::Valid);
+                }
+                return Ok(ValidationStatus::Pending);
+            }
+            if header.number <= block_number {
+                return Ok(ValidationStatus::Pending);
+            }
+            match db::get_parent_block_address(tx, &ancestor)? {
+                Some(parent) => ancestor = parent,
+                None => return Ok(ValidationStatus::Pending),
+            }
+        }
     }
 }
 
-/// The `query-state` get endpoint.
+/// The `get-solution-set` get endpoint.
 ///
-/// Takes a contract content address and a byte array key as path parameters,
-/// both encoded as hex.
-pub mod query_state {
-    use std::fmt::Display;
-
+/// Takes a solution set content address as a path parameter, encoded as hex.
+///
+/// Returns the solution set along with every block in which it was included, or
+/// `None` if the solution set has not been included in any block.
+pub mod get_solution_set {
+    use super::*;
+    use essential_types::solution::SolutionSet;
     use serde::Serialize;
 
+    pub const PATH: &str = "/get-solution-set/:ca";
+
+    /// The location of a solution set within a block.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct Inclusion {
+        /// The address of the block that includes the solution set.
+        pub block_address: ContentAddress,
+        /// The number of the block that includes the solution set.
+        pub block_number: Word,
+        /// The index of the solution set within the block.
+        pub solution_set_index: u64,
+    }
+
+    /// A solution set along with the blocks in which it was included.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct SolutionSetWithInclusions {
+        /// The solution set.
+        pub solution_set: SolutionSet,
+        /// Every block in which the solution set was included, ordered by block number.
+        pub inclusions: Vec<Inclusion>,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path(ca): Path<String>,
+    ) -> Result<Json<Option<SolutionSetWithInclusions>>, Error> {
+        let ca: ContentAddress = ca.parse()?;
+        let res = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| {
+                    let inclusions = db::list_solution_set_blocks(tx, &ca)?;
+                    if inclusions.is_empty() {
+                        return Ok(None);
+                    }
+                    let solution_set = db::get_solution_set(tx, &ca)?;
+                    let inclusions = inclusions
+                        .into_iter()
+                        .map(
+                            |(block_address, block_number, solution_set_index)| Inclusion {
+                                block_address,
+                                block_number,
+                                solution_set_index,
+                            },
+                        )
+                        .collect();
+                    Ok::<_, db::QueryError>(Some(SolutionSetWithInclusions {
+                        solution_set,
+                        inclusions,
+                    }))
+                })
+            })
+            .await?;
+        Ok(Json(res))
+    }
+}
+
+/// The `contract` get endpoint.
+///
+/// Takes a contract content address (encoded as hex) as a path parameter.
+///
+/// Returns the contract as registered within the contract registry as of the
+/// latest finalized block, or `None` if the contract is not registered.
+pub mod get_contract {
     use super::*;
+    use essential_node_types::contract_registry;
+    use essential_types::{contract::Contract, convert::u8_32_from_word_4};
 
-    pub const HELP_MSG: &str = r#"
-The query parameters must be empty or one of the following combinations:
-    - block_inclusive
-    - block_exclusive
-    - block_inclusive, solution_inclusive
-    - block_inclusive, solution_exclusive
-"#;
+    pub const PATH: &str = "/contract/:contract-ca";
 
-    #[derive(Deserialize, Serialize, Default, Debug)]
-    pub struct QueryStateParams {
-        pub block_inclusive: Option<Word>,
-        pub block_exclusive: Option<Word>,
-        pub solution_inclusive: Option<u64>,
-        pub solution_exclusive: Option<u64>,
+    /// The raw words of a contract's entries within the contract registry.
+    struct ContractEntry {
+        salt: Value,
+        /// The entry for each predicate, or `None` if missing.
+        predicates: Vec<Option<Value>>,
     }
 
-    impl Display for QueryStateParams {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(
-                f,
-                "block_inclusive: {:?}, block_exclusive: {:?}, solution_inclusive: {:?}, solution_exclusive: {:?}",
-                self.block_inclusive, self.block_exclusive, self.solution_inclusive, self.solution_exclusive
-            )
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path(contract_ca): Path<String>,
+    ) -> Result<Json<Option<Contract>>, Error> {
+        let contract_ca: ContentAddress = contract_ca.parse()?;
+        let registry = state.contract_registry.clone();
+        let ca = contract_ca.clone();
+        let entry = state
+            .conn_pool
+            .acquire_then(move |h| db::with_tx_dropped(h, |tx| query_contract(tx, &registry, &ca)))
+            .await?;
+        let Some(ContractEntry { salt, predicates }) = entry else {
+            return Ok(Json(None));
+        };
+        let invalid = || Error::InvalidContractEntry(contract_ca.clone());
+        let salt: [Word; 4] = salt.try_into().map_err(|_| invalid())?;
+        let predicates = predicates
+            .into_iter()
+            .map(|words| {
+                let words = words.ok_or_else(invalid)?;
+                Ok(essential_node::validate::decode_predicate(&words)?)
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Json(Some(Contract {
+            predicates,
+            salt: u8_32_from_word_4(salt),
+        })))
+    }
+
+    /// Query the contract's salt and the entry of each of its predicates.
+    fn query_contract(
+        tx: &rusqlite::Transaction,
+        contract_registry: &ContentAddress,
+        contract_ca: &ContentAddress,
+    ) -> Result<Option<ContractEntry>, db::QueryError> {
+        let Some(block) = latest_finalized_block_number(tx)? else {
+            return Ok(None);
+        };
+        let salt_key = contract_registry::contract_salt_key(contract_ca);
+        let Some(salt) =
+            db::finalized::query_state_inclusive_block(tx, contract_registry, &salt_key, block)?
+        else {
+            return Ok(None);
+        };
+
+        // All predicate keys share the contract's prefix, followed by the predicate address.
+        let prefix = contract_registry::contract_key_prefix(contract_ca);
+        let entries = db::finalized::query_state_prefix_inclusive_block(
+            tx,
+            contract_registry,
+            &prefix,
+            block,
+            None,
+            i64::MAX,
+        )?;
+        let mut predicates = vec![];
+        for (key, _) in entries {
+            let Ok(pred_words) = <[Word; 4]>::try_from(&key[prefix.len()..]) else {
+                // Skip the salt entry.
+                continue;
+            };
+            let pred_ca = ContentAddress(u8_32_from_word_4(pred_words));
+            let pred_key = contract_registry::predicate_key(&pred_ca);
+            let pred = db::finalized::query_state_inclusive_block(
+                tx,
+                contract_registry,
+                &pred_key,
+                block,
+            )?;
+            predicates.push(pred);
         }
+        Ok(Some(ContractEntry { salt, predicates }))
     }
+}
+
+/// The `predicate` get endpoint.
+///
+/// Takes a predicate content address (encoded as hex) as a path parameter.
+///
+/// Returns the predicate as registered within the contract registry as of the
+/// latest finalized block, or `None` if the predicate is not registered.
+pub mod get_predicate {
+    use super::*;
+    use essential_node_types::contract_registry;
+    use essential_types::predicate::Predicate;
+
+    pub const PATH: &str = "/predicate/:predicate-ca";
 
-    pub const PATH: &str = "/query-state/:contract-ca/:key";
     pub async fn handler(
         State(state): State<crate::State>,
-        Path((contract_ca, key)): Path<(String, String)>,
-        Query(params): Query<QueryStateParams>,
-    ) -> Result<Json<Option<Value>>, Error> {
-        let contract_ca: ContentAddress = contract_ca.parse()?;
-        let key: Vec<u8> = hex::decode(key)?;
-        let key = key_words_from_bytes(&key);
-        // TODO: When state is compacted and blocks are discarded, this query should
-        // fall back to querying compacted state.
+        Path(predicate_ca): Path<String>,
+    ) -> Result<Json<Option<Predicate>>, Error> {
+        let predicate_ca: ContentAddress = predicate_ca.parse()?;
+        let registry = state.contract_registry.clone();
+        let key = contract_registry::predicate_key(&predicate_ca);
+        let words = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| query_latest_finalized(tx, &registry, &key))
+            })
+            .await?;
+        let predicate = words
+            .map(|words| essential_node::validate::decode_predicate(&words))
+            .transpose()?;
+        Ok(Json(predicate))
+    }
+}
 
-        // TODO: When blocks aren't immediately finalized, this query will need to
-        // either take a block address or use a fork choice rule to determine the
-        // latest state to return. It's possible this query won't make much sense
-        // at that point.
-
-        let value = match params {
-            QueryStateParams {
-                block_inclusive: Some(block),
-                block_exclusive: None,
-                solution_inclusive: None,
-                solution_exclusive: None,
-            } => {
-                state
-                    .conn_pool
-                    .query_state_finalized_inclusive_block(contract_ca, key, block)
-                    .await?
-            }
-            QueryStateParams {
-                block_inclusive: None,
-                block_exclusive: Some(block),
-                solution_inclusive: None,
-                solution_exclusive: None,
-            } => {
-                state
-                    .conn_pool
-                    .query_state_finalized_exclusive_block(contract_ca, key, block)
-                    .await?
-            }
-            QueryStateParams {
-                block_inclusive: Some(block),
-                block_exclusive: None,
-                solution_inclusive: Some(solution_ix),
-                solution_exclusive: None,
-            } => {
-                state
-                    .conn_pool
-                    .query_state_finalized_inclusive_solution_set(
-                        contract_ca,
-                        key,
-                        block,
-                        solution_ix,
-                    )
-                    .await?
+/// The `program` get endpoint.
+///
+/// Takes a program content address (encoded as hex) as a path parameter.
+///
+/// Returns the program as registered within the program registry as of the
+/// latest finalized block, or `None` if the program is not registered.
+pub mod get_program {
+    use super::*;
+    use essential_node_types::program_registry;
+    use essential_types::predicate::Program;
+
+    pub const PATH: &str = "/program/:program-ca";
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path(program_ca): Path<String>,
+    ) -> Result<Json<Option<Program>>, Error> {
+        let program_ca: ContentAddress = program_ca.parse()?;
+        let registry = state.program_registry.clone();
+        let key = program_registry::program_key(&program_ca);
+        let words = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| query_latest_finalized(tx, &registry, &key))
+            })
+            .await?;
+        let program = words
+            .map(|words| essential_node::validate::decode_program(&words))
+            .transpose()?;
+        Ok(Json(program))
+    }
+}
+
+/// The `info` get endpoint.
+///
+/// Describes the network served by the node along with its progress, so that
+/// clients may refuse to talk to a node serving the wrong network.
+pub mod info {
+    use super::*;
+    use crate::NodeConfig;
+    use serde::Serialize;
+
+    pub const PATH: &str = "/info";
+
+    /// The node's configuration, crate versions and progress.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct NodeInfo {
+        /// The node's chain and run configuration.
+        #[serde(flatten)]
+        pub config: NodeConfig,
+        /// The versions of the node's crates.
+        pub versions: Versions,
+        /// The latest finalized block, or `None` if no block has been finalized.
+        pub latest_finalized_block: Option<LatestBlock>,
+        /// The latest block to be successfully validated, or `None` if
+        /// validation has not yet begun.
+        pub latest_validated_block: Option<LatestBlock>,
+    }
+
+    /// The versions of the node's crates.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct Versions {
+        /// The version of `essential-node`.
+        pub node: String,
+        /// The version of `essential-node-api`.
+        pub node_api: String,
+        /// The version of `essential-node-db`.
+        pub node_db: String,
+        /// The version of `essential-node-types`.
+        pub node_types: String,
+    }
+
+    /// The address and number of a block.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct LatestBlock {
+        /// The address of the block.
+        pub block_address: ContentAddress,
+        /// The number of the block.
+        pub block_number: Word,
+    }
+
+    pub async fn handler(State(state): State<crate::State>) -> Result<Json<NodeInfo>, Error> {
+        let (latest_finalized_block, latest_validated_block) = state
+            .conn_pool
+            .acquire_then(|h| {
+                db::with_tx_dropped(h, |tx| {
+                    let finalized = match db::get_latest_finalized_block_address(tx)? {
+                        Some(block_address) => {
+                            db::get_block_header(tx, &block_address)?.map(|header| LatestBlock {
+                                block_address,
+                                block_number: header.number,
+                            })
+                        }
+                        None => None,
+                    };
+                    let validated = validation_progress::query(tx)?.map(|progress| LatestBlock {
+                        block_address: progress.block_address,
+                        block_number: progress.block_number,
+                    });
+                    Ok::<_, db::QueryError>((finalized, validated))
+                })
+            })
+            .await?;
+        Ok(Json(NodeInfo {
+            config: state.node_config,
+            versions: Versions::current(),
+            latest_finalized_block,
+            latest_validated_block,
+        }))
+    }
+
+    impl Versions {
+        /// The versions of the crates that this node was built with.
+        pub fn current() -> Self {
+            Self {
+                node: essential_node::VERSION.to_string(),
+                node_api: env!("CARGO_PKG_VERSION").to_string(),
+                node_db: db::VERSION.to_string(),
+                node_types: essential_node_types::VERSION.to_string(),
             }
-            QueryStateParams {
-                block_inclusive: Some(block),
-                block_exclusive: None,
-                solution_inclusive: None,
-                solution_exclusive: Some(solution_ix),
-            } => {
-                state
-                    .conn_pool
-                    .query_state_finalized_exclusive_solution_set(
-                        contract_ca,
-                        key,
-                        block,
-                        solution_ix,
-                    )
-                    .await?
+        }
+    }
+}
+
+/// The `list-blocks` get endpoint.
+///
+/// Takes a range of L2 blocks as a parameter.
+///
+/// The range is limited to [`ListBlocksLimits::max_range`] block numbers, and
+/// the page ends at the first change in block number after
+/// [`ListBlocksLimits::max_bytes`] have been encoded. The response is not
+/// streamed: the whole page is read and encoded into memory before it is sent,
+/// so the DB connection is released before the client receives the response,
+/// and each request buffers up to roughly `max_bytes` plus the size of the
+/// last block. In the case that the range is incomplete,
+/// the response carries a [`NEXT_CURSOR_HEADER`](list_blocks::NEXT_CURSOR_HEADER)
+/// to use as the `start` of the next request.
+///
+/// Blocks are encoded as negotiated via the `Accept` header. See
+/// [`BlockEncoding`].
+///
+/// [`ListBlocksLimits::max_range`]: crate::ListBlocksLimits::max_range
+/// [`ListBlocksLimits::max_bytes`]: crate::ListBlocksLimits::max_bytes
+pub mod list_blocks {
+    use super::*;
+    use crate::ListBlocksLimits;
+    use axum::{body::Body, http::HeaderValue};
+    use serde::Serialize;
+    use serde_json::value::RawValue;
+    use std::ops::{ControlFlow, Range};
+
+    pub const PATH: &str = "/list-blocks";
+
+    /// The response header carrying the `start` of the next page, present only
+    /// if the requested range is incomplete.
+    pub const NEXT_CURSOR_HEADER: &str = "next-cursor";
+
+    /// A page of blocks along with the cursor of the next page.
+    ///
+    /// Returned by the `list_blocks` method of the [`rpc`] endpoint. See
+    /// [`page`].
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct BlockPage {
+        /// The blocks within the page, in order of block number.
+        pub blocks: Vec<Block>,
+        /// The `start` of the next page, or `None` if the range is complete.
+        pub next_cursor: Option<Word>,
+    }
+
+    /// A page of blocks, each already encoded for the response.
+    struct EncodedPage {
+        blocks: Vec<Vec<u8>>,
+        next_cursor: Option<Word>,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Query(block_range): Query<BlockRange>,
+        encoding: BlockEncoding,
+    ) -> Result<axum::response::Response, Error> {
+        let range = block_range.start..block_range.end;
+        let limits = state.list_blocks_limits;
+        let page = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| encode_page(tx, range, limits, encoding))
+            })
+            .await??;
+
+        // Join the encoded blocks into a single buffered body.
+        let (content_type, body) = match encoding {
+            BlockEncoding::Json => {
+                let body = [b"[".to_vec()]
+                    .into_iter()
+                    .chain(page.blocks)
+                    .chain([b"]".to_vec()]);
+                ("application/json", body.collect::<Vec<_>>().concat())
             }
-            QueryStateParams {
-                block_inclusive: None,
-                block_exclusive: None,
-                solution_inclusive: None,
-                solution_exclusive: None,
-            } => {
-                state
-                    .conn_pool
-                    .query_latest_finalized_block(contract_ca, key)
-                    .await?
+            BlockEncoding::Binary => (binary::MEDIA_TYPE, page.blocks.concat()),
+        };
+        let mut response =
+            ([(header::CONTENT_TYPE, content_type)], Body::from(body)).into_response();
+        if let Some(next_cursor) = page.next_cursor {
+            response
+                .headers_mut()
+                .insert(NEXT_CURSOR_HEADER, HeaderValue::from(next_cursor));
+        }
+        Ok(response)
+    }
+
+    /// Encode a page of blocks as a JSON [`BlockPage`], applying the same
+    /// limits as the [`handler`].
+    ///
+    /// Each block is serialized once, and the byte limit applies to the
+    /// serialized blocks.
+    pub async fn page(
+        State(state): State<crate::State>,
+        Query(block_range): Query<BlockRange>,
+    ) -> Result<Box<RawValue>, Error> {
+        let range = block_range.start..block_range.end;
+        let limits = state.list_blocks_limits;
+        let page = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| encode_page(tx, range, limits, BlockEncoding::Json))
+            })
+            .await??;
+        let next_cursor = serde_json::to_vec(&page.next_cursor)?;
+        let json = [br#"{"blocks":["#.to_vec()]
+            .into_iter()
+            .chain(page.blocks)
+            .chain([br#"],"next_cursor":"#.to_vec(), next_cursor, b"}".to_vec()])
+            .collect::<Vec<_>>()
+            .concat();
+        let json = String::from_utf8(json).expect("serde_json produces valid UTF-8");
+        Ok(RawValue::from_string(json)?)
+    }
+
+    /// Encode the page of blocks within the range.
+    ///
+    /// JSON-encoded blocks after the first are prefixed with a comma, ready to
+    /// be joined into an array.
+    fn encode_page(
+        tx: &rusqlite::Transaction,
+        range: Range<Word>,
+        limits: ListBlocksLimits,
+        encoding: BlockEncoding,
+    ) -> Result<Result<EncodedPage, Error>, db::QueryError> {
+        let mut blocks = vec![];
+        let mut encode_err = None;
+        let next_cursor = visit_page(tx, range, limits, |block| {
+            let encoded = match encoding {
+                BlockEncoding::Json => {
+                    let mut json = if blocks.is_empty() {
+                        vec![]
+                    } else {
+                        vec![b',']
+                    };
+                    serde_json::to_writer(&mut json, &block)
+                        .map(|()| json)
+                        .map_err(Error::from)
+                }
+                BlockEncoding::Binary => binary::encode_frame(&block).map_err(Error::from),
+            };
+            match encoded {
+                Ok(encoded) => {
+                    let size = encoded.len();
+                    blocks.push(encoded);
+                    Some(size)
+                }
+                Err(err) => {
+                    encode_err = Some(err);
+                    None
+                }
             }
-            _ => return Err(Error::InvalidQueryParameters(params)),
+        })?;
+        Ok(match encode_err {
+            Some(err) => Err(err),
+            None => Ok(EncodedPage {
+                blocks,
+                next_cursor,
+            }),
+        })
+    }
+
+    /// Visit the blocks of the page within the given range in order.
+    ///
+    /// `f` returns the encoded size of each block, or `None` to stop early.
+    /// Returns the `next_cursor` of the page.
+    fn visit_page(
+        tx: &rusqlite::Transaction,
+        range: Range<Word>,
+        limits: ListBlocksLimits,
+        mut f: impl FnMut(Block) -> Option<usize>,
+    ) -> Result<Option<Word>, db::QueryError> {
+        let max_end = range
+            .start
+            .saturating_add(Word::try_from(limits.max_range).unwrap_or(Word::MAX));
+        let (range, mut next_cursor) = if range.end > max_end {
+            (range.start..max_end, Some(max_end))
+        } else {
+            (range, None)
         };
-        Ok(Json(value))
+        let mut written = 0;
+        let mut last_number = None;
+        db::for_each_block(tx, range, |block| {
+            // Only end the page between block numbers so that the cursor never
+            // splits the blocks sharing a number.
+            let number = block.header.number;
+            if written >= limits.max_bytes && last_number != Some(number) {
+                next_cursor = Some(number);
+                return ControlFlow::Break(());
+            }
+            last_number = Some(number);
+            match f(block) {
+                Some(size) => {
+                    written += size;
+                    ControlFlow::Continue(())
+                }
+                None => ControlFlow::Break(()),
+            }
+        })?;
+        Ok(next_cursor)
     }
 }
 
-/// The `subscribe-blocks` get endpoint.
+/// The `list-blocks-by-time` get endpoint.
 ///
-/// Produces an event for every block starting from the given block number.
-pub mod subscribe_blocks {
+/// Takes a paginated range of time as a parameter. See [`TimeRange`].
+///
+/// Pagination is applied to the blocks within the range, so each block is
+/// returned whole.
+///
+/// Blocks are encoded as negotiated via the `Accept` header. See
+/// [`BlockEncoding`].
+pub mod list_blocks_by_time {
     use super::*;
-    pub const PATH: &str = "/subscribe-blocks";
+    use std::time::Duration;
+
+    pub const PATH: &str = "/list-blocks-by-time";
+
     pub async fn handler(
         State(state): State<crate::State>,
-        Query(StartBlock { start_block }): Query<StartBlock>,
-    ) -> Sse<impl Stream<Item = Result<sse::Event, SubscriptionError>>> {
-        // The block stream.
-        let new_block = AwaitNewBlock(state.new_block.clone());
-        let blocks = state.conn_pool.subscribe_blocks(start_block, new_block);
+        Query(range): Query<TimeRange>,
+        encoding: BlockEncoding,
+    ) -> Result<Blocks, Error> {
+        let page_size = page_size(range.page_size)?;
+        // The offset `page * page_size` must not overflow.
+        let page = range.page.unwrap_or(0);
+        let max_page = i64::MAX as u64 / page_size;
+        if page > max_page {
+            return Err(Error::InvalidPage(page, max_page));
+        }
+        // Times are stored as SQLite integers, which are signed.
+        for time in [range.start, range.end] {
+            if i64::try_from(time).is_err() {
+                return Err(Error::InvalidTime(time));
+            }
+        }
+        let time_range = Duration::from_secs(range.start)..Duration::from_secs(range.end);
+        let blocks = state
+            .conn_pool
+            .list_blocks_by_time(time_range, page_size as i64, page as i64)
+            .await?;
+        Ok(Blocks { blocks, encoding })
+    }
+}
 
-        // Map the stream of blocks to SSE events.
-        let sse_events = blocks.map(|res| {
-            let block = res?;
-            let event = sse::Event::default().json_data(block)?;
-            Ok(event)
-        });
+/// The `list-failed-blocks` get endpoint.
+///
+/// Takes a range of L2 blocks as a parameter.
+///
+/// Returns each failed block number along with the address of the solution set
+/// that caused the failure.
+pub mod list_failed_blocks {
+    use super::*;
+    use serde::Serialize;
 
-        Sse::new(sse_events).keep_alive(sse::KeepAlive::default())
+    pub const PATH: &str = "/list-failed-blocks";
+
+    /// A block that failed validation.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct FailedBlock {
+        /// The number of the failed block.
+        pub block_number: Word,
+        /// The address of the solution set that caused the failure.
+        pub solution_set_address: ContentAddress,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Query(block_range): Query<BlockRange>,
+    ) -> Result<Json<Vec<FailedBlock>>, Error> {
+        let failed = state
+            .conn_pool
+            .list_failed_blocks(block_range.start..block_range.end)
+            .await?
+            .into_iter()
+            .map(|(block_number, solution_set_address)| FailedBlock {
+                block_number,
+                solution_set_address,
+            })
+            .collect();
+        Ok(Json(failed))
     }
 }
 
+/// The `list-unchecked-blocks` get endpoint.
+///
+/// Takes a range of L2 blocks as a parameter.
+///
+/// Returns the blocks within the range that are yet to be validated, i.e. those
+/// beyond the current validation progress that have not been marked as failed.
+///
+/// Blocks are encoded as negotiated via the `Accept` header. See
+/// [`BlockEncoding`].
+pub mod list_unchecked_blocks {
+    use super::*;
+
+    pub const PATH: &str = "/list-unchecked-blocks";
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Query(block_range): Query<BlockRange>,
+        encoding: BlockEncoding,
+    ) -> Result<Blocks, Error> {
+        let blocks = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| {
+                    let start = match validation_progress::query(tx)? {
+                        Some(progress) => block_range
+                            .start
+                            .max(progress.block_number.saturating_add(1)),
+                        None => block_range.start,
+                    };
+                    db::list_unchecked_blocks(tx, start..block_range.end)
+                })
+            })
+            .await?;
+        Ok(Blocks { blocks, encoding })
+    }
+}
+
+/// The `metrics` get endpoint.
+///
+/// Returns the node and API metrics in the Prometheus text exposition format.
+/// See the [`metrics`](crate::metrics) module.
+pub mod metrics {
+    use super::*;
+    use crate::metrics::{DbSnapshot, CONTENT_TYPE};
+
+    pub const PATH: &str = "/metrics";
+
+    pub async fn handler(State(state): State<crate::State>) -> Result<impl IntoResponse, Error> {
+        let snapshot = state
+            .conn_pool
+            .acquire_then(|h| db::with_tx_dropped(h, |tx| DbSnapshot::query(tx)))
+            .await?;
+        let body = crate::metrics::encode(&state, &snapshot)?;
+        Ok(([(header::CONTENT_TYPE, CONTENT_TYPE)], body))
+    }
+}
+
+/// The `validation-progress` get endpoint.
+///
+/// Returns the address and number of the last block to be successfully
+/// validated, or `None` if validation has not yet begun.
+pub mod validation_progress {
+    use super::*;
+    use serde::Serialize;
+
+    pub const PATH: &str = "/validation-progress";
+
+    /// The last block to be successfully validated.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct ValidationProgress {
+        /// The address of the last validated block.
+        pub block_address: ContentAddress,
+        /// The number of the last validated block.
+        pub block_number: Word,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+    ) -> Result<Json<Option<ValidationProgress>>, Error> {
+        let progress = state
+            .conn_pool
+            .acquire_then(|h| db::with_tx_dropped(h, |tx| query(tx)))
+            .await?;
+        Ok(Json(progress))
+    }
+
+    /// Query the validation progress along with the number of the associated block.
+    pub(super) fn query(
+        tx: &rusqlite::Transaction,
+    ) -> Result<Option<ValidationProgress>, db::QueryError> {
+        let Some(block_address) = db::get_validation_progress(tx)? else {
+            return Ok(None);
+        };
+        let Some(header) = db::get_block_header(tx, &block_address)? else {
+            return Ok(None);
+        };
+        Ok(Some(ValidationProgress {
+            block_address,
+            block_number: header.number,
+        }))
+    }
+}
+
+/// The `query-state` get endpoint.
+///
+/// Takes a contract content address and a byte array key as path parameters,
+/// both encoded as hex.
+///
+/// State may be selected by block number or by hex-encoded block address (see
+/// [`HELP_MSG`](query_state::HELP_MSG)), defaulting to the latest finalized
+/// block. Selecting by block address allows for reading state within
+/// unfinalized blocks, including those on competing forks.
+pub mod query_state {
+    use std::fmt::Display;
+
+    use serde::Serialize;
+
+    use super::*;
+
+    pub const HELP_MSG: &str = r#"
+The query parameters must be empty or one of the following combinations:
+    - block_inclusive
+    - block_exclusive
+    - block_inclusive, solution_inclusive
+    - block_inclusive, solution_exclusive
+    - block_address_inclusive
+    - block_address_exclusive
+    - block_address_inclusive, solution_inclusive
+    - block_address_inclusive, solution_exclusive
+"#;
+
+    #[derive(Deserialize, Serialize, Default, Debug)]
+    pub struct QueryStateParams {
+        pub block_inclusive: Option<Word>,
+        pub block_exclusive: Option<Word>,
+        pub block_address_inclusive: Option<String>,
+        pub block_address_exclusive: Option<String>,
+        pub solution_inclusive: Option<u64>,
+        pub solution_exclusive: Option<u64>,
+    }
+
+    impl Display for QueryStateParams {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "block_inclusive: {:?}, block_exclusive: {:?}, block_address_inclusive: {:?}, block_address_exclusive: {:?}, solution_inclusive: {:?}, solution_exclusive: {:?}",
+                self.block_inclusive, self.block_exclusive, self.block_address_inclusive, self.block_address_exclusive, self.solution_inclusive, self.solution_exclusive
+            )
+        }
+    }
+
+    pub const PATH: &str = "/query-state/:contract-ca/:key";
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path((contract_ca, key)): Path<(String, String)>,
+        Query(params): Query<QueryStateParams>,
+    ) -> Result<Json<Option<Value>>, Error> {
+        let contract_ca: ContentAddress = contract_ca.parse()?;
+        let key: Vec<u8> = hex::decode(key)?;
+        let key = key_words_from_bytes(&key);
+        // TODO: When state is compacted and blocks are discarded, this query should
+        // fall back to querying compacted state.
+        let at = state_at(params)?;
+        let value = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| {
+                    let Some(at) = resolve_state_at(tx, at)? else {
+                        return Ok(None);
+                    };
+                    query_state_at(tx, &contract_ca, &key, &at)
+                })
+            })
+            .await?;
+        Ok(Json(value))
+    }
+}
+
+/// The `query-state-batch` post endpoint.
+///
+/// Takes a JSON-serialized [`QueryStateBatch`](query_state_batch::QueryStateBatch)
+/// as the request body. All keys are read within a single transaction at the
+/// same point in state, selected with the same parameters as the `query-state`
+/// endpoint and defaulting to the latest finalized block.
+///
+/// Returns the value at each key in the order requested, along with the number
+/// of the block at which they were read.
+pub mod query_state_batch {
+    use super::*;
+    use essential_types::Key;
+    use query_state::QueryStateParams;
+    use serde::Serialize;
+
+    pub const PATH: &str = "/query-state-batch";
+
+    /// The maximum number of keys that may be requested at once.
+    pub const MAX_KEYS: usize = 1000;
+
+    /// A key within a contract's state.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct ContractKey {
+        /// The content address of the contract.
+        pub contract_ca: ContentAddress,
+        /// The key.
+        pub key: Key,
+    }
+
+    /// The `query-state-batch` request body.
+    #[derive(Debug, Deserialize, Serialize)]
+    pub struct QueryStateBatch {
+        /// The keys to read.
+        pub keys: Vec<ContractKey>,
+        /// The point in state at which to read all keys.
+        #[serde(flatten)]
+        pub params: QueryStateParams,
+    }
+
+    /// The values read by a `query-state-batch` request.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct StateBatch {
+        /// The number of the latest block whose state was read, in full or in
+        /// part. For exclusive selectors, this is the preceding block unless a
+        /// solution set after the first within the block is selected.
+        ///
+        /// `None` if the latest finalized state was requested but no block is
+        /// finalized, if the requested block address is unknown, or if state
+        /// was read before the first block.
+        pub block_number: Option<Word>,
+        /// The value at each requested key, or `None` if it has no value.
+        pub values: Vec<Option<Value>>,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Json(batch): Json<QueryStateBatch>,
+    ) -> Result<Json<StateBatch>, Error> {
+        if batch.keys.len() > MAX_KEYS {
+            return Err(Error::TooManyKeys(batch.keys.len()));
+        }
+        let at = state_at(batch.params)?;
+        let keys = batch.keys;
+        let values = state
+            .conn_pool
+            .acquire_then(move |h| db::with_tx_dropped(h, |tx| query_values(tx, &keys, at)))
+            .await?;
+        Ok(Json(values))
+    }
+
+    /// Read the value at each key within the given transaction.
+    fn query_values(
+        tx: &rusqlite::Transaction,
+        keys: &[ContractKey],
+        at: Option<StateAt>,
+    ) -> Result<StateBatch, db::QueryError> {
+        let Some(at) = resolve_state_at(tx, at)? else {
+            return Ok(StateBatch {
+                block_number: None,
+                values: vec![None; keys.len()],
+            });
+        };
+        let values = keys
+            .iter()
+            .map(|ck| query_state_at(tx, &ck.contract_ca, &ck.key, &at))
+            .collect::<Result<_, _>>()?;
+        Ok(StateBatch {
+            block_number: at.block_number(tx)?,
+            values,
+        })
+    }
+}
+
+/// The `query-state-range` get endpoint.
+///
+/// Reads `num_values` consecutive keys starting from the given key, matching
+/// the key range reads made by predicates during validation. Missing values
+/// are returned as empty.
+///
+/// Takes the same optional block and solution set parameters as the
+/// `query-state` endpoint. All values are read within a single transaction.
+pub mod query_state_range {
+    use super::*;
+    use essential_node::validate::next_key;
+    use essential_types::Key;
+    use query_state::QueryStateParams;
+
+    pub const PATH: &str = "/query-state-range/:contract-ca/:key";
+
+    /// The maximum number of values that may be requested at once.
+    pub const MAX_NUM_VALUES: usize = 1000;
+
+    /// The number of values to read.
+    #[derive(Deserialize)]
+    pub struct NumValues {
+        pub num_values: usize,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path((contract_ca, key)): Path<(String, String)>,
+        Query(NumValues { num_values }): Query<NumValues>,
+        Query(params): Query<QueryStateParams>,
+    ) -> Result<Json<Vec<Value>>, Error> {
+        let contract_ca: ContentAddress = contract_ca.parse()?;
+        let key: Vec<u8> = hex::decode(key)?;
+        let key = key_words_from_bytes(&key);
+        if num_values > MAX_NUM_VALUES {
+            return Err(Error::InvalidNumValues(num_values));
+        }
+        let keys = key_range(key, num_values).ok_or(Error::KeyRangeOverflow)?;
+        let at = state_at(params)?;
+        let values = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| query_values(tx, &contract_ca, &keys, at))
+            })
+            .await?;
+        Ok(Json(values))
+    }
+
+    /// Collect `num_values` consecutive keys starting from `key`.
+    fn key_range(key: Key, num_values: usize) -> Option<Vec<Key>> {
+        let mut keys = Vec::with_capacity(num_values);
+        let mut next = Some(key);
+        for _ in 0..num_values {
+            let key = next?;
+            next = next_key(key.clone());
+            keys.push(key);
+        }
+        Some(keys)
+    }
+
+    /// Read the value at each key within the given transaction.
+    fn query_values(
+        tx: &rusqlite::Transaction,
+        contract_ca: &ContentAddress,
+        keys: &[Key],
+        at: Option<StateAt>,
+    ) -> Result<Vec<Value>, db::QueryError> {
+        let Some(at) = resolve_state_at(tx, at)? else {
+            return Ok(vec![vec![]; keys.len()]);
+        };
+        keys.iter()
+            .map(|key| Ok(query_state_at(tx, contract_ca, key, &at)?.unwrap_or_default()))
+            .collect()
+    }
+}
+
+/// The `query-state-prefix` get endpoint.
+///
+/// Lists the `(key, value)` pairs of a contract's state whose keys start with
+/// the given hex-encoded word `prefix`, ordered by key. The prefix defaults to
+/// empty, listing all keys.
+///
+/// Takes optional `prefix`, `block_inclusive`, `cursor` and `page_size` query
+/// parameters.
+/// State is read as of the given block, defaulting to the latest finalized
+/// block. The `cursor` is the hex-encoded key after which to start listing,
+/// as returned by the previous page.
+pub mod query_state_prefix {
+    use super::*;
+    use essential_types::Key;
+    use serde::Serialize;
+
+    pub const PATH: &str = "/query-state-prefix/:contract-ca";
+
+    /// The query parameters for the `query-state-prefix` endpoint.
+    #[derive(Debug, Default, Deserialize)]
+    pub struct QueryStatePrefixParams {
+        /// The hex-encoded key prefix. Defaults to empty.
+        pub prefix: Option<String>,
+        /// The block at which to read state (inclusive).
+        pub block_inclusive: Option<Word>,
+        /// The hex-encoded key after which to start listing (exclusive).
+        pub cursor: Option<String>,
+        /// The maximum number of entries to return.
+        ///
+        /// Defaults to [`MAX_PAGE_SIZE`].
+        pub page_size: Option<u64>,
+    }
+
+    /// A single key and its value.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct StateEntry {
+        /// The state key.
+        pub key: Key,
+        /// The value at the key.
+        pub value: Value,
+    }
+
+    /// A page of state entries.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct StatePage {
+        /// The entries within the page, ordered by key.
+        pub entries: Vec<StateEntry>,
+        /// The cursor for the next page, or `None` if this is the last page.
+        pub next_cursor: Option<String>,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path(contract_ca): Path<String>,
+        Query(params): Query<QueryStatePrefixParams>,
+    ) -> Result<Json<StatePage>, Error> {
+        let contract_ca: ContentAddress = contract_ca.parse()?;
+        let prefix = match params.prefix {
+            Some(prefix) => key_words_from_bytes(&hex::decode(prefix)?),
+            None => vec![],
+        };
+        let cursor = match params.cursor {
+            Some(cursor) => Some(key_words_from_bytes(&hex::decode(cursor)?)),
+            None => None,
+        };
+        let page_size = page_size(params.page_size)?;
+        let block = params.block_inclusive;
+        let entries = state
+            .conn_pool
+            .acquire_then(move |h| {
+                db::with_tx_dropped(h, |tx| {
+                    let block = match block {
+                        Some(block) => block,
+                        None => match latest_finalized_block_number(tx)? {
+                            Some(block) => block,
+                            None => return Ok(vec![]),
+                        },
+                    };
+                    db::finalized::query_state_prefix_inclusive_block(
+                        tx,
+                        &contract_ca,
+                        &prefix,
+                        block,
+                        cursor.as_ref(),
+                        page_size as i64,
+                    )
+                })
+            })
+            .await?;
+        let next_cursor = match entries.last() {
+            Some((key, _)) if entries.len() as u64 == page_size => Some(hex::encode(
+                key.iter()
+                    .copied()
+                    .flat_map(bytes_from_word)
+                    .collect::<Vec<_>>(),
+            )),
+            _ => None,
+        };
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| StateEntry { key, value })
+            .collect();
+        Ok(Json(StatePage {
+            entries,
+            next_cursor,
+        }))
+    }
+}
+
+/// The `ready` get endpoint.
+///
+/// Reports whether the node is ready to serve traffic. Unlike the
+/// [`health_check`], this checks that a DB connection may be acquired and
+/// queried, that the relayer and validation streams are still running, that the
+/// relayer has made progress within the
+/// [`max_relayer_staleness`](crate::State::max_relayer_staleness), and that the
+/// number of synced blocks awaiting validation is within the
+/// [`max_validation_lag`](crate::State::max_validation_lag).
+///
+/// Responds with a [`Readiness`](ready::Readiness), with status `200 OK` if no
+/// check failed or `503 Service Unavailable` otherwise.
+pub mod ready {
+    use super::*;
+    use crate::metrics::DbSnapshot;
+    use axum::http::StatusCode;
+    use serde::Serialize;
+    use std::time::Duration;
+
+    pub const PATH: &str = "/ready";
+
+    /// The maximum duration to await a DB connection before failing the check.
+    pub const DB_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// The readiness of the node along with the outcome of each check.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct Readiness {
+        /// Whether the node is ready, i.e. no check failed.
+        pub ready: bool,
+        /// Whether a DB connection could be acquired and queried.
+        pub db: Check,
+        /// Whether the relayer stream is still running and has made progress
+        /// within the maximum staleness.
+        pub relayer: Check,
+        /// Whether the validation stream is still running.
+        pub validation: Check,
+        /// Whether the validation lag is within the maximum.
+        pub validation_lag: Check,
+        /// The number of synced blocks awaiting validation, if known.
+        pub lag_blocks: Option<u64>,
+        /// The maximum number of synced blocks that may await validation.
+        pub max_lag_blocks: u64,
+        /// The seconds elapsed since the relayer last made progress, if the
+        /// relayer is run.
+        pub relayer_staleness_secs: Option<u64>,
+        /// The maximum seconds the relayer may go without making progress.
+        pub max_relayer_staleness_secs: u64,
+    }
+
+    /// The outcome of a single readiness check.
+    #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Check {
+        /// The check passed.
+        Pass,
+        /// The check failed.
+        Fail,
+        /// The check does not apply, e.g. the stream is not run by this node.
+        Skip,
+    }
+
+    pub async fn handler(State(state): State<crate::State>) -> (StatusCode, Json<Readiness>) {
+        let query = state
+            .conn_pool
+            .acquire_then(|h| db::with_tx_dropped(h, |tx| DbSnapshot::query(tx)));
+        let snapshot = tokio::time::timeout(DB_TIMEOUT, query)
+            .await
+            .ok()
+            .and_then(Result::ok);
+
+        let liveness = state.node_liveness.as_ref();
+        let relayer_staleness = liveness.and_then(|l| l.relayer_since_progress());
+        let relayer = liveness.and_then(|l| l.relayer()).map(|alive| {
+            alive && relayer_staleness.is_some_and(|s| s <= state.max_relayer_staleness)
+        });
+        let validation = liveness.and_then(|l| l.validation());
+        // Validation lag only applies if the node validates blocks.
+        let validates = liveness.is_none() || validation.is_some();
+        let lag_blocks = snapshot
+            .as_ref()
+            .filter(|_| validates)
+            .map(DbSnapshot::validation_lag);
+
+        let readiness = Readiness {
+            ready: false,
+            db: Check::from(Some(snapshot.is_some())),
+            relayer: Check::from(relayer),
+            validation: Check::from(validation),
+            validation_lag: Check::from(lag_blocks.map(|lag| lag <= state.max_validation_lag)),
+            lag_blocks,
+            max_lag_blocks: state.max_validation_lag,
+            relayer_staleness_secs: relayer_staleness.map(|s| s.as_secs()),
+            max_relayer_staleness_secs: state.max_relayer_staleness.as_secs(),
+        };
+        let checks = [
+            readiness.db,
+            readiness.relayer,
+            readiness.validation,
+            readiness.validation_lag,
+        ];
+        let ready = !checks.contains(&Check::Fail);
+        let status = if ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        (status, Json(Readiness { ready, ..readiness }))
+    }
+
+    impl From<Option<bool>> for Check {
+        fn from(pass: Option<bool>) -> Self {
+            match pass {
+                Some(true) => Self::Pass,
+                Some(false) => Self::Fail,
+                None => Self::Skip,
+            }
+        }
+    }
+}
+
+/// The `rpc` post endpoint.
+///
+/// A JSON-RPC 2.0 facade over the request-response endpoints. Takes a single
+/// [`Request`](rpc::Request) or a batch of requests as the body, and returns
+/// the corresponding response or batch of responses. Notifications (requests
+/// without an `id`) produce no response.
+///
+/// Each method takes its parameters by name, using the same names and formats
+/// as the path and query parameters of the endpoint it mirrors. See
+/// [`METHODS`](rpc::METHODS) for the supported methods.
+///
+/// Batches may contain at most [`MAX_BATCH_SIZE`](rpc::MAX_BATCH_SIZE) requests,
+/// of which at most [`MAX_BATCH_CONCURRENCY`](rpc::MAX_BATCH_CONCURRENCY) are
+/// handled at once. Each request within a batch counts toward the client's rate
+/// limit, and those beyond it fail with [`RATE_LIMITED`](rpc::RATE_LIMITED).
+pub mod rpc {
+    use super::*;
+    use crate::auth;
+    use axum::{
+        body::Bytes,
+        extract::ConnectInfo,
+        http::{HeaderMap, StatusCode},
+        Extension,
+    };
+    use essential_types::solution::SolutionSet;
+    use serde::{de::DeserializeOwned, Deserializer, Serialize};
+    use serde_json::{value::RawValue, Value as JsonValue};
+    use std::net::{IpAddr, SocketAddr};
+
+    pub const PATH: &str = "/rpc";
+
+    /// The JSON-RPC protocol version.
+    pub const VERSION: &str = "2.0";
+
+    /// The maximum number of requests within a batch.
+    pub const MAX_BATCH_SIZE: usize = 100;
+
+    /// The maximum number of requests within a batch handled concurrently.
+    pub const MAX_BATCH_CONCURRENCY: usize = 8;
+
+    /// The supported methods, each named after the endpoint it mirrors.
+    pub const METHODS: &[&str] = &[
+        "get_block",
+        "get_contract",
+        "get_predicate",
+        "get_program",
+        "get_solution_set",
+        "info",
+        "list_blocks",
+        "list_blocks_by_time",
+        "list_failed_blocks",
+        "list_unchecked_blocks",
+        "query_state",
+        "query_state_batch",
+        "query_state_prefix",
+        "query_state_range",
+        "validate_block",
+        "validate_solution_set",
+        "validation_progress",
+    ];
+
+    /// Invalid JSON was received.
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The JSON sent is not a valid request object.
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// The method does not exist.
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// Invalid method parameters.
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// An internal error occurred while handling the request.
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// The request's bearer token does not grant the scope required by the method.
+    pub const UNAUTHORIZED: i64 = -32001;
+    /// The client exceeded its rate limit.
+    pub const RATE_LIMITED: i64 = -32002;
+
+    /// A JSON-RPC request.
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    pub struct Request {
+        /// Must be exactly [`VERSION`].
+        pub jsonrpc: String,
+        /// The name of the method to call.
+        pub method: String,
+        /// The method parameters, by name.
+        #[serde(default, skip_serializing_if = "JsonValue::is_null")]
+        pub params: JsonValue,
+        /// The request ID, or `None` for a notification.
+        #[serde(
+            default,
+            deserialize_with = "present",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub id: Option<JsonValue>,
+    }
+
+    /// A JSON-RPC response.
+    ///
+    /// Exactly one of `result` or `error` is present.
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct Response {
+        /// Always [`VERSION`].
+        pub jsonrpc: String,
+        /// The serialized result of a successful call.
+        ///
+        /// Results are serialized once by the method and embedded as is.
+        #[serde(
+            default,
+            deserialize_with = "present",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub result: Option<Box<RawValue>>,
+        /// The error of a failed call.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub error: Option<RpcError>,
+        /// The ID of the request, or `null` if it could not be determined.
+        pub id: JsonValue,
+    }
+
+    /// A JSON-RPC error object.
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    pub struct RpcError {
+        /// The error code.
+        pub code: i64,
+        /// A short description of the error.
+        pub message: String,
+    }
+
+    /// Parameters for `get_block`.
+    #[derive(Deserialize)]
+    struct BlockAddress {
+        block_address: String,
+    }
+
+    /// Parameters for `get_contract`.
+    #[derive(Deserialize)]
+    struct ContractCa {
+        contract_ca: String,
+    }
+
+    /// Parameters for `get_predicate`.
+    #[derive(Deserialize)]
+    struct PredicateCa {
+        predicate_ca: String,
+    }
+
+    /// Parameters for `get_program`.
+    #[derive(Deserialize)]
+    struct ProgramCa {
+        program_ca: String,
+    }
+
+    /// Parameters for `get_solution_set`.
+    #[derive(Deserialize)]
+    struct SolutionSetCa {
+        solution_set_ca: String,
+    }
+
+    /// Parameters for `query_state`.
+    #[derive(Deserialize)]
+    struct QueryState {
+        contract_ca: String,
+        key: String,
+        #[serde(flatten)]
+        params: query_state::QueryStateParams,
+    }
+
+    /// Parameters for `query_state_range`.
+    #[derive(Deserialize)]
+    struct QueryStateRange {
+        contract_ca: String,
+        key: String,
+        #[serde(flatten)]
+        num_values: query_state_range::NumValues,
+        #[serde(flatten)]
+        params: query_state::QueryStateParams,
+    }
+
+    /// Parameters for `query_state_prefix`.
+    #[derive(Deserialize)]
+    struct QueryStatePrefix {
+        contract_ca: String,
+        #[serde(flatten)]
+        params: query_state_prefix::QueryStatePrefixParams,
+    }
+
+    /// Parameters for `validate_block`.
+    #[derive(Deserialize)]
+    struct ValidateBlock {
+        block: Block,
+    }
+
+    /// Parameters for `validate_solution_set`.
+    #[derive(Deserialize)]
+    struct ValidateSolutionSet {
+        solution_set: SolutionSet,
+    }
+
+    impl Response {
+        fn new(id: JsonValue, result: Result<Box<RawValue>, RpcError>) -> Self {
+            let (result, error) = match result {
+                Ok(result) => (Some(result), None),
+                Err(error) => (None, Some(error)),
+            };
+            Self {
+                jsonrpc: VERSION.to_string(),
+                result,
+                error,
+                id,
+            }
+        }
+    }
+
+    impl RpcError {
+        fn new(code: i64, message: impl Into<String>) -> Self {
+            let message = message.into();
+            Self { code, message }
+        }
+    }
+
+    impl From<Error> for RpcError {
+        fn from(err: Error) -> Self {
+            let code = match err {
+                Error::HexDecode(_)
+                | Error::InvalidQueryParameters(_)
+                | Error::InvalidPageSize(_)
+                | Error::InvalidTime(_)
+                | Error::InvalidPage(..)
+                | Error::InvalidNumValues(_)
+                | Error::KeyRangeOverflow
+                | Error::TooManyKeys(_) => INVALID_PARAMS,
+                Error::ConnPoolQuery(_)
+                | Error::DecodePredicate(_)
+                | Error::DecodeProgram(_)
+                | Error::InvalidContractEntry(_)
+                | Error::Validation(_)
+                | Error::EncodeMetrics(_)
+                | Error::EncodeBlock(_)
+                | Error::EncodeBlockJson(_) => INTERNAL_ERROR,
+            };
+            Self::new(code, err.to_string())
+        }
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        grant: Option<Extension<auth::Grant>>,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> axum::response::Response {
+        let grant = grant.map(|Extension(grant)| grant);
+        let remote_addr = connect_info.map(|ConnectInfo(addr)| addr);
+        let client = state.limiter.client(&headers, remote_addr);
+        let body: JsonValue = match serde_json::from_slice(&body) {
+            Ok(body) => body,
+            Err(err) => {
+                let error = RpcError::new(PARSE_ERROR, err.to_string());
+                return Json(Response::new(JsonValue::Null, Err(error))).into_response();
+            }
+        };
+        match body {
+            JsonValue::Array(batch) if batch.len() > MAX_BATCH_SIZE => {
+                let msg = format!("batch of {} exceeds {MAX_BATCH_SIZE} requests", batch.len());
+                let error = RpcError::new(INVALID_REQUEST, msg);
+                Json(Response::new(JsonValue::Null, Err(error))).into_response()
+            }
+            JsonValue::Array(batch) if !batch.is_empty() => {
+                // Each request beyond the first, which was charged by the
+                // limits middleware, is charged to the client's rate limit.
+                let calls = batch.into_iter().enumerate().map(|(ix, req)| {
+                    let charge = client.filter(|_| ix > 0);
+                    handle(state.clone(), grant.as_ref(), charge, req)
+                });
+                let responses: Vec<_> = futures::stream::iter(calls)
+                    .buffered(MAX_BATCH_CONCURRENCY)
+                    .filter_map(futures::future::ready)
+                    .collect()
+                    .await;
+                // A batch of only notifications produces no response.
+                if responses.is_empty() {
+                    return StatusCode::NO_CONTENT.into_response();
+                }
+                Json(responses).into_response()
+            }
+            req => match handle(state, grant.as_ref(), None, req).await {
+                Some(response) => Json(response).into_response(),
+                None => StatusCode::NO_CONTENT.into_response(),
+            },
+        }
+    }
+
+    /// Handle a single request, returning `None` for notifications.
+    ///
+    /// If a `charge` client is given, the request is first charged to its rate
+    /// limit.
+    async fn handle(
+        state: crate::State,
+        grant: Option<&auth::Grant>,
+        charge: Option<IpAddr>,
+        req: JsonValue,
+    ) -> Option<Response> {
+        let req = match serde_json::from_value::<Request>(req) {
+            Ok(req) if req.jsonrpc == VERSION => req,
+            Ok(req) => {
+                let msg = format!("unsupported JSON-RPC version {:?}", req.jsonrpc);
+                let error = RpcError::new(INVALID_REQUEST, msg);
+                return Some(Response::new(req.id.unwrap_or_default(), Err(error)));
+            }
+            Err(err) => {
+                let error = RpcError::new(INVALID_REQUEST, err.to_string());
+                return Some(Response::new(JsonValue::Null, Err(error)));
+            }
+        };
+        let result = match charge.map(|ip| state.limiter.check_rate(ip)) {
+            Some(Err(err)) => Err(RpcError::new(RATE_LIMITED, err.to_string())),
+            _ => match authorize(&state, grant, &req.method) {
+                Ok(()) => call(state, &req.method, req.params).await,
+                Err(error) => Err(error),
+            },
+        };
+        req.id.map(|id| Response::new(id, result))
+    }
+
+    /// Check that the grant permits calling the given method.
+    fn authorize(
+        state: &crate::State,
+        grant: Option<&auth::Grant>,
+        method: &str,
+    ) -> Result<(), RpcError> {
+        let (Some(auth), Some(scope)) = (&state.auth, required_scope(method)) else {
+            return Ok(());
+        };
+        if auth.permits(grant, scope) {
+            return Ok(());
+        }
+        let msg = format!("method {method:?} requires the {scope:?} scope");
+        Err(RpcError::new(UNAUTHORIZED, msg))
+    }
+
+    /// The scope required to call the given method, matching that of the
+    /// endpoint it mirrors.
+    fn required_scope(method: &str) -> Option<auth::Scope> {
+        match method {
+            "validate_block" => auth::required_scope(validate_block::PATH),
+            "validate_solution_set" => auth::required_scope(validate_solution_set::PATH),
+            _ => auth::required_scope(PATH),
+        }
+    }
+
+    /// Call the method with the given parameters.
+    async fn call(
+        state: crate::State,
+        method: &str,
+        params: JsonValue,
+    ) -> Result<Box<RawValue>, RpcError> {
+        let state = State(state);
+        match method {
+            "get_block" => {
+                let BlockAddress { block_address } = params_from(params)?;
+                result(get_block::handler(state, Path(block_address)).await)
+            }
+            "get_contract" => {
+                let ContractCa { contract_ca } = params_from(params)?;
+                result(get_contract::handler(state, Path(contract_ca)).await)
+            }
+            "get_predicate" => {
+                let PredicateCa { predicate_ca } = params_from(params)?;
+                result(get_predicate::handler(state, Path(predicate_ca)).await)
+            }
+            "get_program" => {
+                let ProgramCa { program_ca } = params_from(params)?;
+                result(get_program::handler(state, Path(program_ca)).await)
+            }
+            "get_solution_set" => {
+                let SolutionSetCa { solution_set_ca } = params_from(params)?;
+                result(get_solution_set::handler(state, Path(solution_set_ca)).await)
+            }
+            "info" => result(info::handler(state).await),
+            "list_blocks" => {
+                let range = params_from(params)?;
+                Ok(list_blocks::page(state, Query(range)).await?)
+            }
+            "list_blocks_by_time" => {
+                let range = params_from(params)?;
+                let res =
+                    list_blocks_by_time::handler(state, Query(range), BlockEncoding::Json).await;
+                result(res.map(|Blocks { blocks, .. }| Json(blocks)))
+            }
+            "list_failed_blocks" => {
+                let range = params_from(params)?;
+                result(list_failed_blocks::handler(state, Query(range)).await)
+            }
+            "list_unchecked_blocks" => {
+                let range = params_from(params)?;
+                let res =
+                    list_unchecked_blocks::handler(state, Query(range), BlockEncoding::Json).await;
+                result(res.map(|Blocks { blocks, .. }| Json(blocks)))
+            }
+            "query_state" => {
+                let QueryState {
+                    contract_ca,
+                    key,
+                    params,
+                } = params_from(params)?;
+                let path = Path((contract_ca, key));
+                result(query_state::handler(state, path, Query(params)).await)
+            }
+            "query_state_batch" => {
+                let batch = params_from(params)?;
+                result(query_state_batch::handler(state, Json(batch)).await)
+            }
+            "query_state_prefix" => {
+                let QueryStatePrefix {
+                    contract_ca,
+                    params,
+                } = params_from(params)?;
+                let path = Path(contract_ca);
+                result(query_state_prefix::handler(state, path, Query(params)).await)
+            }
+            "query_state_range" => {
+                let QueryStateRange {
+                    contract_ca,
+                    key,
+                    num_values,
+                    params,
+                } = params_from(params)?;
+                let path = Path((contract_ca, key));
+                let res = query_state_range::handler(state, path, Query(num_values), Query(params));
+                result(res.await)
+            }
+            "validate_block" => {
+                let ValidateBlock { block } = params_from(params)?;
+                result(validate_block::handler(state, Json(block)).await)
+            }
+            "validate_solution_set" => {
+                let ValidateSolutionSet { solution_set } = params_from(params)?;
+                result(validate_solution_set::handler(state, Json(solution_set)).await)
+            }
+            "validation_progress" => result(validation_progress::handler(state).await),
+            _ => {
+                let msg = format!("method {method:?} not found");
+                Err(RpcError::new(METHOD_NOT_FOUND, msg))
+            }
+        }
+    }
+
+    /// Deserialize the named method parameters.
+    fn params_from<T: DeserializeOwned>(params: JsonValue) -> Result<T, RpcError> {
+        serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+    }
+
+    /// Convert the result of an endpoint handler to a JSON-RPC result.
+    fn result<T: Serialize>(res: Result<Json<T>, Error>) -> Result<Box<RawValue>, RpcError> {
+        let Json(value) = res?;
+        serde_json::value::to_raw_value(&value)
+            .map_err(|err| RpcError::new(INTERNAL_ERROR, err.to_string()))
+    }
+
+    /// Distinguishes a present `null` from an absent field.
+    fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+        d: D,
+    ) -> Result<Option<T>, D::Error> {
+        T::deserialize(d).map(Some)
+    }
+}
+
+/// The `subscribe-blocks` get endpoint.
+///
+/// Produces an event for every block starting from the given block number.
+///
+/// If the client accepts the binary [`MEDIA_TYPE`](binary::MEDIA_TYPE), the
+/// response is instead a stream of length-prefixed binary frames, one per
+/// block, interleaved with empty keep-alive frames while idle. See
+/// [`BlockEncoding`].
+pub mod subscribe_blocks {
+    use super::*;
+    use std::time::Duration;
+
+    pub const PATH: &str = "/subscribe-blocks";
+
+    /// The idle time after which a keep-alive frame is sent on binary streams.
+    pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Query(StartBlock { start_block }): Query<StartBlock>,
+        encoding: BlockEncoding,
+        shutdown: Shutdown,
+    ) -> axum::response::Response {
+        // The block stream.
+        let new_block = AwaitNewBlock(state.new_block.clone());
+        let blocks = state.conn_pool.subscribe_blocks(start_block, new_block);
+
+        match encoding {
+            // Map the stream of blocks to SSE events.
+            BlockEncoding::Json => {
+                let sse_events = blocks.map(|res| {
+                    let block = res?;
+                    let event = sse::Event::default().json_data(block)?;
+                    Ok::<_, SubscriptionError>(event)
+                });
+                Sse::new(close_on_shutdown(sse_events, shutdown))
+                    .keep_alive(sse::KeepAlive::default())
+                    .into_response()
+            }
+            // Map the stream of blocks to binary frames.
+            BlockEncoding::Binary => {
+                let frames = blocks.map(|res| {
+                    let frame = binary::encode_frame(&res?)?;
+                    Ok::<_, SubscriptionError>(frame)
+                });
+                let frames = with_keep_alive(until_shutdown(frames, shutdown));
+                let body = axum::body::Body::from_stream(frames);
+                ([(header::CONTENT_TYPE, binary::MEDIA_TYPE)], body).into_response()
+            }
+        }
+    }
+
+    /// Send a keep-alive frame whenever no frame is sent within the
+    /// [`KEEP_ALIVE_INTERVAL`], ending with the given stream.
+    fn with_keep_alive<E>(
+        frames: impl Stream<Item = Result<Vec<u8>, E>>,
+    ) -> impl Stream<Item = Result<Vec<u8>, E>> {
+        futures::stream::unfold(Box::pin(frames), |mut frames| async move {
+            let frame = match tokio::time::timeout(KEEP_ALIVE_INTERVAL, frames.next()).await {
+                Ok(frame) => frame?,
+                Err(_elapsed) => Ok(binary::KEEP_ALIVE_FRAME.to_vec()),
+            };
+            Some((frame, frames))
+        })
+    }
+}
+
+/// The `subscribe-contract-solutions` get endpoint.
+///
+/// Takes a comma-separated list of contract content addresses (encoded as hex)
+/// along with the block number to start from.
+///
+/// Produces an event for every solution that solves a predicate of one of the
+/// given contracts within finalized blocks, starting from the given block number.
+pub mod subscribe_contract_solutions {
+    use super::*;
+    use essential_types::solution::Solution;
+    use serde::Serialize;
+
+    pub const PATH: &str = "/subscribe-contract-solutions";
+
+    /// The query parameters for the `subscribe-contract-solutions` endpoint.
+    #[derive(Deserialize)]
+    pub struct Params {
+        /// Comma-separated contract content addresses.
+        pub contracts: String,
+        /// The block number to start from.
+        pub start_block: Word,
+    }
+
+    /// A solution along with its location within the chain.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct ContractSolution {
+        /// The number of the block containing the solution.
+        pub block_number: Word,
+        /// The address of the block containing the solution.
+        pub block_address: ContentAddress,
+        /// The index of the solution set containing the solution within the block.
+        pub solution_set_index: u64,
+        /// The solution.
+        pub solution: Solution,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Query(params): Query<Params>,
+        shutdown: Shutdown,
+    ) -> Result<Sse<impl Stream<Item = Result<sse::Event, SubscriptionError>>>, Error> {
+        let contracts = params
+            .contracts
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<ContentAddress>, _>>()?;
+
+        // The solution stream.
+        let new_block = AwaitNewBlock(state.new_block.clone());
+        let solutions =
+            state
+                .conn_pool
+                .subscribe_contract_solutions(contracts, params.start_block, new_block);
+
+        // Map the stream of solutions to SSE events.
+        let sse_events = solutions.map(|res| {
+            let (block_number, block_address, solution_set_index, solution) = res?;
+            let solution = ContractSolution {
+                block_number,
+                block_address,
+                solution_set_index,
+                solution,
+            };
+            let event = sse::Event::default().json_data(solution)?;
+            Ok(event)
+        });
+
+        let sse_events = close_on_shutdown(sse_events, shutdown);
+        Ok(Sse::new(sse_events).keep_alive(sse::KeepAlive::default()))
+    }
+}
+
+/// The `subscribe-state` get endpoint.
+///
+/// Takes a contract content address and a key (both encoded as hex) as path
+/// parameters, along with the block number to start from.
+///
+/// Produces an event for every mutation of the key within finalized blocks,
+/// starting from the given block number.
+pub mod subscribe_state {
+    use super::*;
+    use serde::Serialize;
+
+    pub const PATH: &str = "/subscribe-state/:contract-ca/:key";
+
+    /// A change to the value at a key.
+    #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+    pub struct StateChange {
+        /// The number of the block in which the key was mutated.
+        pub block_number: Word,
+        /// The index of the solution set within the block that mutated the key.
+        pub solution_set_index: u64,
+        /// The new value at the key.
+        pub value: Value,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Path((contract_ca, key)): Path<(String, String)>,
+        Query(StartBlock { start_block }): Query<StartBlock>,
+        shutdown: Shutdown,
+    ) -> Result<Sse<impl Stream<Item = Result<sse::Event, SubscriptionError>>>, Error> {
+        let contract_ca: ContentAddress = contract_ca.parse()?;
+        let key = key_words_from_bytes(&hex::decode(key)?);
+
+        // The state change stream.
+        let new_block = AwaitNewBlock(state.new_block.clone());
+        let changes = state
+            .conn_pool
+            .subscribe_state(contract_ca, key, start_block, new_block);
+
+        // Map the stream of state changes to SSE events.
+        let sse_events = changes.map(|res| {
+            let (block_number, solution_set_index, value) = res?;
+            let change = StateChange {
+                block_number,
+                solution_set_index,
+                value,
+            };
+            let event = sse::Event::default().json_data(change)?;
+            Ok(event)
+        });
+
+        let sse_events = close_on_shutdown(sse_events, shutdown);
+        Ok(Sse::new(sse_events).keep_alive(sse::KeepAlive::default()))
+    }
+}
+
+/// The `subscribe-validation` get endpoint.
+///
+/// Produces an event with the outcome of every block validated by the node
+/// from the time of subscribing. If the subscriber falls too far behind, the
+/// missed outcomes are skipped and a [`LAGGED_EVENT`] reports how many.
+pub mod subscribe_validation {
+    use super::*;
+
+    pub const PATH: &str = "/subscribe-validation";
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        shutdown: Shutdown,
+    ) -> Sse<impl Stream<Item = Result<sse::Event, SubscriptionError>>> {
+        let validated = validated_blocks(state.validated_block);
+
+        // Map the stream of outcomes to SSE events.
+        let sse_events = validated.map(|validated| {
+            let event = match validated {
+                Validated::Block(validated) => sse::Event::default().json_data(&*validated)?,
+                Validated::Lagged(lagged) => sse::Event::default()
+                    .event(LAGGED_EVENT)
+                    .json_data(lagged)?,
+            };
+            Ok(event)
+        });
+
+        let sse_events = close_on_shutdown(sse_events, shutdown);
+        Sse::new(sse_events).keep_alive(sse::KeepAlive::default())
+    }
+}
+
+/// The `subscribe-ws` WebSocket endpoint.
+///
+/// Multiplexes any number of block, state and validation subscriptions over a
+/// single WebSocket connection. Clients send JSON-serialized
+/// [`Request`](subscribe_ws::Request)s as text messages, each carrying a
+/// client-chosen ID. Every JSON-serialized [`Response`](subscribe_ws::Response)
+/// produced for a subscription carries the ID it was opened with.
+///
+/// WebSocket upgrades require an HTTP/1.1 connection. Requests over HTTP/2 are
+/// refused with `505 HTTP Version Not Supported`.
+///
+/// Each subscription opened over the connection counts toward the client's
+/// rate limit and cap of concurrent subscriptions (see [`crate::limits`]).
+/// Each connection may have at most [`MAX_SUBSCRIPTIONS`](subscribe_ws::MAX_SUBSCRIPTIONS)
+/// open subscriptions, and messages larger than
+/// [`MAX_MESSAGE_SIZE`](subscribe_ws::MAX_MESSAGE_SIZE) close the connection.
+///
+/// Upon shutdown, the server closes the connection with a close frame.
+pub mod subscribe_ws {
+    use super::*;
+    use crate::limits::SubscriptionPermit;
+    use axum::{
+        extract::{
+            ws::{
+                close_code, rejection::WebSocketUpgradeRejection, CloseFrame, Message, WebSocket,
+                WebSocketUpgrade,
+            },
+            ConnectInfo,
+        },
+        http::{HeaderMap, StatusCode, Version},
+    };
+    use essential_types::Key;
+    use futures::stream::{AbortHandle, Abortable, BoxStream, SelectAll};
+    use serde::Serialize;
+    use std::{
+        collections::HashMap,
+        net::{IpAddr, SocketAddr},
+        sync::Arc,
+    };
+    use subscribe_state::StateChange;
+
+    pub const PATH: &str = "/subscribe-ws";
+
+    /// The maximum number of subscriptions open at once over a single connection.
+    pub const MAX_SUBSCRIPTIONS: usize = 64;
+
+    /// The maximum size in bytes of a message sent by the client.
+    pub const MAX_MESSAGE_SIZE: usize = 16 * 1024;
+
+    /// A client-chosen ID, unique among the subscriptions of a connection.
+    pub type RequestId = u64;
+
+    /// A request sent by the client.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    pub enum Request {
+        /// Open a new subscription with the given ID.
+        Subscribe {
+            /// The ID of the new subscription.
+            id: RequestId,
+            /// What to subscribe to.
+            subscription: Subscription,
+        },
+        /// Close the subscription with the given ID.
+        Unsubscribe {
+            /// The ID of the subscription to close.
+            id: RequestId,
+        },
+    }
+
+    /// The kinds of subscription available.
+    #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+    #[serde(tag = "kind", rename_all = "snake_case")]
+    pub enum Subscription {
+        /// Every block from the given block number, as with `subscribe-blocks`.
+        Blocks {
+            /// The block number to start from.
+            start_block: Word,
+        },
+        /// Every change to a key within finalized blocks from the given block
+        /// number, as with `subscribe-state`.
+        State {
+            /// The content address of the contract.
+            contract_ca: ContentAddress,
+            /// The key to watch.
+            key: Key,
+            /// The block number to start from.
+            start_block: Word,
+        },
+        /// The outcome of every block validated from the time of subscribing,
+        /// as with `subscribe-validation`.
+        Validation,
+    }
+
+    /// A message sent by the server.
+    #[derive(Debug, Serialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    pub enum Response {
+        /// The subscription was opened.
+        Subscribed {
+            /// The ID of the subscription.
+            id: RequestId,
+        },
+        /// The subscription was closed at the client's request.
+        Unsubscribed {
+            /// The ID of the subscription.
+            id: RequestId,
+        },
+        /// The subscription ended and will produce no more messages.
+        Ended {
+            /// The ID of the subscription.
+            id: RequestId,
+        },
+        /// A block produced by a `blocks` subscription.
+        Block {
+            /// The ID of the subscription.
+            id: RequestId,
+            /// The block.
+            block: Block,
+        },
+        /// A change produced by a `state` subscription.
+        StateChange {
+            /// The ID of the subscription.
+            id: RequestId,
+            /// The change to the key.
+            change: StateChange,
+        },
+        /// An outcome produced by a `validation` subscription.
+        Validation {
+            /// The ID of the subscription.
+            id: RequestId,
+            /// The validated block and its outcome.
+            validated_block: Arc<ValidatedBlock>,
+        },
+        /// A `validation` subscription fell too far behind, and some outcomes
+        /// were skipped.
+        Lagged {
+            /// The ID of the subscription.
+            id: RequestId,
+            /// The number of skipped outcomes.
+            skipped: u64,
+        },
+        /// A request could not be handled, or a subscription failed.
+        ///
+        /// A failed subscription is followed by an `ended` message.
+        Error {
+            /// The ID of the request, if it could be determined.
+            id: Option<RequestId>,
+            /// A description of the error.
+            message: String,
+        },
+    }
+
+    /// The open subscriptions of a single connection.
+    #[derive(Default)]
+    struct Subscriptions {
+        /// The address of the client, if known, to which the per-client limits
+        /// apply.
+        client: Option<IpAddr>,
+        /// All subscription streams, merged in the order items become ready.
+        streams: SelectAll<Abortable<BoxStream<'static, Response>>>,
+        /// Handles for closing each subscription by ID.
+        handles: HashMap<RequestId, Handle>,
+    }
+
+    /// Closes an open subscription, and counts it toward the client's cap
+    /// until dropped.
+    struct Handle {
+        abort: AbortHandle,
+        _permit: Option<SubscriptionPermit>,
+    }
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+        headers: HeaderMap,
+        shutdown: Shutdown,
+        version: Version,
+        ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+    ) -> axum::response::Response {
+        if version >= Version::HTTP_2 {
+            let msg = "WebSocket subscriptions require an HTTP/1.1 connection";
+            return (StatusCode::HTTP_VERSION_NOT_SUPPORTED, msg).into_response();
+        }
+        let ws = match ws {
+            Ok(ws) => ws,
+            Err(rejection) => return rejection.into_response(),
+        };
+        let remote_addr = connect_info.map(|ConnectInfo(addr)| addr);
+        let client = state.limiter.client(&headers, remote_addr);
+        ws.max_message_size(MAX_MESSAGE_SIZE)
+            .max_frame_size(MAX_MESSAGE_SIZE)
+            .on_upgrade(move |socket| serve(state, client, shutdown, socket))
+    }
+
+    /// Serve requests and subscription messages until the connection closes,
+    /// or until the server shuts down.
+    async fn serve(
+        state: crate::State,
+        client: Option<IpAddr>,
+        shutdown: Shutdown,
+        mut socket: WebSocket,
+    ) {
+        let mut subs = Subscriptions {
+            client,
+            ..Default::default()
+        };
+        let shutdown = shutdown.triggered();
+        tokio::pin!(shutdown);
+        loop {
+            let response = tokio::select! {
+                _ = &mut shutdown => {
+                    let close = CloseFrame {
+                        code: close_code::AWAY,
+                        reason: "server shutting down".into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    break;
+                },
+                msg = socket.recv() => match msg {
+                    Some(Ok(Message::Text(text))) => handle_request(&state, &mut subs, &text),
+                    Some(Ok(Message::Binary(_))) => Response::Error {
+                        id: None,
+                        message: "requests must be sent as text messages".to_string(),
+                    },
+                    // Pings are answered automatically.
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                },
+                Some(response) = subs.streams.next() => {
+                    if let Response::Ended { id } = response {
+                        subs.handles.remove(&id);
+                    }
+                    response
+                }
+            };
+            let text = match serde_json::to_string(&response) {
+                Ok(text) => text,
+                Err(_err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Failed to serialize WebSocket response: {_err}");
+                    continue;
+                }
+            };
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Handle a single text message from the client.
+    fn handle_request(state: &crate::State, subs: &mut Subscriptions, text: &str) -> Response {
+        let request = match serde_json::from_str(text) {
+            Ok(request) => request,
+            Err(err) => {
+                return Response::Error {
+                    id: None,
+                    message: format!("invalid request: {err}"),
+                }
+            }
+        };
+        match request {
+            Request::Subscribe { id, subscription } => {
+                if subs.handles.contains_key(&id) {
+                    return Response::Error {
+                        id: Some(id),
+                        message: format!("subscription {id} is already open"),
+                    };
+                }
+                if subs.handles.len() >= MAX_SUBSCRIPTIONS {
+                    return Response::Error {
+                        id: Some(id),
+                        message: format!(
+                            "at most {MAX_SUBSCRIPTIONS} subscriptions may be open per connection"
+                        ),
+                    };
+                }
+                // Each subscription counts toward the client's limits as
+                // though it were opened with its own request.
+                let permit = match subs.client.map(|ip| subscribe_permit(state, ip)) {
+                    None => None,
+                    Some(Ok(permit)) => Some(permit),
+                    Some(Err(err)) => {
+                        return Response::Error {
+                            id: Some(id),
+                            message: err.to_string(),
+                        }
+                    }
+                };
+                let (abort, registration) = AbortHandle::new_pair();
+                let stream = subscription_stream(state, id, subscription);
+                subs.streams.push(Abortable::new(stream, registration));
+                let handle = Handle {
+                    abort,
+                    _permit: permit,
+                };
+                subs.handles.insert(id, handle);
+                Response::Subscribed { id }
+            }
+            Request::Unsubscribe { id } => match subs.handles.remove(&id) {
+                Some(handle) => {
+                    handle.abort.abort();
+                    Response::Unsubscribed { id }
+                }
+                None => Response::Error {
+                    id: Some(id),
+                    message: format!("no open subscription {id}"),
+                },
+            },
+        }
+    }
+
+    /// Apply the client's rate limit and subscription cap to a new subscription.
+    fn subscribe_permit(
+        state: &crate::State,
+        ip: IpAddr,
+    ) -> Result<SubscriptionPermit, crate::limits::LimitError> {
+        state.limiter.check_rate(ip)?;
+        state.limiter.try_subscribe(ip)
+    }
+
+    /// The stream of messages for a new subscription, finishing with `ended`.
+    fn subscription_stream(
+        state: &crate::State,
+        id: RequestId,
+        subscription: Subscription,
+    ) -> BoxStream<'static, Response> {
+        let new_block = AwaitNewBlock(state.new_block.clone());
+        let responses = match subscription {
+            Subscription::Blocks { start_block } => {
+                let blocks = state.conn_pool.subscribe_blocks(start_block, new_block);
+                until_error(id, blocks, move |block| Response::Block { id, block }).boxed()
+            }
+            Subscription::State {
+                contract_ca,
+                key,
+                start_block,
+            } => {
+                let changes =
+                    state
+                        .conn_pool
+                        .subscribe_state(contract_ca, key, start_block, new_block);
+                until_error(
+                    id,
+                    changes,
+                    move |(block_number, solution_set_index, value)| {
+                        let change = StateChange {
+                            block_number,
+                            solution_set_index,
+                            value,
+                        };
+                        Response::StateChange { id, change }
+                    },
+                )
+                .boxed()
+            }
+            Subscription::Validation => validated_blocks(state.validated_block.clone())
+                .map(move |validated| match validated {
+                    Validated::Block(validated_block) => Response::Validation {
+                        id,
+                        validated_block,
+                    },
+                    Validated::Lagged(Lagged { skipped }) => Response::Lagged { id, skipped },
+                })
+                .boxed(),
+        };
+        let ended = futures::stream::once(async move { Response::Ended { id } });
+        responses.chain(ended).boxed()
+    }
+
+    /// Map each item to a response, ending after the first error.
+    fn until_error<T>(
+        id: RequestId,
+        items: impl Stream<Item = Result<T, db::QueryError>>,
+        f: impl Fn(T) -> Response,
+    ) -> impl Stream<Item = Response> {
+        items.scan(false, move |failed, res| {
+            if *failed {
+                return futures::future::ready(None);
+            }
+            let response = match res {
+                Ok(item) => f(item),
+                Err(err) => {
+                    *failed = true;
+                    Response::Error {
+                        id: Some(id),
+                        message: format!("DB query failed: {err}"),
+                    }
+                }
+            };
+            futures::future::ready(Some(response))
+        })
+    }
+}
+
+/// The `validate-block` post endpoint.
+///
+/// Takes a JSON-serialized `Block` as the request body and validates it against
+/// the current state without adding it to the DB.
+///
+/// Returns the JSON-serialized `ValidateOutcome`.
+pub mod validate_block {
+    use super::*;
+    use essential_node::validate::ValidateOutcome;
+
+    pub const PATH: &str = "/validate-block";
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Json(block): Json<Block>,
+    ) -> Result<Json<ValidateOutcome>, Error> {
+        let outcome = essential_node::validate_dry_run(
+            &state.conn_pool,
+            &state.contract_registry,
+            &state.program_registry,
+            &block,
+        )
+        .await?;
+        Ok(Json(outcome))
+    }
+}
+
+/// The `validate-solution-set` post endpoint.
+///
+/// Takes a JSON-serialized `SolutionSet` as the request body and validates it
+/// within a block following the latest finalized block, without adding it to
+/// the DB.
+///
+/// Returns the JSON-serialized `ValidateOutcome`.
+pub mod validate_solution_set {
+    use super::*;
+    use essential_node::validate::ValidateOutcome;
+    use essential_types::solution::SolutionSet;
+
+    pub const PATH: &str = "/validate-solution-set";
+
+    pub async fn handler(
+        State(state): State<crate::State>,
+        Json(solution_set): Json<SolutionSet>,
+    ) -> Result<Json<ValidateOutcome>, Error> {
+        let outcome = essential_node::validate_solution_set_dry_run(
+            &state.conn_pool,
+            &state.contract_registry,
+            &state.program_registry,
+            solution_set,
+        )
+        .await?;
+        Ok(Json(outcome))
+    }
+}
+
+/// Each validated block, reporting any missed due to lag and ending when the
+/// node's validation stream closes.
+fn validated_blocks(rx: Option<ValidationRx>) -> impl Stream<Item = Validated> {
+    use tokio::sync::broadcast::error::RecvError;
+    futures::stream::unfold(rx, |rx| async move {
+        let mut rx = rx?;
+        let validated = match rx.recv().await {
+            Ok(validated) => Validated::Block(validated),
+            Err(RecvError::Lagged(skipped)) => Validated::Lagged(Lagged { skipped }),
+            Err(RecvError::Closed) => return None,
+        };
+        Some((validated, Some(rx)))
+    })
+}
+
+/// End the given stream once the server shuts down.
+fn until_shutdown<S: Stream>(stream: S, shutdown: Shutdown) -> impl Stream<Item = S::Item> {
+    stream.take_until(shutdown.triggered())
+}
+
+/// End the given SSE event stream once the server shuts down, finishing with a
+/// [`CLOSE_EVENT`].
+fn close_on_shutdown<E>(
+    events: impl Stream<Item = Result<sse::Event, E>>,
+    shutdown: Shutdown,
+) -> impl Stream<Item = Result<sse::Event, E>> {
+    let closed = shutdown.clone();
+    let close = futures::stream::once(async move { closed.is_triggered() }).filter_map(|closed| {
+        let event = sse::Event::default().event(CLOSE_EVENT).data("shutdown");
+        futures::future::ready(closed.then_some(Ok(event)))
+    });
+    until_shutdown(events, shutdown).chain(close)
+}
+
+/// The point in state at which values are read.
+///
+/// Points selected by block address may be within unfinalized blocks, falling
+/// back to finalized state for keys that are not mutated since the last
+/// finalized block.
+#[derive(Clone, Debug)]
+enum StateAt {
+    InclusiveBlock(Word),
+    ExclusiveBlock(Word),
+    InclusiveSolutionSet(Word, u64),
+    ExclusiveSolutionSet(Word, u64),
+    InclusiveBlockAddress(ContentAddress),
+    ExclusiveBlockAddress(ContentAddress),
+    InclusiveSolutionSetAddress(ContentAddress, u64),
+    ExclusiveSolutionSetAddress(ContentAddress, u64),
+}
+
+impl StateAt {
+    /// The number of the latest block whose state is read, in full or in part.
+    ///
+    /// Exclusive selectors resolve to the preceding block, unless they select
+    /// a solution set after the first within the block.
+    ///
+    /// Returns `None` if the state is selected by the address of an unknown
+    /// block, or if it precedes the first block.
+    fn block_number(&self, tx: &rusqlite::Transaction) -> Result<Option<Word>, db::QueryError> {
+        let number = |addr: &ContentAddress| -> Result<Option<Word>, db::QueryError> {
+            Ok(db::get_block_header(tx, addr)?.map(|header| header.number))
+        };
+        let block = match self {
+            StateAt::InclusiveBlock(block) | StateAt::InclusiveSolutionSet(block, _) => *block,
+            StateAt::ExclusiveBlock(block) | StateAt::ExclusiveSolutionSet(block, 0) => {
+                block.saturating_sub(1)
+            }
+            StateAt::ExclusiveSolutionSet(block, _) => *block,
+            StateAt::InclusiveBlockAddress(addr)
+            | StateAt::InclusiveSolutionSetAddress(addr, _)
+            | StateAt::ExclusiveSolutionSetAddress(addr, 1..) => match number(addr)? {
+                Some(block) => block,
+                None => return Ok(None),
+            },
+            StateAt::ExclusiveBlockAddress(addr)
+            | StateAt::ExclusiveSolutionSetAddress(addr, 0) => match number(addr)? {
+                Some(block) => block.saturating_sub(1),
+                None => return Ok(None),
+            },
+        };
+        Ok((block >= 0).then_some(block))
+    }
+}
+
+/// Map the query parameters to the point in state at which to read.
+///
+/// Returns `None` if the latest finalized state should be read.
+fn state_at(params: query_state::QueryStateParams) -> Result<Option<StateAt>, Error> {
+    let parse = |addr: &Option<String>| addr.as_deref().map(str::parse).transpose();
+    let addr_inclusive: Option<ContentAddress> = parse(&params.block_address_inclusive)?;
+    let addr_exclusive: Option<ContentAddress> = parse(&params.block_address_exclusive)?;
+    let at = match (
+        params.block_inclusive,
+        params.block_exclusive,
+        addr_inclusive,
+        addr_exclusive,
+        params.solution_inclusive,
+        params.solution_exclusive,
+    ) {
+        (Some(block), None, None, None, None, None) => Some(StateAt::InclusiveBlock(block)),
+        (None, Some(block), None, None, None, None) => Some(StateAt::ExclusiveBlock(block)),
+        (Some(block), None, None, None, Some(ix), None) => {
+            Some(StateAt::InclusiveSolutionSet(block, ix))
+        }
+        (Some(block), None, None, None, None, Some(ix)) => {
+            Some(StateAt::ExclusiveSolutionSet(block, ix))
+        }
+        (None, None, Some(addr), None, None, None) => Some(StateAt::InclusiveBlockAddress(addr)),
+        (None, None, None, Some(addr), None, None) => Some(StateAt::ExclusiveBlockAddress(addr)),
+        (None, None, Some(addr), None, Some(ix), None) => {
+            Some(StateAt::InclusiveSolutionSetAddress(addr, ix))
+        }
+        (None, None, Some(addr), None, None, Some(ix)) => {
+            Some(StateAt::ExclusiveSolutionSetAddress(addr, ix))
+        }
+        (None, None, None, None, None, None) => None,
+        _ => return Err(Error::InvalidQueryParameters(params)),
+    };
+    Ok(at)
+}
+
+/// Resolve the point at which to read, defaulting to the latest finalized
+/// block.
+///
+/// Returns `None` if there is no finalized block to read from.
+fn resolve_state_at(
+    tx: &rusqlite::Transaction,
+    at: Option<StateAt>,
+) -> Result<Option<StateAt>, db::QueryError> {
+    match at {
+        Some(at) => Ok(Some(at)),
+        None => Ok(latest_finalized_block_number(tx)?.map(StateAt::InclusiveBlock)),
+    }
+}
+
+/// Read the value at the given key at the given point in state.
+fn query_state_at(
+    tx: &rusqlite::Transaction,
+    contract_ca: &ContentAddress,
+    key: &essential_types::Key,
+    at: &StateAt,
+) -> Result<Option<Value>, db::QueryError> {
+    use db::{address, finalized};
+    match at {
+        StateAt::InclusiveBlock(block) => {
+            finalized::query_state_inclusive_block(tx, contract_ca, key, *block)
+        }
+        StateAt::ExclusiveBlock(block) => {
+            finalized::query_state_exclusive_block(tx, contract_ca, key, *block)
+        }
+        StateAt::InclusiveSolutionSet(block, ix) => {
+            finalized::query_state_inclusive_solution_set(tx, contract_ca, key, *block, *ix)
+        }
+        StateAt::ExclusiveSolutionSet(block, ix) => {
+            finalized::query_state_exclusive_solution_set(tx, contract_ca, key, *block, *ix)
+        }
+        StateAt::InclusiveBlockAddress(addr) => {
+            address::query_state_inclusive_block(tx, contract_ca, key, addr)
+        }
+        StateAt::ExclusiveBlockAddress(addr) => {
+            address::query_state_exclusive_block(tx, contract_ca, key, addr)
+        }
+        StateAt::InclusiveSolutionSetAddress(addr, ix) => {
+            address::query_state_inclusive_solution_set(tx, contract_ca, key, addr, *ix)
+        }
+        StateAt::ExclusiveSolutionSetAddress(addr, ix) => {
+            address::query_state_exclusive_solution_set(tx, contract_ca, key, addr, *ix)
+        }
+    }
+}
+
+/// Query the value at the given key as of the latest finalized block.
+fn query_latest_finalized(
+    tx: &rusqlite::Transaction,
+    contract_ca: &ContentAddress,
+    key: &essential_types::Key,
+) -> Result<Option<Value>, db::QueryError> {
+    match latest_finalized_block_number(tx)? {
+        Some(block) => db::finalized::query_state_inclusive_block(tx, contract_ca, key, block),
+        None => Ok(None),
+    }
+}
+
+/// Validate the requested page size, defaulting to [`MAX_PAGE_SIZE`].
+fn page_size(requested: Option<u64>) -> Result<u64, Error> {
+    let page_size = requested.unwrap_or(MAX_PAGE_SIZE);
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(Error::InvalidPageSize(page_size));
+    }
+    Ok(page_size)
+}
+
+/// The number of the latest finalized block, if any.
+fn latest_finalized_block_number(
+    tx: &rusqlite::Transaction,
+) -> Result<Option<Word>, db::QueryError> {
+    let Some(addr) = db::get_latest_finalized_block_address(tx)? else {
+        return Ok(None);
+    };
+    Ok(db::get_block_header(tx, &addr)?.map(|header| header.number))
+}
+
 fn key_words_from_bytes(key: &[u8]) -> Vec<Word> {
     key.chunks_exact(core::mem::size_of::<Word>())
         .map(|chunk| word_from_bytes(chunk.try_into().expect("safe due to chunk size")))
@@ -3,32 +3,167 @@
 //! Find the available endpoints under the [`endpoint`] module.
 //!
 //! To serve the node API, construct a [`router`], a [`TcpListener`] and call [`serve`].
+//!
+//! To configure the protocol, HTTPS or a graceful shutdown, call
+//! [`serve_with_config`] instead.
 
-use axum::{routing::get, Router};
-use essential_node::db;
-use essential_node_types::block_notify::BlockRx;
-use std::{io, net::SocketAddr};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use essential_node::{db, validation_notify::ValidationRx, RunConfig};
+use essential_node_types::{block_notify::BlockRx, BigBang};
+use essential_types::{ContentAddress, PredicateAddress};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, io, net::SocketAddr, time::Duration};
 use thiserror::Error;
 use tokio::{
+    io::{AsyncRead, AsyncWrite},
     net::{TcpListener, TcpStream},
     task::JoinSet,
 };
+use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
 
+pub mod auth;
 pub mod endpoint;
+pub mod limits;
+pub mod metrics;
+pub mod tls;
 
 /// State provided to the endpoints when serving connections.
 #[derive(Clone)]
 pub struct State {
     /// A node DB connection pool.
     pub conn_pool: db::ConnectionPool,
+    /// The address of the contract registry, used for dry-run validation.
+    pub contract_registry: ContentAddress,
+    /// The address of the program registry, used for dry-run validation.
+    pub program_registry: ContentAddress,
     /// Notifies on availability of a new block (e.g. from the relayer).
     ///
     /// In the case that this is `None`, subscription streams will close after
     /// the last available item in the DB.
     pub new_block: Option<BlockRx>,
+    /// Notifies of the outcome of each block validated by the node.
+    ///
+    /// In the case that this is `None`, validation subscription streams will
+    /// close immediately.
+    pub validated_block: Option<ValidationRx>,
+    /// Limits applied to responses of the `list-blocks` endpoint.
+    pub list_blocks_limits: ListBlocksLimits,
+    /// Metrics recorded by the API and exposed via the `metrics` endpoint.
+    pub metrics: metrics::Metrics,
+    /// Reports whether the node's relayer and validation streams are running.
+    ///
+    /// In the case that this is `None`, the `ready` endpoint skips the stream
+    /// checks.
+    pub node_liveness: Option<essential_node::Liveness>,
+    /// The number of synced blocks that may await validation before the
+    /// `ready` endpoint reports that the node is not ready.
+    pub max_validation_lag: u64,
+    /// The time the relayer may go without syncing a block or receiving a
+    /// keep-alive before the `ready` endpoint reports that the node is not
+    /// ready.
+    pub max_relayer_staleness: Duration,
+    /// The node's chain and run configuration, reported by the `info` endpoint.
+    pub node_config: NodeConfig,
+    /// Verifies the bearer tokens presented with requests.
+    ///
+    /// In the case that this is `None`, all endpoints are public.
+    pub auth: Option<auth::Auth>,
+    /// Enforces the per-client rate limits, request timeouts and subscription
+    /// caps.
+    pub limiter: limits::Limiter,
+}
+
+/// The node's chain and run configuration, reported by the `info` endpoint so
+/// that clients may check that they are talking to the expected network.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NodeConfig {
+    /// The content address of the big bang block.
+    pub big_bang_block: ContentAddress,
+    /// The address of the big bang's block state contract.
+    pub block_state: PredicateAddress,
+    /// The address of the big bang's contract registry.
+    pub contract_registry: PredicateAddress,
+    /// The address of the big bang's program registry.
+    pub program_registry: PredicateAddress,
+    /// Whether the node runs the relayer stream.
+    pub relayer_enabled: bool,
+    /// Whether the node runs the validation stream.
+    pub validation_enabled: bool,
+}
+
+/// Limits applied to responses of the `list-blocks` endpoint.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ListBlocksLimits {
+    /// The maximum number of block numbers covered by a single response.
+    pub max_range: u64,
+    /// The number of encoded bytes after which a response ends at the next
+    /// boundary between block numbers.
+    ///
+    /// Each response is buffered in memory before it is sent, so this also
+    /// bounds the memory used by each request, and should be kept small
+    /// relative to the number of concurrent requests served.
+    pub max_bytes: usize,
+}
+
+/// The HTTP protocol versions accepted when serving connections.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum HttpProtocol {
+    /// Only accept HTTP/1.1 connections.
+    ///
+    /// Useful for clients and reverse proxies that do not speak HTTP/2 over
+    /// plain TCP. Subscriptions are served over keep-alive connections.
+    Http1,
+    /// Only accept HTTP/2 connections with prior knowledge.
+    ///
+    /// WebSocket upgrades require HTTP/1.1, so the `subscribe-ws` endpoint is
+    /// unavailable with this protocol.
+    Http2,
+    /// Detect the protocol of each connection, accepting both HTTP/1.1 and
+    /// HTTP/2 with prior knowledge. The default.
+    #[default]
+    Auto,
+}
+
+/// Options for serving connections. See [`serve_with_config`].
+///
+/// The `shutdown` future is set with [`ServeConfig::with_shutdown`], and the
+/// remaining options may be set directly, e.g.
+/// `ServeConfig { protocol, ..Default::default() }`.
+#[derive(Clone, Debug)]
+pub struct ServeConfig<F = std::future::Pending<()>> {
+    /// The HTTP protocol versions accepted.
+    pub protocol: HttpProtocol,
+    /// If provided, connections are served over TLS.
+    pub tls: Option<tls::Tls>,
+    /// Counts connections while they are served, typically those of the
+    /// [`metrics::Metrics`] of the router's [`State`].
+    pub open_connections: metrics::OpenConnections,
+    /// Upon completion, the server shuts down gracefully. Never completes by
+    /// default.
+    pub shutdown: F,
+    /// The time allowed for open connections to complete their in-flight
+    /// requests upon shutdown.
+    pub drain_timeout: Duration,
 }
 
+/// Signals connections and subscriptions that the server is shutting down.
+///
+/// Triggered by [`serve_with_config`] and provided to each request via its
+/// extensions. Endpoints may extract it to end their streams early. Requests
+/// not served by [`serve_with_config`] extract a signal that never triggers.
+///
+/// Clones share the same signal.
+#[derive(Clone, Debug, Default)]
+pub struct Shutdown(CancellationToken);
+
 /// An error occurred while attempting to serve a new connection.
 #[derive(Debug, Error)]
 pub enum ServeNextConnError {
@@ -49,25 +184,213 @@ pub struct ServeConnError(#[from] Box<dyn std::error::Error + Send + Sync>);
 /// TCP stream connections to maintain at once.
 pub const DEFAULT_CONNECTION_LIMIT: usize = 2_000;
 
+/// The default number of synced blocks that may await validation before the
+/// `ready` endpoint reports that the node is not ready.
+pub const DEFAULT_MAX_VALIDATION_LAG: u64 = 100;
+
+/// The default time the relayer may go without making progress before the
+/// `ready` endpoint reports that the node is not ready.
+///
+/// Sources send keep-alives more frequently than this while idle.
+pub const DEFAULT_MAX_RELAYER_STALENESS: Duration = Duration::from_secs(60);
+
+/// The default maximum number of block numbers covered by a single response
+/// of the `list-blocks` endpoint.
+pub const DEFAULT_LIST_BLOCKS_MAX_RANGE: u64 = 1_000;
+
+/// The default number of encoded bytes after which a response of the
+/// `list-blocks` endpoint ends.
+pub const DEFAULT_LIST_BLOCKS_MAX_BYTES: usize = 1024 * 1024;
+
+/// The default number of requests per second replenished to each client's
+/// rate limit.
+pub const DEFAULT_RATE_LIMIT_PER_SEC: u32 = 100;
+
+/// The default number of requests each client may make in a burst.
+pub const DEFAULT_RATE_LIMIT_BURST: u32 = 200;
+
+/// The default time allowed for non-streaming endpoints to respond.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default number of concurrent subscriptions allowed for each client.
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CLIENT: usize = 100;
+
+/// The default time allowed for open connections to complete their in-flight
+/// requests upon shutdown.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl NodeConfig {
+    /// Describe a node run with the given big bang and run configuration.
+    pub fn new(big_bang: &BigBang, run_conf: &RunConfig) -> Self {
+        Self {
+            big_bang_block: essential_hash::content_addr(&big_bang.block()),
+            block_state: big_bang.block_state.clone(),
+            contract_registry: big_bang.contract_registry.clone(),
+            program_registry: big_bang.program_registry.clone(),
+            relayer_enabled: run_conf.relayer_source_endpoint.is_some(),
+            validation_enabled: run_conf.run_validation,
+        }
+    }
+}
+
+impl<F> ServeConfig<F> {
+    /// Shut down gracefully once the given future completes.
+    pub fn with_shutdown<S>(self, shutdown: S) -> ServeConfig<S>
+    where
+        S: Future<Output = ()>,
+    {
+        self.replace_shutdown(shutdown).0
+    }
+
+    /// Separate the `shutdown` future from the remaining options.
+    fn split_shutdown(self) -> (ServeConfig<()>, F) {
+        self.replace_shutdown(())
+    }
+
+    /// Replace the `shutdown` future, returning the original.
+    fn replace_shutdown<S>(self, shutdown: S) -> (ServeConfig<S>, F) {
+        let config = ServeConfig {
+            protocol: self.protocol,
+            tls: self.tls,
+            open_connections: self.open_connections,
+            shutdown,
+            drain_timeout: self.drain_timeout,
+        };
+        (config, self.shutdown)
+    }
+}
+
+impl Shutdown {
+    /// Signal the shutdown.
+    pub fn trigger(&self) {
+        self.0.cancel();
+    }
+
+    /// Whether the shutdown has been signalled.
+    pub fn is_triggered(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    /// A future that completes once the shutdown is signalled.
+    pub fn triggered(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.0.clone().cancelled_owned()
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Shutdown {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            protocol: HttpProtocol::default(),
+            tls: None,
+            open_connections: metrics::OpenConnections::default(),
+            shutdown: std::future::pending(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        }
+    }
+}
+
+impl Default for ListBlocksLimits {
+    fn default() -> Self {
+        Self {
+            max_range: DEFAULT_LIST_BLOCKS_MAX_RANGE,
+            max_bytes: DEFAULT_LIST_BLOCKS_MAX_BYTES,
+        }
+    }
+}
+
 /// Continuously serve the Node API using the given `router` and TCP `listener`.
 ///
 /// The number of simultaneous TCP stream connections will be capped at the given
-/// `conn_limit`.
+/// `conn_limit`. Connections are served with the default [`ServeConfig`].
 ///
-/// This constructs a new `JoinSet` to use for limiting connections and then
-/// calls [`serve_next_conn`] in a loop. Any outstanding connections will not be
-/// counted toward the connection limit.
+/// This never returns. Dropping the returned future drops all open
+/// connections. To configure the protocol, TLS or a graceful shutdown, use
+/// [`serve_with_config`].
 pub async fn serve(router: &Router, listener: &TcpListener, conn_limit: usize) {
+    serve_with_config(router, listener, conn_limit, ServeConfig::default()).await
+}
+
+/// Serve the Node API as with [`serve`] using the given `config`, until its
+/// [`shutdown`](ServeConfig::shutdown) future completes, then shut down
+/// gracefully.
+///
+/// Upon shutdown, no more connections are accepted and the [`Shutdown`] signal
+/// is triggered. Open connections complete their in-flight requests and then
+/// close. SSE subscriptions end with a final
+/// [`CLOSE_EVENT`](endpoint::CLOSE_EVENT), and WebSocket subscriptions are
+/// closed with a close frame.
+///
+/// Returns once all connections have closed, or once the
+/// [`drain_timeout`](ServeConfig::drain_timeout) has elapsed, after which any
+/// remaining connections are aborted.
+///
+/// This constructs a new `JoinSet` to use for limiting connections and then
+/// calls [`serve_next_conn`] in a loop.
+pub async fn serve_with_config<F>(
+    router: &Router,
+    listener: &TcpListener,
+    conn_limit: usize,
+    config: ServeConfig<F>,
+) where
+    F: Future<Output = ()>,
+{
+    let (config, shutdown) = config.split_shutdown();
+    let signal = Shutdown::default();
     let mut conn_set = JoinSet::new();
+    tokio::pin!(shutdown);
     loop {
-        serve_next_conn(router, listener, conn_limit, &mut conn_set).await;
+        let next = serve_next_conn(
+            router,
+            listener,
+            conn_limit,
+            &config,
+            &signal,
+            &mut conn_set,
+        );
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = next => {}
+        }
+    }
+
+    // Stop accepting connections and signal those open to close.
+    #[cfg(feature = "tracing")]
+    tracing::info!("Shutting down, draining {} connections", conn_set.len());
+    signal.trigger();
+    let drain = async { while conn_set.join_next().await.is_some() {} };
+    if tokio::time::timeout(config.drain_timeout, drain)
+        .await
+        .is_err()
+    {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            "Aborting {} connections after drain timeout",
+            conn_set.len()
+        );
+        conn_set.shutdown().await;
     }
 }
 
 /// Accept and serve the next connection.
 ///
 /// The number of simultaneous TCP stream connections will be capped at the given
-/// `conn_limit`.
+/// `conn_limit`, and the connection is served using the
+/// [`protocol`](ServeConfig::protocol) of the given `config`. If the `config`
+/// has [`tls`](ServeConfig::tls), the connection is served over TLS with the
+/// configuration current at the time it is accepted. The connection is counted
+/// in the config's [`open_connections`](ServeConfig::open_connections) for as
+/// long as it is served. Once `shutdown` is triggered, the connection closes
+/// after completing its in-flight requests. The config's own `shutdown` future
+/// is ignored.
 ///
 /// If we're at the connection limit, this first awaits for a connection task to
 /// become available.
@@ -79,33 +402,69 @@ pub async fn serve(router: &Router, listener: &TcpListener, conn_limit: usize) {
 /// # use essential_node_api as node_api;
 /// let conf = node::db::pool::Config::default();
 /// let db = node::db::ConnectionPool::with_tables(&conf).unwrap();
+/// let big_bang = essential_node_types::BigBang::default();
+/// let run_conf = node::RunConfig {
+///     relayer_source_endpoint: None,
+///     relayer_ca_bundle: None,
+///     run_validation: false,
+/// };
+/// let node_config = node_api::NodeConfig::new(&big_bang, &run_conf);
+/// let metrics = node_api::metrics::Metrics::default();
+/// let config = node_api::ServeConfig {
+///     protocol: node_api::HttpProtocol::Auto,
+///     tls: Some(node_api::tls::Tls::load("cert.pem", "key.pem").unwrap()),
+///     open_connections: metrics.open_connections.clone(),
+///     ..Default::default()
+/// };
 /// let state = node_api::State {
 ///     conn_pool: db,
+///     contract_registry: big_bang.contract_registry.contract,
+///     program_registry: big_bang.program_registry.contract,
 ///     new_block: None,
+///     validated_block: None,
+///     list_blocks_limits: Default::default(),
+///     metrics,
+///     node_liveness: None,
+///     max_validation_lag: node_api::DEFAULT_MAX_VALIDATION_LAG,
+///     max_relayer_staleness: node_api::DEFAULT_MAX_RELAYER_STALENESS,
+///     node_config,
+///     auth: None,
+///     limiter: Default::default(),
 /// };
 /// let router = node_api::router(state);
 /// let listener = tokio::net::TcpListener::bind("127.0.0.1:3553").await.unwrap();
 /// let conn_limit = node_api::DEFAULT_CONNECTION_LIMIT;
+/// let shutdown = node_api::Shutdown::default();
 /// let mut conn_set = tokio::task::JoinSet::new();
 /// // Accept and serve connections.
 /// loop {
-///     node_api::serve_next_conn(&router, &listener, conn_limit, &mut conn_set).await;
+///     node_api::serve_next_conn(
+///         &router,
+///         &listener,
+///         conn_limit,
+///         &config,
+///         &shutdown,
+///         &mut conn_set,
+///     )
+///     .await;
 /// }
 /// # }
 /// ```
 #[tracing::instrument(skip_all)]
-pub async fn serve_next_conn(
+pub async fn serve_next_conn<F>(
     router: &Router,
     listener: &TcpListener,
     conn_limit: usize,
+    config: &ServeConfig<F>,
+    shutdown: &Shutdown,
     conn_set: &mut JoinSet<()>,
 ) {
     // Await the next connection.
-    let stream = match next_conn(listener, conn_limit, conn_set).await {
-        Ok((stream, _remote_addr)) => {
+    let (stream, remote_addr) = match next_conn(listener, conn_limit, conn_set).await {
+        Ok((stream, remote_addr)) => {
             #[cfg(feature = "tracing")]
-            tracing::trace!("Accepted new connection from: {_remote_addr}");
-            stream
+            tracing::trace!("Accepted new connection from: {remote_addr}");
+            (stream, remote_addr)
         }
         Err(_err) => {
             #[cfg(feature = "tracing")]
@@ -116,8 +475,19 @@ pub async fn serve_next_conn(
 
     // Serve the acquired connection.
     let router = router.clone();
+    let protocol = config.protocol;
+    let tls = config.tls.clone();
+    let shutdown = shutdown.clone();
+    let open = config.open_connections.open();
     conn_set.spawn(async move {
-        if let Err(_err) = serve_conn(&router, stream).await {
+        let _open = open;
+        let res = match tls {
+            None => serve_conn(&router, stream, remote_addr, protocol, &shutdown).await,
+            Some(tls) => {
+                serve_tls_conn(&router, stream, remote_addr, protocol, &tls, &shutdown).await
+            }
+        };
+        if let Err(_err) = res {
             #[cfg(feature = "tracing")]
             tracing::trace!("Serve connection error: {_err}");
         }
@@ -145,9 +515,51 @@ pub async fn next_conn(
     listener.accept().await
 }
 
-/// Serve a newly accepted TCP stream.
+/// Perform a TLS handshake on a newly accepted TCP stream, then serve it.
+///
+/// The protocol negotiated via ALPN is served. If the client did not negotiate
+/// a protocol, the given `protocol` is served. Connections that do not complete
+/// the handshake within [`tls::HANDSHAKE_TIMEOUT`] are closed.
 #[tracing::instrument(skip_all, err)]
-pub async fn serve_conn(router: &Router, stream: TcpStream) -> Result<(), ServeConnError> {
+pub async fn serve_tls_conn(
+    router: &Router,
+    stream: TcpStream,
+    remote_addr: SocketAddr,
+    protocol: HttpProtocol,
+    tls: &tls::Tls,
+    shutdown: &Shutdown,
+) -> Result<(), ServeConnError> {
+    let accept = tls.acceptor(protocol).accept(stream);
+    let stream = tokio::time::timeout(tls::HANDSHAKE_TIMEOUT, accept)
+        .await
+        .map_err(|elapsed| ServeConnError(elapsed.into()))?
+        .map_err(|err| ServeConnError(err.into()))?;
+    let protocol = match stream.get_ref().1.alpn_protocol() {
+        Some(tls::ALPN_H2) => HttpProtocol::Http2,
+        Some(_) => HttpProtocol::Http1,
+        None => protocol,
+    };
+    serve_conn(router, stream, remote_addr, protocol, shutdown).await
+}
+
+/// Serve a newly accepted stream using the given `protocol`.
+///
+/// The stream may be a plain TCP stream or a TLS stream. The `remote_addr` is
+/// provided to the endpoints via [`ConnectInfo`](axum::extract::ConnectInfo)
+/// and identifies the client for the [`limits`]. The `shutdown` signal is also
+/// provided to the endpoints, and once triggered the connection closes after
+/// completing its in-flight requests.
+#[tracing::instrument(skip_all, err)]
+pub async fn serve_conn<S>(
+    router: &Router,
+    stream: S,
+    remote_addr: SocketAddr,
+    protocol: HttpProtocol,
+    shutdown: &Shutdown,
+) -> Result<(), ServeConnError>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
     // Hyper has its own `AsyncRead` and `AsyncWrite` traits and doesn't use
     // tokio. `TokioIo` converts between them.
     let stream = hyper_util::rt::TokioIo::new(stream);
@@ -155,24 +567,68 @@ pub async fn serve_conn(router: &Router, stream: TcpStream) -> Result<(), ServeC
     // Hyper also has its own `Service` trait and doesn't use tower. We can use
     // `hyper::service::service_fn` to create a hyper `Service` that calls our
     // app through `tower::Service::call`.
+    let request_shutdown = shutdown.clone();
     let hyper_service = hyper::service::service_fn(
-        move |request: axum::extract::Request<hyper::body::Incoming>| {
+        move |mut request: axum::extract::Request<hyper::body::Incoming>| {
+            let connect_info = axum::extract::ConnectInfo(remote_addr);
+            request.extensions_mut().insert(connect_info);
+            request.extensions_mut().insert(request_shutdown.clone());
             tower::Service::call(&mut router.clone(), request)
         },
     );
 
+    // Serve the connection until it closes. Upon shutdown, stop reading new
+    // requests and close once those in-flight have completed.
+    macro_rules! serve_gracefully {
+        ($conn:expr) => {{
+            let conn = $conn;
+            tokio::pin!(conn);
+            tokio::select! {
+                res = conn.as_mut() => res,
+                _ = shutdown.triggered() => {
+                    conn.as_mut().graceful_shutdown();
+                    conn.await
+                }
+            }
+        }};
+    }
+
     // `TokioExecutor` tells hyper to use `tokio::spawn` to spawn tasks.
     let executor = hyper_util::rt::TokioExecutor::new();
-    let conn = hyper_util::server::conn::auto::Builder::new(executor).http2_only();
-    conn.serve_connection(stream, hyper_service)
-        .await
-        .map_err(ServeConnError)
+    // HTTP/1.1 connections are served with upgrades so that clients may
+    // upgrade to a WebSocket connection.
+    match protocol {
+        HttpProtocol::Http1 => {
+            let builder = hyper::server::conn::http1::Builder::new();
+            let conn = builder
+                .serve_connection(stream, hyper_service)
+                .with_upgrades();
+            serve_gracefully!(conn).map_err(|err| ServeConnError(err.into()))
+        }
+        HttpProtocol::Http2 => {
+            let builder = hyper::server::conn::http2::Builder::new(executor);
+            let conn = builder.serve_connection(stream, hyper_service);
+            serve_gracefully!(conn).map_err(|err| ServeConnError(err.into()))
+        }
+        HttpProtocol::Auto => {
+            let builder = hyper_util::server::conn::auto::Builder::new(executor);
+            let conn = builder.serve_connection_with_upgrades(stream, hyper_service);
+            serve_gracefully!(conn).map_err(ServeConnError)
+        }
+    }
 }
 
-/// Construct the endpoint router with the node [`endpoint`]s, CORS layer and DB
-/// connection pool as state.
+/// Construct the endpoint router with the node [`endpoint`]s, authentication,
+/// per-client limits, latency metrics, CORS layer and DB connection pool as
+/// state.
 pub fn router(state: State) -> Router {
+    let authenticate = middleware::from_fn_with_state(state.clone(), auth::authenticate);
+    let limit = middleware::from_fn_with_state(state.clone(), limits::limit);
+    let track_latency = middleware::from_fn_with_state(state.clone(), metrics::track_latency);
     with_endpoints(Router::new())
+        .route_layer(authenticate)
+        .route_layer(limit)
+        .route_layer(track_latency)
         .layer(cors_layer())
         .with_state(state)
 }
@@ -182,15 +638,52 @@ pub fn with_endpoints(router: Router<State>) -> Router<State> {
     use endpoint::*;
     router
         .route(health_check::PATH, get(health_check::handler))
+        .route(get_block::PATH, get(get_block::handler))
+        .route(get_contract::PATH, get(get_contract::handler))
+        .route(get_predicate::PATH, get(get_predicate::handler))
+        .route(get_program::PATH, get(get_program::handler))
+        .route(get_solution_set::PATH, get(get_solution_set::handler))
+        .route(info::PATH, get(info::handler))
         .route(list_blocks::PATH, get(list_blocks::handler))
+        .route(list_blocks_by_time::PATH, get(list_blocks_by_time::handler))
+        .route(list_failed_blocks::PATH, get(list_failed_blocks::handler))
+        .route(
+            list_unchecked_blocks::PATH,
+            get(list_unchecked_blocks::handler),
+        )
+        .route(metrics::PATH, get(metrics::handler))
         .route(query_state::PATH, get(query_state::handler))
+        .route(query_state_batch::PATH, post(query_state_batch::handler))
+        .route(query_state_range::PATH, get(query_state_range::handler))
+        .route(query_state_prefix::PATH, get(query_state_prefix::handler))
+        .route(ready::PATH, get(ready::handler))
+        .route(rpc::PATH, post(rpc::handler))
         .route(subscribe_blocks::PATH, get(subscribe_blocks::handler))
+        .route(
+            subscribe_contract_solutions::PATH,
+            get(subscribe_contract_solutions::handler),
+        )
+        .route(subscribe_state::PATH, get(subscribe_state::handler))
+        .route(
+            subscribe_validation::PATH,
+            get(subscribe_validation::handler),
+        )
+        .route(subscribe_ws::PATH, get(subscribe_ws::handler))
+        .route(validation_progress::PATH, get(validation_progress::handler))
+        .route(validate_block::PATH, post(validate_block::handler))
+        .route(
+            validate_solution_set::PATH,
+            post(validate_solution_set::handler),
+        )
 }
 
 /// The default CORS layer.
 pub fn cors_layer() -> CorsLayer {
     CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
-        .allow_methods([http::Method::GET, http::Method::OPTIONS])
-        .allow_headers([http::header::CONTENT_TYPE])
+        .allow_methods([http::Method::GET, http::Method::POST, http::Method::OPTIONS])
+        .allow_headers([http::header::AUTHORIZATION, http::header::CONTENT_TYPE])
+        .expose_headers([http::HeaderName::from_static(
+            endpoint::list_blocks::NEXT_CURSOR_HEADER,
+        )])
 }
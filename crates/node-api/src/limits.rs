@@ -0,0 +1,369 @@
+//! Per-client rate limits, request timeouts and subscription caps.
+//!
+//! Clients are identified by the IP address of their connection, as provided
+//! by [`serve_conn`](crate::serve_conn) via [`ConnectInfo`], or by the address
+//! appended to the `X-Forwarded-For` header by a trusted reverse proxy (see
+//! [`ClientLimits::trust_forwarded_for`]). IPv6 clients are grouped by their
+//! `/64` prefix, as a single host is typically allocated the whole prefix.
+//! Requests without a known address are only subject to the request timeout.
+
+use crate::{
+    DEFAULT_MAX_SUBSCRIPTIONS_PER_CLIENT, DEFAULT_RATE_LIMIT_BURST, DEFAULT_RATE_LIMIT_PER_SEC,
+    DEFAULT_REQUEST_TIMEOUT,
+};
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+/// The `Retry-After` seconds suggested to clients at their subscription cap.
+const SUBSCRIPTION_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// The number of tracked clients beyond which idle clients are forgotten.
+const PRUNE_THRESHOLD: usize = 1_024;
+
+/// The length of the prefix by which IPv6 clients are grouped.
+pub const IPV6_CLIENT_PREFIX_LEN: u32 = 64;
+
+/// Limits applied to each client of the API.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClientLimits {
+    /// The token bucket rate limit applied to each client's requests.
+    ///
+    /// In the case that this is `None`, requests are not rate limited.
+    pub rate_limit: Option<RateLimit>,
+    /// The time allowed for non-streaming endpoints to produce a response.
+    ///
+    /// In the case that this is `None`, requests do not time out.
+    pub request_timeout: Option<Duration>,
+    /// The maximum number of concurrent subscriptions for each client, across
+    /// all of its connections.
+    ///
+    /// In the case that this is `None`, subscriptions are not capped.
+    pub max_subscriptions: Option<usize>,
+    /// Whether to identify clients by the last address of the
+    /// `X-Forwarded-For` header, rather than by the address of their
+    /// connection.
+    ///
+    /// Only enable this when all connections are made via a reverse proxy that
+    /// appends the address of its client to the header, as otherwise clients
+    /// may claim any address. Without this, clients behind a proxy share the
+    /// limits of the proxy's address.
+    pub trust_forwarded_for: bool,
+}
+
+/// A token bucket rate limit.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RateLimit {
+    /// The number of requests replenished each second.
+    pub per_sec: u32,
+    /// The maximum number of requests that may be made in a burst.
+    pub burst: u32,
+}
+
+/// Enforces [`ClientLimits`] across all connections.
+///
+/// Clones share the same client state.
+#[derive(Clone, Debug, Default)]
+pub struct Limiter {
+    limits: ClientLimits,
+    buckets: Arc<Mutex<HashMap<IpAddr, Bucket>>>,
+    subscriptions: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+/// Counts toward a client's concurrent subscriptions for as long as it lives.
+#[derive(Debug)]
+pub struct SubscriptionPermit {
+    ip: IpAddr,
+    subscriptions: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+/// A request was refused due to the client's limits.
+#[derive(Debug, Error)]
+pub enum LimitError {
+    /// The client exceeded its rate limit.
+    #[error("rate limit exceeded")]
+    RateLimited {
+        /// The time until the client may make another request.
+        retry_after: Duration,
+    },
+    /// The client is at its cap of concurrent subscriptions.
+    #[error("too many concurrent subscriptions")]
+    TooManySubscriptions,
+    /// The endpoint did not respond within the request timeout.
+    #[error("the request timed out")]
+    Timeout,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl Default for ClientLimits {
+    fn default() -> Self {
+        Self {
+            rate_limit: Some(RateLimit::default()),
+            request_timeout: Some(DEFAULT_REQUEST_TIMEOUT),
+            max_subscriptions: Some(DEFAULT_MAX_SUBSCRIPTIONS_PER_CLIENT),
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            per_sec: DEFAULT_RATE_LIMIT_PER_SEC,
+            burst: DEFAULT_RATE_LIMIT_BURST,
+        }
+    }
+}
+
+impl ClientLimits {
+    /// No limits.
+    pub const NONE: Self = Self {
+        rate_limit: None,
+        request_timeout: None,
+        max_subscriptions: None,
+        trust_forwarded_for: false,
+    };
+}
+
+impl Limiter {
+    /// Enforce the given limits.
+    pub fn new(limits: ClientLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// The limits being enforced.
+    pub fn limits(&self) -> &ClientLimits {
+        &self.limits
+    }
+
+    /// Identify the client of a request by its headers and the address of its
+    /// connection, if known.
+    ///
+    /// The returned address is the key under which the client's limits are
+    /// tracked, so IPv6 addresses are truncated to their
+    /// [`IPV6_CLIENT_PREFIX_LEN`] prefix.
+    pub fn client(&self, headers: &HeaderMap, remote_addr: Option<SocketAddr>) -> Option<IpAddr> {
+        let forwarded = self
+            .limits
+            .trust_forwarded_for
+            .then(|| forwarded_for(headers))
+            .flatten();
+        let ip = forwarded.or(remote_addr.map(|addr| addr.ip()))?;
+        Some(client_key(ip))
+    }
+
+    /// Take a token from the client's bucket, or return the time until one
+    /// becomes available.
+    pub fn check_rate(&self, ip: IpAddr) -> Result<(), LimitError> {
+        let Some(rate) = self.limits.rate_limit else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limit lock poisoned");
+        if !buckets.contains_key(&ip) && buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| bucket.refilled(now, rate) < f64::from(rate.burst));
+        }
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: f64::from(rate.burst),
+            last: now,
+        });
+        bucket.tokens = bucket.refilled(now, rate);
+        bucket.last = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let per_sec = f64::from(rate.per_sec.max(1));
+        let secs = ((1.0 - bucket.tokens) / per_sec).ceil().max(1.0);
+        Err(LimitError::RateLimited {
+            retry_after: Duration::from_secs_f64(secs),
+        })
+    }
+
+    /// Count a new subscription toward the client's cap, or refuse it if the
+    /// client is at its cap.
+    pub fn try_subscribe(&self, ip: IpAddr) -> Result<SubscriptionPermit, LimitError> {
+        let mut subscriptions = self
+            .subscriptions
+            .lock()
+            .expect("subscriptions lock poisoned");
+        let count = subscriptions.entry(ip).or_default();
+        if self
+            .limits
+            .max_subscriptions
+            .is_some_and(|max| *count >= max)
+        {
+            return Err(LimitError::TooManySubscriptions);
+        }
+        *count += 1;
+        Ok(SubscriptionPermit {
+            ip,
+            subscriptions: self.subscriptions.clone(),
+        })
+    }
+}
+
+impl Bucket {
+    /// The tokens in the bucket after refilling until `now`.
+    fn refilled(&self, now: Instant, rate: RateLimit) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        let tokens = self.tokens + elapsed * f64::from(rate.per_sec);
+        tokens.min(f64::from(rate.burst))
+    }
+}
+
+impl Drop for SubscriptionPermit {
+    fn drop(&mut self) {
+        let mut subscriptions = self
+            .subscriptions
+            .lock()
+            .expect("subscriptions lock poisoned");
+        if let Some(count) = subscriptions.get_mut(&self.ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                subscriptions.remove(&self.ip);
+            }
+        }
+    }
+}
+
+impl IntoResponse for LimitError {
+    fn into_response(self) -> Response {
+        let retry_after = match self {
+            LimitError::RateLimited { retry_after } => retry_after,
+            LimitError::TooManySubscriptions => SUBSCRIPTION_RETRY_AFTER,
+            e @ LimitError::Timeout => {
+                return (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response()
+            }
+        };
+        let retry_after = retry_after.as_secs().max(1).to_string();
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after)],
+            self.to_string(),
+        )
+            .into_response()
+    }
+}
+
+/// Whether the endpoint at the given route path is a subscription.
+///
+/// Each response of these endpoints counts toward the client's cap of
+/// concurrent subscriptions for as long as it streams. Subscriptions opened
+/// over `subscribe-ws` are counted individually by that endpoint.
+pub fn is_subscription(path: &str) -> bool {
+    use crate::endpoint::*;
+    matches!(
+        path,
+        subscribe_blocks::PATH
+            | subscribe_contract_solutions::PATH
+            | subscribe_state::PATH
+            | subscribe_validation::PATH
+    )
+}
+
+/// Whether the endpoint at the given route path streams its response, and so
+/// is exempt from the request timeout.
+pub fn is_streaming(path: &str) -> bool {
+    use crate::endpoint::*;
+    is_subscription(path) || path == subscribe_ws::PATH
+}
+
+/// Middleware applying the [`Limiter`] of the [`State`](crate::State) to each
+/// request to a matched endpoint.
+pub async fn limit(State(state): State<crate::State>, request: Request, next: Next) -> Response {
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let limiter = &state.limiter;
+    let remote_addr = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let ip = limiter.client(request.headers(), remote_addr);
+
+    let permit = match ip {
+        None => None,
+        Some(ip) => {
+            if let Err(err) = limiter.check_rate(ip) {
+                return err.into_response();
+            }
+            if !is_subscription(&path) {
+                None
+            } else {
+                match limiter.try_subscribe(ip) {
+                    Ok(permit) => Some(permit),
+                    Err(err) => return err.into_response(),
+                }
+            }
+        }
+    };
+
+    let response = match limiter.limits.request_timeout {
+        Some(timeout) if !is_streaming(&path) => {
+            match tokio::time::timeout(timeout, next.run(request)).await {
+                Ok(response) => response,
+                Err(_elapsed) => return LimitError::Timeout.into_response(),
+            }
+        }
+        _ => next.run(request).await,
+    };
+
+    // Hold the subscription permit until the response body is dropped.
+    match permit {
+        None => response,
+        Some(permit) => {
+            let (parts, body) = response.into_parts();
+            let body = body.into_data_stream().map(move |chunk| {
+                let _permit = &permit;
+                chunk
+            });
+            Response::from_parts(parts, Body::from_stream(body))
+        }
+    }
+}
+
+/// The last address of the `X-Forwarded-For` header, i.e. the address of the
+/// client of the nearest proxy.
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    let value = headers.get_all("x-forwarded-for").iter().next_back()?;
+    let last = value.to_str().ok()?.rsplit(',').next()?;
+    last.trim().parse().ok()
+}
+
+/// The key under which the limits of the client at the given address are
+/// tracked.
+fn client_key(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => {
+                let mask = u128::MAX << (128 - IPV6_CLIENT_PREFIX_LEN);
+                IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask))
+            }
+        },
+    }
+}
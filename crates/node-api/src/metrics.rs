@@ -0,0 +1,248 @@
+//! Prometheus metrics for the node and its API.
+//!
+//! Request latencies and open connections are recorded as they are served,
+//! while the remaining metrics are read from the DB, the connection pool and
+//! the relayer at the time of each scrape. See the
+//! [`metrics`](crate::endpoint::metrics) endpoint.
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use essential_node::{db, RelayerRestarts};
+use essential_types::Word;
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::Histogram},
+    registry::Registry,
+};
+use std::{
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// The content type of the Prometheus text exposition produced by [`encode`].
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The prefix applied to the name of every metric.
+const PREFIX: &str = "essential_node";
+
+/// The upper bounds of the request latency histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Metrics recorded by the API and exposed via the `metrics` endpoint.
+///
+/// Clones share the same recorded metrics.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    /// Counts of the node's relayer stream restarts, if the relayer is running.
+    pub relayer_restarts: Option<RelayerRestarts>,
+    /// Request latency histograms by endpoint.
+    pub latency: Latency,
+    /// The number of TCP connections currently being served.
+    pub open_connections: OpenConnections,
+}
+
+/// Request latency histograms, labelled by endpoint path and method.
+///
+/// Clones share the same histograms.
+#[derive(Clone, Debug)]
+pub struct Latency(Family<EndpointLabels, Histogram, fn() -> Histogram>);
+
+/// The number of TCP connections currently being served by the server they
+/// are provided to, e.g. via [`serve`](crate::serve).
+///
+/// Clones share the same count.
+#[derive(Clone, Debug, Default)]
+pub struct OpenConnections(Arc<AtomicI64>);
+
+/// Node progress read from the DB at the time of a scrape.
+#[derive(Clone, Debug, Default)]
+pub struct DbSnapshot {
+    /// The number of the latest block synced to the DB.
+    pub latest_block_number: Option<Word>,
+    /// The number of the latest block to be successfully validated.
+    pub latest_validated_block_number: Option<Word>,
+    /// The number of recorded block failures.
+    pub failed_blocks: u64,
+}
+
+/// Marks a TCP connection as open for as long as it lives.
+pub(crate) struct OpenConnection(OpenConnections);
+
+#[derive(Clone, Debug, EncodeLabelSet, Eq, Hash, PartialEq)]
+struct EndpointLabels {
+    endpoint: String,
+    method: String,
+}
+
+#[derive(Clone, Debug, EncodeLabelSet, Eq, Hash, PartialEq)]
+struct RestartLabels {
+    kind: String,
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self(Family::new_with_constructor(|| {
+            Histogram::new(LATENCY_BUCKETS.into_iter())
+        }))
+    }
+}
+
+impl DbSnapshot {
+    /// Read the node's progress within the given transaction.
+    pub fn query(tx: &rusqlite::Transaction) -> Result<Self, db::QueryError> {
+        let latest_block_number = db::get_latest_block_number(tx)?;
+        let latest_validated_block_number = match db::get_validation_progress(tx)? {
+            Some(address) => db::get_block_header(tx, &address)?.map(|header| header.number),
+            None => None,
+        };
+        let failed_blocks = db::count_failed_blocks(tx)?;
+        Ok(Self {
+            latest_block_number,
+            latest_validated_block_number,
+            failed_blocks,
+        })
+    }
+
+    /// The number of synced blocks awaiting validation.
+    pub fn validation_lag(&self) -> u64 {
+        let lag = match (self.latest_block_number, self.latest_validated_block_number) {
+            (Some(latest), Some(validated)) => latest.saturating_sub(validated),
+            // Validation has not begun, so every synced block is awaiting validation.
+            (Some(latest), None) => latest.saturating_add(1),
+            (None, _) => 0,
+        };
+        u64::try_from(lag).unwrap_or(0)
+    }
+}
+
+impl OpenConnections {
+    /// The number of connections currently open.
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Count a connection as open until the returned guard is dropped.
+    pub(crate) fn open(&self) -> OpenConnection {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        OpenConnection(self.clone())
+    }
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Middleware recording the latency of each request to a matched endpoint.
+///
+/// Latency is measured until the response head is produced, so the bodies of
+/// streamed responses and subscriptions are not included.
+pub async fn track_latency(
+    State(state): State<crate::State>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let labels = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| EndpointLabels {
+            endpoint: path.as_str().to_string(),
+            method: request.method().to_string(),
+        });
+    let start = Instant::now();
+    let response = next.run(request).await;
+    if let Some(labels) = labels {
+        let secs = start.elapsed().as_secs_f64();
+        state.metrics.latency.0.get_or_create(&labels).observe(secs);
+    }
+    response
+}
+
+/// Encode all metrics in the Prometheus text exposition format.
+pub fn encode(state: &crate::State, snapshot: &DbSnapshot) -> Result<String, std::fmt::Error> {
+    let mut registry = Registry::with_prefix(PREFIX);
+
+    let gauge = |value: i64| {
+        let gauge = Gauge::<i64>::default();
+        gauge.set(value);
+        gauge
+    };
+
+    if let Some(number) = snapshot.latest_block_number {
+        registry.register(
+            "latest_block_number",
+            "The number of the latest block synced to the DB",
+            gauge(number),
+        );
+    }
+    if let Some(number) = snapshot.latest_validated_block_number {
+        registry.register(
+            "latest_validated_block_number",
+            "The number of the latest block to be successfully validated",
+            gauge(number),
+        );
+    }
+    registry.register(
+        "validation_lag_blocks",
+        "The number of synced blocks awaiting validation",
+        gauge(i64::try_from(snapshot.validation_lag()).unwrap_or(i64::MAX)),
+    );
+    registry.register(
+        "failed_blocks",
+        "The number of blocks that failed validation",
+        gauge(i64::try_from(snapshot.failed_blocks).unwrap_or(i64::MAX)),
+    );
+
+    if let Some(restarts) = &state.metrics.relayer_restarts {
+        let family = Family::<RestartLabels, Counter>::default();
+        for (kind, count) in restarts.counts() {
+            let labels = RestartLabels {
+                kind: kind.to_string(),
+            };
+            family.get_or_create(&labels).inc_by(count);
+        }
+        registry.register(
+            "relayer_restarts",
+            "The number of relayer stream restarts by kind of recoverable error",
+            family,
+        );
+    }
+
+    let capacity = state.conn_pool.capacity();
+    let available = state.conn_pool.available();
+    registry.register(
+        "db_pool_connections_in_use",
+        "The number of API DB connections currently in use",
+        gauge(capacity.saturating_sub(available) as i64),
+    );
+    registry.register(
+        "db_pool_connections_available",
+        "The number of API DB connections currently available",
+        gauge(available as i64),
+    );
+
+    registry.register(
+        "tcp_connections_open",
+        "The number of TCP connections currently being served",
+        gauge(state.metrics.open_connections.get()),
+    );
+
+    registry.register(
+        "request_duration_seconds",
+        "The latency of requests to each endpoint",
+        state.metrics.latency.0.clone(),
+    );
+
+    let mut buf = String::new();
+    prometheus_client::encoding::text::encode(&mut buf, &registry)?;
+    Ok(buf)
+}
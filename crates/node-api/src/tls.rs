@@ -0,0 +1,197 @@
+//! TLS termination for serving the node API over HTTPS.
+//!
+//! Load a [`Tls`] configuration from a PEM certificate chain and private key
+//! and provide it to [`serve`](crate::serve). HTTP/2 is negotiated with
+//! clients via ALPN.
+
+use crate::HttpProtocol;
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use thiserror::Error;
+use tokio_rustls::TlsAcceptor;
+
+/// The ALPN protocol identifier for HTTP/2.
+pub(crate) const ALPN_H2: &[u8] = b"h2";
+
+/// The ALPN protocol identifier for HTTP/1.1.
+pub(crate) const ALPN_HTTP1: &[u8] = b"http/1.1";
+
+/// The time allowed for a client to complete the TLS handshake before its
+/// connection is closed.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A TLS configuration loaded from a PEM certificate chain and private key.
+///
+/// Clones share the same configuration, so a [`reload`](Tls::reload) applies
+/// to all of them. Connections accepted prior to a reload continue to be
+/// served with the configuration they were accepted with.
+#[derive(Clone, Debug)]
+pub struct Tls {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    acceptors: Arc<RwLock<Acceptors>>,
+}
+
+/// An acceptor advertising each [`HttpProtocol`] via ALPN.
+///
+/// Built once per load so that each protocol's server configuration, along
+/// with its session cache, is shared by all connections.
+struct Acceptors {
+    http1: TlsAcceptor,
+    http2: TlsAcceptor,
+    auto: TlsAcceptor,
+}
+
+/// An error occurred while loading a TLS configuration.
+#[derive(Debug, Error)]
+pub enum TlsError {
+    /// Failed to read or parse a PEM file.
+    #[error("failed to read {}: {err}", path.display())]
+    Read {
+        /// The path of the PEM file.
+        path: PathBuf,
+        /// The underlying error.
+        #[source]
+        err: io::Error,
+    },
+    /// The certificate chain file contained no certificates.
+    #[error("no certificates found in {}", .0.display())]
+    NoCertificates(PathBuf),
+    /// The private key file contained no private key.
+    #[error("no private key found in {}", .0.display())]
+    NoPrivateKey(PathBuf),
+    /// The certificate chain and private key do not form a valid configuration.
+    #[error("invalid TLS configuration: {0}")]
+    Config(#[from] rustls::Error),
+}
+
+impl Tls {
+    /// Load the PEM certificate chain and private key at the given paths.
+    pub fn load(
+        cert_path: impl Into<PathBuf>,
+        key_path: impl Into<PathBuf>,
+    ) -> Result<Self, TlsError> {
+        let cert_path = cert_path.into();
+        let key_path = key_path.into();
+        let acceptors = Acceptors::new(server_config(&cert_path, &key_path)?);
+        Ok(Self {
+            cert_path,
+            key_path,
+            acceptors: Arc::new(RwLock::new(acceptors)),
+        })
+    }
+
+    /// Reload the certificate chain and private key from their paths.
+    ///
+    /// On failure, the previously loaded configuration remains in use.
+    pub fn reload(&self) -> Result<(), TlsError> {
+        let acceptors = Acceptors::new(server_config(&self.cert_path, &self.key_path)?);
+        *self.acceptors.write().expect("TLS config lock poisoned") = acceptors;
+        Ok(())
+    }
+
+    /// Listen for `SIGHUP`, returning a future that [reloads](Tls::reload)
+    /// the configuration upon each signal.
+    ///
+    /// The signal handler is registered before returning. Failures to reload
+    /// are logged and the previous configuration remains in use.
+    #[cfg(unix)]
+    pub fn reload_on_sighup(&self) -> io::Result<impl std::future::Future<Output = ()>> {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangup = signal(SignalKind::hangup())?;
+        let tls = self.clone();
+        Ok(async move {
+            while hangup.recv().await.is_some() {
+                match tls.reload() {
+                    Ok(()) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::info!("Reloaded TLS certificate and key");
+                    }
+                    Err(_err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("Failed to reload TLS certificate and key: {_err}");
+                    }
+                }
+            }
+        })
+    }
+
+    /// An acceptor for the current configuration, advertising the given
+    /// `protocol` via ALPN.
+    pub(crate) fn acceptor(&self, protocol: HttpProtocol) -> TlsAcceptor {
+        let acceptors = self.acceptors.read().expect("TLS config lock poisoned");
+        match protocol {
+            HttpProtocol::Http1 => acceptors.http1.clone(),
+            HttpProtocol::Http2 => acceptors.http2.clone(),
+            HttpProtocol::Auto => acceptors.auto.clone(),
+        }
+    }
+}
+
+impl Acceptors {
+    fn new(config: ServerConfig) -> Self {
+        let with_alpn = |protocols: &[&[u8]]| {
+            let mut config = config.clone();
+            config.alpn_protocols = protocols.iter().map(|p| p.to_vec()).collect();
+            TlsAcceptor::from(Arc::new(config))
+        };
+        Self {
+            http1: with_alpn(&[ALPN_HTTP1]),
+            http2: with_alpn(&[ALPN_H2]),
+            auto: with_alpn(&[ALPN_H2, ALPN_HTTP1]),
+        }
+    }
+}
+
+impl std::fmt::Debug for Acceptors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Acceptors").finish_non_exhaustive()
+    }
+}
+
+/// Build a server configuration from the PEM files at the given paths.
+fn server_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig, TlsError> {
+    let certs = read_certs(cert_path)?;
+    let key = read_private_key(key_path)?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(config)
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let read_err = |err| TlsError::Read {
+        path: path.to_path_buf(),
+        err,
+    };
+    let mut reader = BufReader::new(File::open(path).map_err(read_err)?);
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_err)?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificates(path.to_path_buf()));
+    }
+    Ok(certs)
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TlsError> {
+    let read_err = |err| TlsError::Read {
+        path: path.to_path_buf(),
+        err,
+    };
+    let mut reader = BufReader::new(File::open(path).map_err(read_err)?);
+    rustls_pemfile::private_key(&mut reader)
+        .map_err(read_err)?
+        .ok_or_else(|| TlsError::NoPrivateKey(path.to_path_buf()))
+}
@@ -1,20 +1,61 @@
 use essential_node::{self as node};
 use essential_node_api as node_api;
-use essential_node_types::{block_notify::BlockTx, Block};
-use essential_types::{convert::bytes_from_word, Value};
-use futures::{StreamExt, TryStreamExt};
+use essential_node_api::endpoint::{
+    get_block::{BlockWithStatus, ValidationStatus},
+    get_solution_set::{Inclusion, SolutionSetWithInclusions},
+    info::{LatestBlock, NodeInfo},
+    list_blocks::{self, BlockPage},
+    list_failed_blocks::FailedBlock,
+    query_state_prefix::{StateEntry, StatePage},
+    ready::{Check, Readiness},
+    subscribe_contract_solutions::ContractSolution,
+    subscribe_state::StateChange,
+    validation_progress::{self, ValidationProgress},
+};
+use essential_node_types::{block::binary, block_notify::BlockTx, BigBang, Block};
+use essential_types::{
+    contract::Contract,
+    convert::bytes_from_word,
+    predicate::{Predicate, Program},
+    solution::Mutation,
+    ContentAddress, PredicateAddress, Value, Word,
+};
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use std::time::Duration;
 use tokio_util::{
     bytes::{self, Buf},
     codec::FramedRead,
     io::StreamReader,
 };
 use util::{
-    client, get_url, init_tracing_subscriber, reqwest_get, state_db_only, test_conn_pool,
-    with_test_server,
+    client, get_url, http1_client, init_tracing_subscriber, reqwest_get, state_db_only,
+    test_conn_pool, test_single_conn_pool, with_test_server, with_test_server_protocol,
 };
 
 mod util;
 
+type WebSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Decode a body of concatenated binary frames, skipping keep-alives.
+fn decode_frames(mut buf: &[u8]) -> Vec<Block> {
+    let mut blocks = vec![];
+    while let Some((frame, len)) = binary::decode_frame(buf).unwrap() {
+        if let binary::Frame::Block(block) = frame {
+            blocks.push(block);
+        }
+        buf = &buf[len..];
+    }
+    assert!(buf.is_empty());
+    blocks
+}
+
+/// The `next-cursor` header of a `list-blocks` response.
+fn next_cursor(response: &reqwest::Response) -> Option<Word> {
+    let cursor = response.headers().get(list_blocks::NEXT_CURSOR_HEADER)?;
+    Some(cursor.to_str().unwrap().parse().unwrap())
+}
+
 #[tokio::test]
 async fn test_health_check() {
     #[cfg(feature = "tracing")]
@@ -28,6 +69,104 @@ async fn test_health_check() {
     .await;
 }
 
+#[tokio::test]
+async fn test_http_protocols() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+    let (blocks, _, _) = node::test_utils::test_blocks(10);
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Over HTTP/1.1, a subscription streams until it completes and the
+    // connection is kept alive for the following request.
+    let block_tx = BlockTx::new();
+    let state = node_api::State {
+        new_block: Some(block_tx.new_listener()),
+        ..state_db_only(db.clone())
+    };
+    std::mem::drop(block_tx);
+    with_test_server_protocol(state, node_api::HttpProtocol::Http1, |port| async move {
+        let http1 = http1_client();
+        let response = http1
+            .get(get_url(port, "/subscribe-blocks?start_block=0"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+        assert!(response.headers().get(http::header::CONNECTION).is_none());
+        let bytes_stream = StreamReader::new(
+            response
+                .bytes_stream()
+                .map_err(|e| std::io::Error::other(format!("{}", e))),
+        );
+        let frame_stream = FramedRead::new(bytes_stream, SseDecoder::<Block>::new());
+        let fetched_blocks: Vec<_> = frame_stream.map(Result::unwrap).collect().await;
+        assert_eq!(blocks, fetched_blocks);
+
+        let response = http1
+            .get(get_url(port, node_api::endpoint::health_check::PATH))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // HTTP/2 is refused.
+        let result = client()
+            .get(get_url(port, node_api::endpoint::health_check::PATH))
+            .send()
+            .await;
+        assert!(result.is_err());
+    })
+    .await;
+
+    // Over HTTP/2 only, HTTP/1.1 is refused.
+    let state = state_db_only(db.clone());
+    with_test_server_protocol(state, node_api::HttpProtocol::Http2, |port| async move {
+        let response = reqwest_get(port, node_api::endpoint::health_check::PATH).await;
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+        let result = http1_client()
+            .get(get_url(port, node_api::endpoint::health_check::PATH))
+            .send()
+            .await;
+        assert!(result.is_err());
+    })
+    .await;
+
+    // Both are accepted when auto-detecting the protocol.
+    let state = state_db_only(db);
+    with_test_server_protocol(state, node_api::HttpProtocol::Auto, |port| async move {
+        for (client, version) in [
+            (http1_client(), reqwest::Version::HTTP_11),
+            (client(), reqwest::Version::HTTP_2),
+        ] {
+            let response = client
+                .get(get_url(port, node_api::endpoint::health_check::PATH))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.version(), version);
+        }
+
+        // WebSocket subscriptions are refused over HTTP/2 with a clear error.
+        let response = reqwest_get(port, node_api::endpoint::subscribe_ws::PATH).await;
+        assert_eq!(
+            response.status(),
+            reqwest::StatusCode::HTTP_VERSION_NOT_SUPPORTED
+        );
+    })
+    .await;
+    assert_eq!(
+        node_api::HttpProtocol::default(),
+        node_api::HttpProtocol::Auto
+    );
+}
+
 #[tokio::test]
 async fn test_query_state() {
     #[cfg(feature = "tracing")]
@@ -93,107 +232,2297 @@ async fn test_query_state() {
 }
 
 #[tokio::test]
-async fn test_list_blocks() {
+async fn test_query_state_block_address() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // A finalized block followed by two competing unfinalized blocks, each
+    // mutating the same key.
+    let (blocks, _, _) = node::test_utils::test_blocks(2);
+    let key: Vec<Word> = vec![7];
+    let with_value = |mut block: Block, value: Word| {
+        let contract = blocks[0].solution_sets[0].solutions[0]
+            .predicate_to_solve
+            .contract
+            .clone();
+        let solution = &mut block.solution_sets[0].solutions[0];
+        solution.predicate_to_solve.contract = contract;
+        solution.state_mutations = vec![Mutation {
+            key: key.clone(),
+            value: vec![value],
+        }];
+        block
+    };
+    let parent = with_value(blocks[0].clone(), 1);
+    let fork_a = with_value(blocks[1].clone(), 2);
+    let fork_b = with_value(blocks[1].clone(), 3);
+    let contract = parent.solution_sets[0].solutions[0]
+        .predicate_to_solve
+        .contract
+        .clone();
+
+    let parent_ca = db.insert_block(parent.into()).await.unwrap();
+    db.finalize_block(parent_ca.clone()).await.unwrap();
+    let fork_a_ca = db.insert_block(fork_a.into()).await.unwrap();
+    let fork_b_ca = db.insert_block(fork_b.into()).await.unwrap();
+    assert_ne!(fork_a_ca, fork_b_ca);
+
+    // Blocks are not yet inserted with their parent, so link the forks manually.
+    for fork_ca in [fork_a_ca.clone(), fork_b_ca.clone()] {
+        let parent_ca = parent_ca.clone();
+        db.acquire_then(move |h| {
+            h.execute(
+                "UPDATE block SET parent_block_id = (SELECT id FROM block WHERE block_address = ?) WHERE block_address = ?",
+                [parent_ca.0, fork_ca.0],
+            )
+        })
+        .await
+        .unwrap();
+    }
+
+    with_test_server(state_db_only(db), |port| async move {
+        let key_hex = hex::encode(bytes_from_word(key[0]));
+        let query = |params: String| {
+            let path = format!("/query-state/{contract}/{key_hex}?{params}");
+            async move {
+                let response = reqwest_get(port, &path).await;
+                assert!(response.status().is_success());
+                response.json::<Option<Value>>().await.unwrap()
+            }
+        };
+
+        // Each fork sees its own mutation.
+        let value = query(format!("block_address_inclusive={fork_a_ca}")).await;
+        assert_eq!(value, Some(vec![2]));
+        let value = query(format!("block_address_inclusive={fork_b_ca}")).await;
+        assert_eq!(value, Some(vec![3]));
+        let params = format!("block_address_inclusive={fork_b_ca}&solution_inclusive=0");
+        assert_eq!(query(params).await, Some(vec![3]));
+
+        // Excluding a fork falls back to its finalized parent.
+        let value = query(format!("block_address_exclusive={fork_a_ca}")).await;
+        assert_eq!(value, Some(vec![1]));
+        let params = format!("block_address_inclusive={fork_b_ca}&solution_exclusive=0");
+        assert_eq!(query(params).await, Some(vec![1]));
+
+        // The latest finalized state ignores the forks.
+        assert_eq!(query(String::new()).await, Some(vec![1]));
+
+        // Block numbers and addresses can't be combined.
+        let path = format!(
+            "/query-state/{contract}/{key_hex}?block_inclusive=1&block_address_inclusive={fork_a_ca}"
+        );
+        let response = reqwest_get(port, &path).await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_query_state_range() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Two blocks mutating a sparse range of keys within the same contract.
+    let (mut block_0, _, _) = node::test_utils::test_block(0, Duration::from_secs(0));
+    let (mut block_1, _, _) = node::test_utils::test_block(1, Duration::from_secs(1));
+    let solution_0 = &mut block_0.solution_sets[0].solutions[0];
+    solution_0.state_mutations = vec![
+        Mutation {
+            key: vec![5],
+            value: vec![50],
+        },
+        Mutation {
+            key: vec![7],
+            value: vec![70],
+        },
+    ];
+    let contract = solution_0.predicate_to_solve.contract.clone();
+    let solution_1 = &mut block_1.solution_sets[0].solutions[0];
+    solution_1.predicate_to_solve = block_0.solution_sets[0].solutions[0]
+        .predicate_to_solve
+        .clone();
+    solution_1.state_mutations = vec![Mutation {
+        key: vec![5],
+        value: vec![51],
+    }];
+    for block in [block_0, block_1] {
+        let block_ca = db.insert_block(std::sync::Arc::new(block)).await.unwrap();
+        db.finalize_block(block_ca).await.unwrap();
+    }
+
+    with_test_server(state_db_only(db), |port| async move {
+        let key_hex = |key: Word| hex::encode(bytes_from_word(key));
+        let query = |key: Word, params: &str| {
+            let path = format!("/query-state-range/{contract}/{}?{params}", key_hex(key));
+            async move { reqwest_get(port, &path).await }
+        };
+        let values = |key: Word, params: &str| {
+            let response = query(key, params);
+            async move {
+                let response = response.await;
+                assert!(response.status().is_success());
+                response.json::<Vec<Value>>().await.unwrap()
+            }
+        };
+
+        // Latest finalized state.
+        let expected = vec![vec![], vec![51], vec![], vec![70], vec![]];
+        assert_eq!(values(4, "num_values=5").await, expected);
+        assert_eq!(values(4, "num_values=5&block_inclusive=1").await, expected);
+
+        // State prior to the second block.
+        let expected = vec![vec![], vec![50], vec![], vec![70], vec![]];
+        assert_eq!(values(4, "num_values=5&block_exclusive=1").await, expected);
+        assert_eq!(
+            values(4, "num_values=5&block_inclusive=1&solution_exclusive=0").await,
+            expected
+        );
+
+        // An empty range.
+        assert!(values(4, "num_values=0").await.is_empty());
+
+        // Invalid requests.
+        let max = node_api::endpoint::query_state_range::MAX_NUM_VALUES;
+        let response = query(0, &format!("num_values={}", max + 1)).await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let response = query(Word::MAX, "num_values=2").await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let response = query(0, "num_values=1&block_exclusive=1&solution_inclusive=0").await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_query_state_prefix() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // A block mutating keys under two different prefixes.
+    let (mut block, _, _) = node::test_utils::test_block(0, Duration::from_secs(0));
+    let solution = &mut block.solution_sets[0].solutions[0];
+    let contract = solution.predicate_to_solve.contract.clone();
+    solution.state_mutations = [([0, 1], 1), ([1, 1], 2), ([0, 2], 3)]
+        .into_iter()
+        .map(|(key, value)| Mutation {
+            key: key.to_vec(),
+            value: vec![value],
+        })
+        .collect();
+    let block_ca = db.insert_block(std::sync::Arc::new(block)).await.unwrap();
+    db.finalize_block(block_ca).await.unwrap();
+
+    with_test_server(state_db_only(db), |port| async move {
+        let prefix = hex::encode(bytes_from_word(0));
+        let query = |params: String| {
+            let path = format!("/query-state-prefix/{contract}?prefix={prefix}&{params}");
+            async move {
+                let response = reqwest_get(port, &path).await;
+                assert!(response.status().is_success());
+                response.json::<StatePage>().await.unwrap()
+            }
+        };
+        let expected = vec![
+            StateEntry {
+                key: vec![0, 1],
+                value: vec![1],
+            },
+            StateEntry {
+                key: vec![0, 2],
+                value: vec![3],
+            },
+        ];
+
+        // Fetch all entries under the prefix at the latest and given block.
+        for params in ["", "block_inclusive=0"] {
+            let page = query(params.to_string()).await;
+            assert_eq!(page.entries, expected);
+            assert!(page.next_cursor.is_none());
+        }
+
+        // Nothing is returned before the block.
+        let page = query("block_inclusive=-1".to_string()).await;
+        assert!(page.entries.is_empty());
+
+        // Page through the entries one at a time.
+        let mut entries = vec![];
+        let mut params = "page_size=1".to_string();
+        loop {
+            let page = query(params).await;
+            entries.extend(page.entries);
+            match page.next_cursor {
+                Some(cursor) => params = format!("page_size=1&cursor={cursor}"),
+                None => break,
+            }
+        }
+        assert_eq!(entries, expected);
+
+        // Without a prefix, all keys are listed.
+        let path = format!("/query-state-prefix/{contract}");
+        let page = reqwest_get(port, &path)
+            .await
+            .json::<StatePage>()
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 3);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_registry_lookups() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = node::test_utils::test_conn_pool_with_big_bang().await;
+
+    // Register the contracts and programs of a test block.
+    let timestamp = Duration::from_secs(1);
+    let block = node::test_utils::test_block_with_contracts(1, timestamp);
+    let (_, contracts, programs) = node::test_utils::test_block(1, timestamp);
+    let block_ca = db.insert_block(std::sync::Arc::new(block)).await.unwrap();
+    db.finalize_block(block_ca).await.unwrap();
+
+    with_test_server(state_db_only(db), |port| async move {
+        for contract in &contracts {
+            let contract_ca = essential_hash::content_addr(contract);
+            let response = reqwest_get(port, &format!("/contract/{contract_ca}")).await;
+            assert!(response.status().is_success());
+            let fetched = response.json::<Option<Contract>>().await.unwrap().unwrap();
+            assert_eq!(fetched.salt, contract.salt);
+            assert_eq!(essential_hash::content_addr(&fetched), contract_ca);
+            for predicate in &contract.predicates {
+                assert!(fetched.predicates.contains(predicate));
+                let predicate_ca = essential_hash::content_addr(predicate);
+                let response = reqwest_get(port, &format!("/predicate/{predicate_ca}")).await;
+                assert!(response.status().is_success());
+                let fetched = response.json::<Option<Predicate>>().await.unwrap();
+                assert_eq!(fetched.as_ref(), Some(predicate));
+            }
+        }
+
+        for program in &programs {
+            let program_ca = essential_hash::content_addr(program);
+            let response = reqwest_get(port, &format!("/program/{program_ca}")).await;
+            assert!(response.status().is_success());
+            let fetched = response.json::<Option<Program>>().await.unwrap();
+            assert_eq!(fetched.as_ref(), Some(program));
+        }
+
+        // Unregistered addresses return `None`.
+        let unknown = ContentAddress([0xFF; 32]);
+        for path in ["contract", "predicate", "program"] {
+            let response = reqwest_get(port, &format!("/{path}/{unknown}")).await;
+            assert!(response.status().is_success());
+            let fetched = response.json::<Option<serde_json::Value>>().await.unwrap();
+            assert!(fetched.is_none());
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_get_block() {
     #[cfg(feature = "tracing")]
     init_tracing_subscriber();
 
     let db = test_conn_pool();
 
     // Create some test blocks.
-    let n_blocks = 100;
+    let n_blocks = 4;
     let (blocks, _, _) = node::test_utils::test_blocks(n_blocks);
+    let block_addrs: Vec<_> = blocks.iter().map(essential_hash::content_addr).collect();
 
-    // Insert them into the node's DB.
-    for block in &blocks {
+    // Insert them into the node's DB, finalizing all but the last.
+    for (block, ca) in blocks.iter().zip(&block_addrs).take(n_blocks as usize - 1) {
         db.insert_block(std::sync::Arc::new(block.clone()))
             .await
             .unwrap();
+        db.finalize_block(ca.clone()).await.unwrap();
     }
+    db.insert_block(std::sync::Arc::new(blocks[3].clone()))
+        .await
+        .unwrap();
 
-    // Fetch all blocks.
-    let fetched_blocks = with_test_server(state_db_only(db), |port| async move {
-        let response = client()
-            .get(get_url(
-                port,
-                &format!("/list-blocks?start={}&end={}", 0, n_blocks),
-            ))
-            .send()
+    // An unfinalized fork of block 1, which is never validated.
+    let mut fork = blocks[1].clone();
+    fork.header.timestamp += Duration::from_secs(1);
+    fork.solution_sets.truncate(1);
+    fork.solution_sets[0].solutions[0].state_mutations[0].value = vec![1];
+    let fork_ca = db.insert_block(fork.into()).await.unwrap();
+
+    // Validate up to block 1 and mark block 2 as failed.
+    db.update_validation_progress(block_addrs[1].clone())
+        .await
+        .unwrap();
+    let failed_ss = essential_hash::content_addr(&blocks[2].solution_sets[0]);
+    let failed_block = block_addrs[2].clone();
+    db.acquire_then(move |h| node::db::insert_failed_block(h, &failed_block, &failed_ss))
+        .await
+        .unwrap();
+
+    with_test_server(state_db_only(db), |port| async move {
+        let expected = [
+            (true, ValidationStatus::Valid),
+            (true, ValidationStatus::Valid),
+            (true, ValidationStatus::Failed),
+            (false, ValidationStatus::Pending),
+        ];
+        for ((block, ca), (finalized, validation)) in blocks.iter().zip(&block_addrs).zip(expected)
+        {
+            let response = reqwest_get(port, &format!("/get-block/{ca}")).await;
+            assert!(response.status().is_success());
+            let fetched = response
+                .json::<Option<BlockWithStatus>>()
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(&fetched.block, block);
+            assert_eq!(fetched.finalized, finalized);
+            assert_eq!(fetched.validation, validation);
+        }
+
+        // Forks below the validation progress are not reported as valid.
+        let response = reqwest_get(port, &format!("/get-block/{fork_ca}")).await;
+        let fetched = response
+            .json::<Option<BlockWithStatus>>()
             .await
+            .unwrap()
             .unwrap();
+        assert!(!fetched.finalized);
+        assert_eq!(fetched.validation, ValidationStatus::Pending);
+
+        // Unknown blocks should return `None`.
+        let unknown = essential_types::ContentAddress([0xFF; 32]);
+        let response = reqwest_get(port, &format!("/get-block/{unknown}")).await;
         assert!(response.status().is_success());
-        response.json::<Vec<Block>>().await.unwrap()
+        let fetched = response.json::<Option<BlockWithStatus>>().await.unwrap();
+        assert!(fetched.is_none());
     })
     .await;
-
-    assert_eq!(blocks, fetched_blocks);
 }
 
 #[tokio::test]
-async fn test_subscribe_blocks() {
+async fn test_get_solution_set() {
     #[cfg(feature = "tracing")]
     init_tracing_subscriber();
 
     let db = test_conn_pool();
 
-    // The test blocks.
-    let (blocks, _, _) = node::test_utils::test_blocks(1000);
+    // Create some test blocks.
+    let (mut blocks, _, _) = node::test_utils::test_blocks(3);
 
-    // A fn for notifying of new blocks.
-    let block_tx = BlockTx::new();
-    let block_rx = block_tx.new_listener();
+    // Include the first solution set of the first block again in the last block.
+    let repeated = blocks[0].solution_sets[0].clone();
+    blocks[2].solution_sets.push(repeated.clone());
 
-    // Write the first 10 blocks to the DB. We'll write the rest later.
-    for block in &blocks[..10] {
-        let block = std::sync::Arc::new(block.clone());
-        db.insert_block(block).await.unwrap();
+    // Insert them into the node's DB.
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
     }
 
-    // Start a test server and subscribe to blocks.
-    let blocks2 = blocks.clone();
-    let state = node_api::State {
-        conn_pool: db.clone(),
-        new_block: Some(block_rx),
-    };
-    let server = with_test_server(state, |port| async move {
-        let response = reqwest_get(port, "/subscribe-blocks?start_block=0").await;
+    with_test_server(state_db_only(db), |port| async move {
+        // Check a solution set included in a single block.
+        let single = &blocks[1].solution_sets[1];
+        let ca = essential_hash::content_addr(single);
+        let response = reqwest_get(port, &format!("/get-solution-set/{ca}")).await;
+        assert!(response.status().is_success());
+        let fetched = response
+            .json::<Option<SolutionSetWithInclusions>>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&fetched.solution_set, single);
+        let expected = vec![Inclusion {
+            block_address: essential_hash::content_addr(&blocks[1]),
+            block_number: 1,
+            solution_set_index: 1,
+        }];
+        assert_eq!(fetched.inclusions, expected);
 
-        // Create the stream from the response.
-        let bytes_stream = StreamReader::new(
-            response
-                .bytes_stream()
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{}", e))),
-        );
-        let mut frame_stream = FramedRead::new(bytes_stream, SseDecoder::<Block>::new());
+        // Check the solution set included in multiple blocks.
+        let ca = essential_hash::content_addr(&repeated);
+        let response = reqwest_get(port, &format!("/get-solution-set/{ca}")).await;
+        assert!(response.status().is_success());
+        let fetched = response
+            .json::<Option<SolutionSetWithInclusions>>()
+            .await
+            .unwrap()
+            .unwrap();
+        let expected = vec![
+            Inclusion {
+                block_address: essential_hash::content_addr(&blocks[0]),
+                block_number: 0,
+                solution_set_index: 0,
+            },
+            Inclusion {
+                block_address: essential_hash::content_addr(&blocks[2]),
+                block_number: 2,
+                solution_set_index: 3,
+            },
+        ];
+        assert_eq!(fetched.inclusions, expected);
 
-        // There should always be 10 blocks available to begin as we wrote those first.
-        let fetched_blocks: Vec<_> = frame_stream
-            .by_ref()
-            .take(10)
-            .map(Result::unwrap)
-            .collect()
-            .await;
+        // Unknown solution sets should return `None`.
+        let unknown = essential_types::ContentAddress([0xFF; 32]);
+        let response = reqwest_get(port, &format!("/get-solution-set/{unknown}")).await;
+        assert!(response.status().is_success());
+        let fetched = response
+            .json::<Option<SolutionSetWithInclusions>>()
+            .await
+            .unwrap();
+        assert!(fetched.is_none());
+    })
+    .await;
+}
 
-        assert_eq!(&blocks2[..10], &fetched_blocks);
+#[tokio::test]
+async fn test_validation_status() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
 
-        // The stream should yield the remaining blocks and then complete after the
-        // `new_block_tx` drops.
-        let fetched_blocks: Vec<_> = frame_stream.map(Result::unwrap).collect().await;
-        assert_eq!(&blocks2[10..], &fetched_blocks);
-    });
+    let db = test_conn_pool();
 
-    // Write the remaining blocks asynchronously, notifying on each new block.
-    let blocks_remaining = blocks[10..].to_vec();
-    let write_remaining_blocks = tokio::spawn(async move {
-        for block in blocks_remaining {
-            db.insert_block(block.into()).await.unwrap();
-            block_tx.notify();
-        }
-        // After writing, drop the new block tx, closing the stream.
-        std::mem::drop(block_tx);
-    });
+    // Create some test blocks.
+    let n_blocks = 10;
+    let (blocks, _, _) = node::test_utils::test_blocks(n_blocks);
+    let block_addrs: Vec<_> = blocks.iter().map(essential_hash::content_addr).collect();
 
-    let ((), res) = tokio::join!(server, write_remaining_blocks);
-    res.unwrap();
+    // Insert them into the node's DB.
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Before validation begins there is no progress.
+    let state = state_db_only(db.clone());
+    with_test_server(state, |port| async move {
+        let response = reqwest_get(port, validation_progress::PATH).await;
+        assert!(response.status().is_success());
+        let progress = response.json::<Option<ValidationProgress>>().await.unwrap();
+        assert!(progress.is_none());
+    })
+    .await;
+
+    // Validate up to block 4 and mark block 5 as failed.
+    db.update_validation_progress(block_addrs[4].clone())
+        .await
+        .unwrap();
+    let failed_ss = essential_hash::content_addr(&blocks[5].solution_sets[1]);
+    let failed_block = block_addrs[5].clone();
+    let ss = failed_ss.clone();
+    db.acquire_then(move |h| node::db::insert_failed_block(h, &failed_block, &ss))
+        .await
+        .unwrap();
+
+    with_test_server(state_db_only(db), |port| async move {
+        let response = reqwest_get(port, validation_progress::PATH).await;
+        assert!(response.status().is_success());
+        let progress = response
+            .json::<Option<ValidationProgress>>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(progress.block_address, block_addrs[4]);
+        assert_eq!(progress.block_number, 4);
+
+        let response =
+            reqwest_get(port, &format!("/list-failed-blocks?start=0&end={n_blocks}")).await;
+        assert!(response.status().is_success());
+        let failed = response.json::<Vec<FailedBlock>>().await.unwrap();
+        let expected = vec![FailedBlock {
+            block_number: 5,
+            solution_set_address: failed_ss,
+        }];
+        assert_eq!(failed, expected);
+
+        // Blocks at or below the progress and failed blocks are not unchecked.
+        let response = reqwest_get(
+            port,
+            &format!("/list-unchecked-blocks?start=0&end={n_blocks}"),
+        )
+        .await;
+        assert!(response.status().is_success());
+        let unchecked = response.json::<Vec<Block>>().await.unwrap();
+        assert_eq!(&unchecked, &blocks[6..]);
+
+        let response = reqwest_get(port, "/list-unchecked-blocks?start=7&end=9").await;
+        assert!(response.status().is_success());
+        let unchecked = response.json::<Vec<Block>>().await.unwrap();
+        assert_eq!(&unchecked, &blocks[7..9]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Create some test blocks and insert them into the node's DB.
+    let (blocks, _, _) = node::test_utils::test_blocks(5);
+    let block_addrs: Vec<_> = blocks.iter().map(essential_hash::content_addr).collect();
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Validate up to block 2 and mark block 3 as failed.
+    db.update_validation_progress(block_addrs[2].clone())
+        .await
+        .unwrap();
+    let failed_ss = essential_hash::content_addr(&blocks[3].solution_sets[0]);
+    let failed_block = block_addrs[3].clone();
+    db.acquire_then(move |h| node::db::insert_failed_block(h, &failed_block, &failed_ss))
+        .await
+        .unwrap();
+
+    let state = node_api::State {
+        metrics: node_api::metrics::Metrics {
+            relayer_restarts: Some(Default::default()),
+            ..Default::default()
+        },
+        ..state_db_only(db)
+    };
+    let open_connections = state.metrics.open_connections.clone();
+    with_test_server(state, |port| async move {
+        // Record the latency of a couple of requests.
+        for _ in 0..2 {
+            let response = reqwest_get(port, "/list-blocks?start=0&end=5").await;
+            assert!(response.status().is_success());
+        }
+
+        let response = reqwest_get(port, node_api::endpoint::metrics::PATH).await;
+        assert!(response.status().is_success());
+        let content_type = &response.headers()[reqwest::header::CONTENT_TYPE];
+        assert_eq!(content_type, node_api::metrics::CONTENT_TYPE);
+        let text = response.text().await.unwrap();
+
+        // Find the value of the sample with the given name and labels.
+        let sample = |name: &str| -> f64 {
+            text.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+                .unwrap_or_else(|| panic!("missing sample {name} in:\n{text}"))
+                .parse()
+                .unwrap()
+        };
+        assert_eq!(sample("essential_node_latest_block_number"), 4.0);
+        assert_eq!(sample("essential_node_latest_validated_block_number"), 2.0);
+        assert_eq!(sample("essential_node_validation_lag_blocks"), 2.0);
+        assert_eq!(sample("essential_node_failed_blocks"), 1.0);
+        assert!(sample("essential_node_tcp_connections_open") >= 1.0);
+        let capacity = sample("essential_node_db_pool_connections_in_use")
+            + sample("essential_node_db_pool_connections_available");
+        assert!(capacity >= 1.0);
+        let count = r#"essential_node_request_duration_seconds_count{endpoint="/list-blocks",method="GET"}"#;
+        assert_eq!(sample(count), 2.0);
+        assert!(text.contains("# TYPE essential_node_relayer_restarts counter"));
+    })
+    .await;
+
+    // The server's connections are no longer counted once it has stopped.
+    let closed = async {
+        while open_connections.get() != 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), closed)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_ready() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Create some test blocks and validate up to block 2.
+    let (blocks, _, _) = node::test_utils::test_blocks(5);
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+    let validated = essential_hash::content_addr(&blocks[2]);
+    db.update_validation_progress(validated).await.unwrap();
+
+    // Fetch the readiness along with the response status.
+    async fn get_ready(port: u16) -> (reqwest::StatusCode, Readiness) {
+        let response = reqwest_get(port, node_api::endpoint::ready::PATH).await;
+        (response.status(), response.json().await.unwrap())
+    }
+
+    // Ready while the lag is within the maximum.
+    let state = node_api::State {
+        max_validation_lag: 2,
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let (status, readiness) = get_ready(port).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+        let expected = Readiness {
+            ready: true,
+            db: Check::Pass,
+            relayer: Check::Skip,
+            validation: Check::Skip,
+            validation_lag: Check::Pass,
+            lag_blocks: Some(2),
+            max_lag_blocks: 2,
+            relayer_staleness_secs: None,
+            max_relayer_staleness_secs: node_api::DEFAULT_MAX_RELAYER_STALENESS.as_secs(),
+        };
+        assert_eq!(readiness, expected);
+    })
+    .await;
+
+    // Not ready once the lag exceeds the maximum.
+    let state = node_api::State {
+        max_validation_lag: 1,
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let (status, readiness) = get_ready(port).await;
+        assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!readiness.ready);
+        assert_eq!(readiness.validation_lag, Check::Fail);
+    })
+    .await;
+
+    // Not ready once a relayer that cannot reach its source exceeds the
+    // maximum staleness, despite it still running.
+    let big_bang = BigBang::default();
+    let run_conf = node::RunConfig {
+        relayer_source_endpoint: Some("http://127.0.0.1:1/".to_string()),
+        relayer_ca_bundle: None,
+        run_validation: false,
+    };
+    let handle = node::run(
+        db.clone(),
+        run_conf,
+        big_bang.contract_registry.contract,
+        big_bang.program_registry.contract,
+        BlockTx::new(),
+    )
+    .unwrap();
+    let liveness = handle.liveness();
+    let max_relayer_staleness = Duration::from_millis(100);
+    tokio::time::sleep(max_relayer_staleness * 2).await;
+    assert_eq!(liveness.relayer(), Some(true));
+    let state = node_api::State {
+        node_liveness: Some(liveness),
+        max_relayer_staleness,
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let (status, readiness) = get_ready(port).await;
+        assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(readiness.relayer, Check::Fail);
+        assert_eq!(readiness.relayer_staleness_secs, Some(0));
+    })
+    .await;
+    drop(handle);
+
+    // Not ready once the DB is unavailable.
+    db.close().unwrap();
+    with_test_server(state_db_only(db), |port| async move {
+        let (status, readiness) = get_ready(port).await;
+        assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(readiness.db, Check::Fail);
+        assert_eq!(readiness.validation_lag, Check::Skip);
+        assert_eq!(readiness.lag_blocks, None);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_validate_dry_run() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = node::test_utils::test_conn_pool_with_big_bang().await;
+
+    // A block that registers its contracts and programs is valid.
+    let valid_block = node::test_utils::test_block_with_contracts(1, Duration::from_secs(1));
+
+    // A solution set solving an unregistered predicate is invalid.
+    let (invalid_block, _, _) = node::test_utils::test_invalid_block(1, Duration::from_secs(1));
+    let invalid_solution_set = invalid_block.solution_sets[0].clone();
+
+    with_test_server(state_db_only(db.clone()), |port| async move {
+        let response = client()
+            .post(get_url(port, node_api::endpoint::validate_block::PATH))
+            .json(&valid_block)
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        let outcome = response.json::<serde_json::Value>().await.unwrap();
+        assert!(outcome["valid"]["total_gas"].as_u64().unwrap() > 0);
+
+        let response = client()
+            .post(get_url(
+                port,
+                node_api::endpoint::validate_solution_set::PATH,
+            ))
+            .json(&invalid_solution_set)
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        let outcome = response.json::<serde_json::Value>().await.unwrap();
+        let invalid = &outcome["invalid"];
+        assert_eq!(invalid["solution_set_index"], 0);
+        assert_eq!(invalid["failure"]["code"], "missing_predicate");
+    })
+    .await;
+
+    // Dry runs must not write to the DB.
+    let blocks = db.list_blocks(1..2).await.unwrap();
+    assert!(blocks.is_empty());
+}
+
+#[tokio::test]
+async fn test_auth() {
+    use node_api::{
+        auth::{self, Auth, AuthConfig, Claims, Scope, StaticToken},
+        endpoint::{health_check, list_blocks, rpc, validate_solution_set},
+    };
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = node::test_utils::test_conn_pool_with_big_bang().await;
+    let (invalid_block, _, _) = node::test_utils::test_invalid_block(1, Duration::from_secs(1));
+    let solution_set = invalid_block.solution_sets[0].clone();
+
+    let hmac_key = [7u8; 32];
+    let hmac_token = |scopes: &[Scope], exp: Option<u64>| {
+        let scopes = scopes.iter().copied().collect();
+        auth::sign(&hmac_key, &Claims { scopes, exp })
+    };
+    let config = AuthConfig {
+        public_read: true,
+        tokens: vec![
+            StaticToken {
+                token: "read-token".to_string(),
+                scopes: [Scope::Read].into(),
+            },
+            StaticToken {
+                token: "admin-token".to_string(),
+                scopes: [Scope::Admin].into(),
+            },
+        ],
+        hmac_keys: vec![hex::encode(hmac_key)],
+    };
+
+    // Request the given endpoint with the given token.
+    let request = |port: u16, method: reqwest::Method, path: &str, token: Option<String>| {
+        let request = client().request(method.clone(), get_url(port, path));
+        let request = match method {
+            reqwest::Method::POST => request.json(&solution_set),
+            _ => request,
+        };
+        let request = match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        async move { request.send().await.unwrap().status() }
+    };
+    let list_blocks_path = format!("{}?start=0&end=1", list_blocks::PATH);
+    let (request, list_blocks_path, solution_set) = (&request, &list_blocks_path, &solution_set);
+
+    let state = node_api::State {
+        auth: Some(Auth::new(config.clone()).unwrap()),
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        use reqwest::{Method, StatusCode};
+
+        // Read endpoints are public, while dry runs require the admin scope.
+        let status = request(port, Method::GET, list_blocks_path, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let status = request(port, Method::POST, validate_solution_set::PATH, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let token = Some("read-token".to_string());
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let token = Some("admin-token".to_string());
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // HMAC tokens grant their signed scopes until they expire.
+        let token = Some(hmac_token(&[Scope::Admin], None));
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = Some(hmac_token(&[Scope::Admin], Some(u64::MAX)));
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = Some(hmac_token(&[Scope::Admin], Some(1)));
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let token = Some(hmac_token(&[Scope::Read], None));
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Tokens that are unknown or signed with another key are refused where
+        // a token is required, and ignored by public endpoints.
+        let token = Some("nope".to_string());
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = request(port, Method::GET, list_blocks_path, Some("nope".into())).await;
+        assert_eq!(status, StatusCode::OK);
+        let claims = Claims {
+            scopes: [Scope::Admin].into(),
+            exp: None,
+        };
+        let forged = auth::sign(&[8u8; 32], &claims);
+        let token = Some(forged.clone());
+        let status = request(port, Method::POST, validate_solution_set::PATH, token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let status = request(port, Method::GET, list_blocks_path, Some(forged)).await;
+        assert_eq!(status, StatusCode::OK);
+        let expired = Some(hmac_token(&[Scope::Read], Some(1)));
+        let status = request(port, Method::GET, list_blocks_path, expired).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // RPC methods require the scope of the endpoint they mirror.
+        let call = |token: Option<&str>| {
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "validate_solution_set",
+                "params": { "solution_set": solution_set },
+                "id": 1,
+            });
+            let request = client().post(get_url(port, rpc::PATH)).json(&body);
+            let request = match token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            };
+            async move {
+                let response = request.send().await.unwrap();
+                response.json::<rpc::Response>().await.unwrap()
+            }
+        };
+        let response = call(None).await;
+        assert_eq!(response.error.unwrap().code, rpc::UNAUTHORIZED);
+        let response = call(Some("read-token")).await;
+        assert_eq!(response.error.unwrap().code, rpc::UNAUTHORIZED);
+        let response = call(Some("admin-token")).await;
+        assert!(response.error.is_none());
+    })
+    .await;
+
+    // Read endpoints may also require a token, while health checks remain public.
+    let state = node_api::State {
+        auth: Some(
+            Auth::new(AuthConfig {
+                public_read: false,
+                ..config
+            })
+            .unwrap(),
+        ),
+        ..state_db_only(db)
+    };
+    with_test_server(state, |port| async move {
+        use reqwest::{Method, StatusCode};
+        let status = request(port, Method::GET, health_check::PATH, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = Some("nope".to_string());
+        let status = request(port, Method::GET, health_check::PATH, token).await;
+        assert_eq!(status, StatusCode::OK);
+        let status = request(port, Method::GET, list_blocks_path, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let token = Some("nope".to_string());
+        let status = request(port, Method::GET, list_blocks_path, token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let token = Some("read-token".to_string());
+        let status = request(port, Method::GET, list_blocks_path, token).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = Some("admin-token".to_string());
+        let status = request(port, Method::GET, list_blocks_path, token).await;
+        assert_eq!(status, StatusCode::OK);
+    })
+    .await;
+
+    // Invalid configurations are rejected.
+    let config = AuthConfig {
+        hmac_keys: vec!["not hex".to_string()],
+        ..Default::default()
+    };
+    assert!(Auth::new(config).is_err());
+}
+
+#[tokio::test]
+async fn test_client_limits() {
+    use node::validation_notify::ValidationTx;
+    use node_api::{
+        endpoint::{health_check, list_failed_blocks, rpc, subscribe_validation, subscribe_ws},
+        limits::{ClientLimits, Limiter, RateLimit},
+    };
+    use reqwest::{header, StatusCode};
+    use std::net::{IpAddr, SocketAddr};
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Requests beyond the burst are refused until the bucket refills.
+    let limits = ClientLimits {
+        rate_limit: Some(RateLimit {
+            per_sec: 1,
+            burst: 3,
+        }),
+        ..ClientLimits::NONE
+    };
+    let state = node_api::State {
+        limiter: Limiter::new(limits),
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let mut refused = None;
+        for _ in 0..5 {
+            let response = reqwest_get(port, health_check::PATH).await;
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                refused = Some(response);
+                break;
+            }
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let refused = refused.expect("expected the rate limit to be exceeded");
+        assert_eq!(refused.headers()[header::RETRY_AFTER], "1");
+        tokio::time::sleep(Duration::from_millis(1_100)).await;
+        let response = reqwest_get(port, health_check::PATH).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    })
+    .await;
+
+    // Each request within an RPC batch is charged to the rate limit.
+    let state = node_api::State {
+        limiter: Limiter::new(limits),
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let batch: Vec<_> = (0..5)
+            .map(|id| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "validation_progress",
+                    "id": id,
+                })
+            })
+            .collect();
+        let response = client()
+            .post(get_url(port, rpc::PATH))
+            .json(&batch)
+            .send()
+            .await
+            .unwrap();
+        let responses: Vec<rpc::Response> = response.json().await.unwrap();
+        let codes: Vec<_> = responses
+            .iter()
+            .map(|r| r.error.as_ref().map(|e| e.code))
+            .collect();
+        // The batch costs as many tokens as it has entries, more than the burst.
+        assert_eq!(codes[0], None);
+        assert_eq!(codes[3..], [Some(rpc::RATE_LIMITED); 2]);
+        let response = reqwest_get(port, health_check::PATH).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    })
+    .await;
+
+    // Clients behind a trusted proxy are identified by the address it appends,
+    // and IPv6 clients are grouped by their /64 prefix.
+    let proxy = SocketAddr::from(([10, 0, 0, 1], 443));
+    let forwarded = |value: &str| {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers
+    };
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+    let limiter = Limiter::new(ClientLimits::NONE);
+    let client_ip = limiter.client(&forwarded("1.2.3.4"), Some(proxy));
+    assert_eq!(client_ip, Some(proxy.ip()));
+    let limiter = Limiter::new(ClientLimits {
+        trust_forwarded_for: true,
+        ..ClientLimits::NONE
+    });
+    let client_ip = limiter.client(&forwarded("9.9.9.9, 1.2.3.4"), Some(proxy));
+    assert_eq!(client_ip, Some(ip("1.2.3.4")));
+    let client_ip = limiter.client(&Default::default(), Some(proxy));
+    assert_eq!(client_ip, Some(proxy.ip()));
+    let client_ip = limiter.client(&forwarded("2001:db8:1:2:3:4:5:6"), Some(proxy));
+    assert_eq!(client_ip, Some(ip("2001:db8:1:2::")));
+    let client_ip = limiter.client(&forwarded("::ffff:1.2.3.4"), Some(proxy));
+    assert_eq!(client_ip, Some(ip("1.2.3.4")));
+
+    // Concurrent subscriptions are capped across SSE and WebSocket.
+    let validation_tx = ValidationTx::new();
+    let limits = ClientLimits {
+        max_subscriptions: Some(1),
+        ..ClientLimits::NONE
+    };
+    let state = node_api::State {
+        validated_block: Some(validation_tx.new_listener()),
+        limiter: Limiter::new(limits),
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let subscription = reqwest_get(port, subscribe_validation::PATH).await;
+        assert_eq!(subscription.status(), StatusCode::OK);
+        let response = reqwest_get(port, subscribe_validation::PATH).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        // Subscriptions over WebSocket count toward the same cap.
+        let url = format!("ws://127.0.0.1:{port}{}", subscribe_ws::PATH);
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let request = serde_json::json!({
+            "type": "subscribe",
+            "id": 1,
+            "subscription": { "kind": "validation" },
+        });
+        ws.send(Message::text(request.to_string())).await.unwrap();
+        let msg = ws.next().await.unwrap().unwrap();
+        let response: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        assert_eq!(response["type"], "error");
+        assert_eq!(response["id"], 1);
+
+        // Closing the subscription frees its place.
+        drop(subscription);
+        let reopened = async {
+            loop {
+                let response = reqwest_get(port, subscribe_validation::PATH).await;
+                if response.status() == StatusCode::OK {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), reopened)
+            .await
+            .unwrap();
+    })
+    .await;
+
+    // Non-streaming endpoints time out, while subscriptions do not. Holding the
+    // only DB connection stalls endpoints that query the DB.
+    let db = test_single_conn_pool();
+    let limits = ClientLimits {
+        request_timeout: Some(Duration::from_millis(100)),
+        ..ClientLimits::NONE
+    };
+    let state = node_api::State {
+        limiter: Limiter::new(limits),
+        ..state_db_only(db.clone())
+    };
+    with_test_server(state, |port| async move {
+        let conn = db.acquire().await.unwrap();
+        let path = format!("{}?start=0&end=1", list_failed_blocks::PATH);
+        let response = reqwest_get(port, &path).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let response = reqwest_get(port, "/subscribe-blocks?start_block=0").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(conn);
+        let response = reqwest_get(port, &path).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_query_state_batch() {
+    use node_api::endpoint::query_state_batch::{self, ContractKey, StateBatch};
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // The test blocks, each mutating a key in each of two contracts.
+    let (blocks, _, _) = node::test_utils::test_blocks(3);
+    let contracts = [ContentAddress([1; 32]), ContentAddress([2; 32])];
+    let blocks: Vec<_> = blocks
+        .into_iter()
+        .map(|mut block| {
+            let n = block.header.number;
+            let solution = block.solution_sets[0].solutions[0].clone();
+            block.solution_sets[0].solutions = contracts
+                .iter()
+                .map(|contract| {
+                    let mut solution = solution.clone();
+                    solution.predicate_to_solve.contract = contract.clone();
+                    solution.state_mutations = vec![Mutation {
+                        key: vec![n],
+                        value: vec![n * 10],
+                    }];
+                    solution
+                })
+                .collect();
+            block
+        })
+        .collect();
+    for block in &blocks {
+        let block_ca = db.insert_block(block.clone().into()).await.unwrap();
+        db.finalize_block(block_ca).await.unwrap();
+    }
+
+    let keys: Vec<_> = (0..3)
+        .flat_map(|n| {
+            contracts.iter().map(move |contract_ca| ContractKey {
+                contract_ca: contract_ca.clone(),
+                key: vec![n],
+            })
+        })
+        .collect();
+
+    with_test_server(state_db_only(db), |port| async move {
+        let post = |body: serde_json::Value| async move {
+            client()
+                .post(get_url(port, query_state_batch::PATH))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        };
+
+        // Read all keys as of the latest finalized block.
+        let response = post(serde_json::json!({ "keys": keys })).await;
+        assert!(response.status().is_success());
+        let batch: StateBatch = response.json().await.unwrap();
+        assert_eq!(batch.block_number, Some(2));
+        let expected: Vec<_> = (0..3)
+            .flat_map(|n| [Some(vec![n * 10]), Some(vec![n * 10])])
+            .collect();
+        assert_eq!(batch.values, expected);
+
+        // Read all keys before the last block, as of the block preceding it.
+        let before_last = [
+            serde_json::json!({ "keys": keys, "block_exclusive": 2 }),
+            serde_json::json!({ "keys": keys, "block_inclusive": 2, "solution_exclusive": 0 }),
+        ];
+        for body in before_last {
+            let batch: StateBatch = post(body).await.json().await.unwrap();
+            assert_eq!(batch.block_number, Some(1));
+            assert_eq!(&batch.values[..4], &expected[..4]);
+            assert_eq!(&batch.values[4..], &[None, None]);
+        }
+
+        // Reading before the first block resolves to no block.
+        let body = serde_json::json!({ "keys": keys, "block_exclusive": 0 });
+        let batch: StateBatch = post(body).await.json().await.unwrap();
+        assert_eq!(batch.block_number, None);
+        assert_eq!(batch.values, vec![None; keys.len()]);
+
+        // Invalid combinations of parameters are rejected.
+        let body = serde_json::json!({ "keys": keys, "block_inclusive": 1, "block_exclusive": 2 });
+        let response = post(body).await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        // As are too many keys.
+        let keys = vec![keys[0].clone(); query_state_batch::MAX_KEYS + 1];
+        let response = post(serde_json::json!({ "keys": keys })).await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_rpc() {
+    use node_api::endpoint::rpc::{
+        self, Response, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+    };
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = node::test_utils::test_conn_pool_with_big_bang().await;
+
+    // Insert some test blocks alongside the big bang block.
+    let (blocks, _, _) = node::test_utils::test_blocks(4);
+    for block in &blocks[1..] {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+    let (invalid_block, _, _) = node::test_utils::test_invalid_block(1, Duration::from_secs(1));
+    let invalid_solution_set = invalid_block.solution_sets[0].clone();
+
+    with_test_server(state_db_only(db), |port| async move {
+        let post = |body: serde_json::Value| async move {
+            client()
+                .post(get_url(port, rpc::PATH))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        };
+
+        // A single call.
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "list_blocks",
+            "params": { "start": 1, "end": 4 },
+            "id": 1,
+        });
+        let response: Response = post(request).await.json().await.unwrap();
+        assert_eq!(response.id, 1);
+        assert!(response.error.is_none());
+        let fetched: BlockPage = serde_json::from_str(response.result.unwrap().get()).unwrap();
+        assert_eq!(&blocks[1..], &fetched.blocks);
+        assert_eq!(fetched.next_cursor, None);
+
+        // A batch, including a notification that produces no response.
+        let block_address = essential_hash::content_addr(&blocks[2]);
+        let batch = serde_json::json!([
+            {
+                "jsonrpc": "2.0",
+                "method": "get_block",
+                "params": { "block_address": block_address },
+                "id": "a",
+            },
+            {
+                "jsonrpc": "2.0",
+                "method": "validate_solution_set",
+                "params": { "solution_set": invalid_solution_set },
+                "id": "b",
+            },
+            {
+                "jsonrpc": "2.0",
+                "method": "query_state",
+                "params": { "contract_ca": ContentAddress([0; 32]), "key": "zz" },
+                "id": "c",
+            },
+            { "jsonrpc": "2.0", "method": "no_such_method", "id": "d" },
+            { "jsonrpc": "2.0", "method": "validation_progress" },
+            { "jsonrpc": "1.0", "method": "validation_progress", "id": "e" },
+            42,
+        ]);
+        let responses: Vec<Response> = post(batch).await.json().await.unwrap();
+        assert_eq!(responses.len(), 6);
+
+        let result = |ix: usize| -> serde_json::Value {
+            serde_json::from_str(responses[ix].result.as_ref().unwrap().get()).unwrap()
+        };
+        let block_with_status = result(0);
+        let fetched: Block = serde_json::from_value(block_with_status["block"].clone()).unwrap();
+        assert_eq!(fetched, blocks[2]);
+        assert_eq!(block_with_status["finalized"], false);
+        assert_eq!(result(1)["invalid"]["failure"]["code"], "missing_predicate");
+
+        let error = |ix: usize| responses[ix].error.as_ref().unwrap().code;
+        assert_eq!(error(2), INVALID_PARAMS);
+        assert_eq!(error(3), METHOD_NOT_FOUND);
+        assert_eq!(error(4), INVALID_REQUEST);
+        assert_eq!(responses[4].id, "e");
+        assert_eq!(error(5), INVALID_REQUEST);
+        assert!(responses[5].id.is_null());
+
+        // A batch of only notifications has no content.
+        let batch = serde_json::json!([{ "jsonrpc": "2.0", "method": "validation_progress" }]);
+        let response = post(batch).await;
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+        // A full batch is answered in order, while a larger batch is refused.
+        let request = |id: usize| {
+            serde_json::json!({ "jsonrpc": "2.0", "method": "validation_progress", "id": id })
+        };
+        let batch: Vec<_> = (0..rpc::MAX_BATCH_SIZE).map(request).collect();
+        let responses: Vec<Response> = post(batch.into()).await.json().await.unwrap();
+        let ids: Vec<_> = responses.iter().map(|response| response.id.clone()).collect();
+        let expected: Vec<_> = (0..rpc::MAX_BATCH_SIZE).map(serde_json::Value::from).collect();
+        assert_eq!(ids, expected);
+        let batch: Vec<_> = (0..=rpc::MAX_BATCH_SIZE).map(request).collect();
+        let response: Response = post(batch.into()).await.json().await.unwrap();
+        assert_eq!(response.error.unwrap().code, INVALID_REQUEST);
+        assert!(response.id.is_null());
+
+        // Malformed JSON.
+        let response: Response = client()
+            .post(get_url(port, rpc::PATH))
+            .body("{")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, PARSE_ERROR);
+        assert!(response.id.is_null());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_info() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Insert some blocks, finalizing up to block 2 and validating up to block 1.
+    let (blocks, _, _) = node::test_utils::test_blocks(4);
+    let block_addrs: Vec<_> = blocks.iter().map(essential_hash::content_addr).collect();
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+    for ca in &block_addrs[..3] {
+        db.finalize_block(ca.clone()).await.unwrap();
+    }
+    db.update_validation_progress(block_addrs[1].clone())
+        .await
+        .unwrap();
+
+    with_test_server(state_db_only(db), |port| async move {
+        let response = reqwest_get(port, node_api::endpoint::info::PATH).await;
+        assert!(response.status().is_success());
+        let json: serde_json::Value = response.json().await.unwrap();
+
+        // The configuration is flattened into the top level.
+        let big_bang = BigBang::default();
+        let big_bang_block = essential_hash::content_addr(&big_bang.block());
+        assert_eq!(json["big_bang_block"], serde_json::json!(big_bang_block));
+        assert_eq!(json["relayer_enabled"], false);
+
+        let info: NodeInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(info.config.big_bang_block, big_bang_block);
+        assert_eq!(info.config.block_state, big_bang.block_state);
+        assert_eq!(info.config.contract_registry, big_bang.contract_registry);
+        assert_eq!(info.config.program_registry, big_bang.program_registry);
+        assert!(!info.config.validation_enabled);
+        assert_eq!(info.versions.node_api, env!("CARGO_PKG_VERSION"));
+        let block = |ix: usize| LatestBlock {
+            block_address: block_addrs[ix].clone(),
+            block_number: blocks[ix].header.number,
+        };
+        assert_eq!(info.latest_finalized_block, Some(block(2)));
+        assert_eq!(info.latest_validated_block, Some(block(1)));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_list_blocks() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Create some test blocks.
+    let n_blocks = 100;
+    let (blocks, _, _) = node::test_utils::test_blocks(n_blocks);
+
+    // Insert them into the node's DB.
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Fetch all blocks.
+    let fetched_blocks = with_test_server(state_db_only(db), |port| async move {
+        let response = client()
+            .get(get_url(
+                port,
+                &format!("/list-blocks?start={}&end={}", 0, n_blocks),
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(next_cursor(&response), None);
+        response.json::<Vec<Block>>().await.unwrap()
+    })
+    .await;
+
+    assert_eq!(blocks, fetched_blocks);
+}
+
+#[tokio::test]
+async fn test_list_blocks_limits() {
+    use node_api::endpoint::rpc;
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Create some test blocks and insert them into the node's DB.
+    let (blocks, _, _) = node::test_utils::test_blocks(30);
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Fetch a JSON page starting from the given block.
+    async fn get_page(port: u16, start: Word) -> BlockPage {
+        let path = format!("/list-blocks?start={start}&end=30");
+        let response = reqwest_get(port, &path).await;
+        assert!(response.status().is_success());
+        let next_cursor = next_cursor(&response);
+        let blocks = response.json().await.unwrap();
+        BlockPage {
+            blocks,
+            next_cursor,
+        }
+    }
+
+    // The range is limited, with a cursor to the rest.
+    let state = node_api::State {
+        list_blocks_limits: node_api::ListBlocksLimits {
+            max_range: 10,
+            max_bytes: usize::MAX,
+        },
+        ..state_db_only(db.clone())
+    };
+    let expected = blocks.clone();
+    with_test_server(state, |port| async move {
+        let blocks = expected;
+        let page = get_page(port, 0).await;
+        assert_eq!(page.blocks, &blocks[..10]);
+        assert_eq!(page.next_cursor, Some(10));
+        let page = get_page(port, 20).await;
+        assert_eq!(page.blocks, &blocks[20..]);
+        assert_eq!(page.next_cursor, None);
+    })
+    .await;
+
+    // With a tiny byte budget, each page ends after a single block.
+    let state = node_api::State {
+        list_blocks_limits: node_api::ListBlocksLimits {
+            max_range: 100,
+            max_bytes: 1,
+        },
+        ..state_db_only(db)
+    };
+    with_test_server(state, |port| async move {
+        // Following the cursor visits every block.
+        let mut fetched = vec![];
+        let mut cursor = Some(0);
+        while let Some(start) = cursor {
+            let page = get_page(port, start).await;
+            assert_eq!(page.blocks.len(), 1);
+            fetched.extend(page.blocks);
+            cursor = page.next_cursor;
+        }
+        assert_eq!(fetched, blocks);
+
+        // The binary encoding carries the same cursor.
+        let response = client()
+            .get(get_url(port, "/list-blocks?start=3&end=30"))
+            .header(reqwest::header::ACCEPT, binary::MEDIA_TYPE)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(next_cursor(&response), Some(4));
+        let bytes = response.bytes().await.unwrap();
+        assert_eq!(decode_frames(&bytes), &blocks[3..4]);
+
+        // As does the JSON-RPC method.
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "list_blocks",
+            "params": { "start": 3, "end": 30 },
+            "id": 1,
+        });
+        let response: rpc::Response = client()
+            .post(get_url(port, rpc::PATH))
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let page: BlockPage = serde_json::from_str(response.result.unwrap().get()).unwrap();
+        assert_eq!(page.blocks, &blocks[3..4]);
+        assert_eq!(page.next_cursor, Some(4));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_list_blocks_by_time() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Create some test blocks, one per second, each with 3 solution sets.
+    let n_blocks = 10;
+    let (blocks, _, _) = node::test_utils::test_blocks(n_blocks);
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    with_test_server(state_db_only(db), |port| async move {
+        let list = |query: String| async move {
+            client()
+                .get(get_url(port, &format!("/list-blocks-by-time?{query}")))
+                .send()
+                .await
+                .unwrap()
+        };
+
+        // Fetch all blocks within the time range.
+        let response = list("start=3&end=6".to_string()).await;
+        assert!(response.status().is_success());
+        let fetched = response.json::<Vec<Block>>().await.unwrap();
+        assert_eq!(&blocks[3..6], &fetched[..]);
+
+        // Page through them, with each block returned whole.
+        let pages = blocks[3..6].chunks(2);
+        for (page, expected) in pages.enumerate() {
+            let query = format!("start=3&end=6&page_size=2&page={page}");
+            let fetched = list(query).await.json::<Vec<Block>>().await.unwrap();
+            assert_eq!(expected, &fetched[..]);
+        }
+        let fetched = list("start=3&end=6&page_size=2&page=2".to_string())
+            .await
+            .json::<Vec<Block>>()
+            .await
+            .unwrap();
+        assert!(fetched.is_empty());
+
+        // Page sizes outside of the allowed range are rejected.
+        let max = node_api::endpoint::MAX_PAGE_SIZE;
+        for page_size in [0, max + 1] {
+            let response = list(format!("start=0&end=10&page_size={page_size}")).await;
+            assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        }
+
+        // Times beyond those representable in the DB are rejected.
+        let response = list(format!("start=0&end={}", u64::MAX)).await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        // Pages whose offset overflows are rejected rather than clamped.
+        let response = list(format!("start=0&end=10&page_size=2&page={}", u64::MAX)).await;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_binary_block_encoding() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // Create some test blocks and insert them into the node's DB.
+    let (blocks, _, _) = node::test_utils::test_blocks(20);
+    for block in &blocks {
+        db.insert_block(std::sync::Arc::new(block.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Without a new block listener, the subscription ends once caught up.
+    with_test_server(state_db_only(db), |port| async move {
+        let get_binary = |path: &'static str| async move {
+            let response = client()
+                .get(get_url(port, path))
+                .header(reqwest::header::ACCEPT, binary::MEDIA_TYPE)
+                .send()
+                .await
+                .unwrap();
+            assert!(response.status().is_success());
+            let content_type = &response.headers()[reqwest::header::CONTENT_TYPE];
+            assert_eq!(content_type, binary::MEDIA_TYPE);
+            response.bytes().await.unwrap()
+        };
+
+        let bytes = get_binary("/list-blocks?start=0&end=20").await;
+        assert_eq!(decode_frames(&bytes), blocks);
+
+        let bytes = get_binary("/subscribe-blocks?start_block=5").await;
+        assert_eq!(decode_frames(&bytes), &blocks[5..]);
+
+        // JSON remains the default.
+        let response = reqwest_get(port, "/list-blocks?start=0&end=20").await;
+        assert_eq!(response.json::<Vec<Block>>().await.unwrap(), blocks);
+
+        // The binary encoding is considerably smaller.
+        let json_len = serde_json::to_vec(&blocks).unwrap().len();
+        let bin_len = get_binary("/list-blocks?start=0&end=20").await.len();
+        assert!(bin_len < json_len / 2, "{bin_len} vs {json_len}");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_subscribe_blocks() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // The test blocks.
+    let (blocks, _, _) = node::test_utils::test_blocks(1000);
+
+    // A fn for notifying of new blocks.
+    let block_tx = BlockTx::new();
+    let block_rx = block_tx.new_listener();
+
+    // Write the first 10 blocks to the DB. We'll write the rest later.
+    for block in &blocks[..10] {
+        let block = std::sync::Arc::new(block.clone());
+        db.insert_block(block).await.unwrap();
+    }
+
+    // Start a test server and subscribe to blocks.
+    let blocks2 = blocks.clone();
+    let state = node_api::State {
+        new_block: Some(block_rx),
+        ..state_db_only(db.clone())
+    };
+    let server = with_test_server(state, |port| async move {
+        let response = reqwest_get(port, "/subscribe-blocks?start_block=0").await;
+
+        // Create the stream from the response.
+        let bytes_stream = StreamReader::new(
+            response
+                .bytes_stream()
+                .map_err(|e| std::io::Error::other(format!("{}", e))),
+        );
+        let mut frame_stream = FramedRead::new(bytes_stream, SseDecoder::<Block>::new());
+
+        // There should always be 10 blocks available to begin as we wrote those first.
+        let fetched_blocks: Vec<_> = frame_stream
+            .by_ref()
+            .take(10)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(&blocks2[..10], &fetched_blocks);
+
+        // The stream should yield the remaining blocks and then complete after the
+        // `new_block_tx` drops.
+        let fetched_blocks: Vec<_> = frame_stream.map(Result::unwrap).collect().await;
+        assert_eq!(&blocks2[10..], &fetched_blocks);
+    });
+
+    // Write the remaining blocks asynchronously, notifying on each new block.
+    let blocks_remaining = blocks[10..].to_vec();
+    let write_remaining_blocks = tokio::spawn(async move {
+        for block in blocks_remaining {
+            db.insert_block(block.into()).await.unwrap();
+            block_tx.notify();
+        }
+        // After writing, drop the new block tx, closing the stream.
+        std::mem::drop(block_tx);
+    });
+
+    let ((), res) = tokio::join!(server, write_remaining_blocks);
+    res.unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_state() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // The test blocks, each mutating the same key within their first solution set.
+    let (blocks, _, _) = node::test_utils::test_blocks(10);
+    let contract = blocks[0].solution_sets[0].solutions[0]
+        .predicate_to_solve
+        .contract
+        .clone();
+    let key: Vec<Word> = vec![7];
+    let blocks: Vec<_> = blocks
+        .into_iter()
+        .map(|mut block| {
+            let solution = &mut block.solution_sets[0].solutions[0];
+            solution.predicate_to_solve.contract = contract.clone();
+            solution.state_mutations = vec![Mutation {
+                key: key.clone(),
+                value: vec![block.header.number],
+            }];
+            block
+        })
+        .collect();
+    let expected: Vec<_> = blocks
+        .iter()
+        .map(|block| StateChange {
+            block_number: block.header.number,
+            solution_set_index: 0,
+            value: vec![block.header.number],
+        })
+        .collect();
+
+    // A fn for notifying of new blocks.
+    let block_tx = BlockTx::new();
+    let block_rx = block_tx.new_listener();
+
+    // Write and finalize the first 5 blocks. We'll write the rest later.
+    for block in &blocks[..5] {
+        let block_ca = db.insert_block(block.clone().into()).await.unwrap();
+        db.finalize_block(block_ca).await.unwrap();
+    }
+
+    // Start a test server and subscribe to the key from the 3rd block.
+    let state = node_api::State {
+        new_block: Some(block_rx),
+        ..state_db_only(db.clone())
+    };
+    let key_hex = hex::encode(bytes_from_word(key[0]));
+    let server = with_test_server(state, |port| async move {
+        let path = format!("/subscribe-state/{contract}/{key_hex}?start_block=2");
+        let response = reqwest_get(port, &path).await;
+
+        // Create the stream from the response.
+        let bytes_stream = StreamReader::new(
+            response
+                .bytes_stream()
+                .map_err(|e| std::io::Error::other(format!("{}", e))),
+        );
+        let frame_stream = FramedRead::new(bytes_stream, SseDecoder::<StateChange>::new());
+
+        // The stream should yield all changes from the 3rd block and then
+        // complete after the `new_block_tx` drops.
+        let fetched: Vec<_> = frame_stream.map(Result::unwrap).collect().await;
+        assert_eq!(&expected[2..], &fetched);
+    });
+
+    // Write the remaining blocks asynchronously, notifying on each new block.
+    let blocks_remaining = blocks[5..].to_vec();
+    let write_remaining_blocks = tokio::spawn(async move {
+        for block in blocks_remaining {
+            let block_ca = db.insert_block(block.into()).await.unwrap();
+            db.finalize_block(block_ca).await.unwrap();
+            block_tx.notify();
+        }
+        // After writing, drop the new block tx, closing the stream.
+        std::mem::drop(block_tx);
+    });
+
+    let ((), res) = tokio::join!(server, write_remaining_blocks);
+    res.unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_contract_solutions() {
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // The test blocks, along with a couple of contracts to filter by.
+    let (blocks, _, _) = node::test_utils::test_blocks(10);
+    let contracts = [
+        blocks[1].solution_sets[0].solutions[0]
+            .predicate_to_solve
+            .contract
+            .clone(),
+        blocks[7].solution_sets[2].solutions[0]
+            .predicate_to_solve
+            .contract
+            .clone(),
+    ];
+    let mut expected = vec![];
+    for block in &blocks {
+        for (ix, set) in block.solution_sets.iter().enumerate() {
+            for solution in &set.solutions {
+                if contracts.contains(&solution.predicate_to_solve.contract) {
+                    expected.push(ContractSolution {
+                        block_number: block.header.number,
+                        block_address: essential_hash::content_addr(block),
+                        solution_set_index: ix as u64,
+                        solution: solution.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // A fn for notifying of new blocks.
+    let block_tx = BlockTx::new();
+    let block_rx = block_tx.new_listener();
+
+    // Write the first 5 blocks to the DB. We'll write the rest later.
+    for block in &blocks[..5] {
+        let block_ca = db.insert_block(block.clone().into()).await.unwrap();
+        db.finalize_block(block_ca).await.unwrap();
+    }
+
+    // Start a test server and subscribe to the contracts' solutions.
+    let state = node_api::State {
+        new_block: Some(block_rx),
+        ..state_db_only(db.clone())
+    };
+    let server = with_test_server(state, |port| async move {
+        let path = format!(
+            "/subscribe-contract-solutions?contracts={},{}&start_block=0",
+            contracts[0], contracts[1],
+        );
+        let response = reqwest_get(port, &path).await;
+
+        // Create the stream from the response.
+        let bytes_stream = StreamReader::new(
+            response
+                .bytes_stream()
+                .map_err(|e| std::io::Error::other(format!("{}", e))),
+        );
+        let frame_stream = FramedRead::new(bytes_stream, SseDecoder::<ContractSolution>::new());
+
+        // The stream should yield only the matching solutions and then
+        // complete after the `new_block_tx` drops.
+        let fetched: Vec<_> = frame_stream.map(Result::unwrap).collect().await;
+        assert_eq!(expected, fetched);
+    });
+
+    // Write the remaining blocks asynchronously, notifying on each new block.
+    let blocks_remaining = blocks[5..].to_vec();
+    let write_remaining_blocks = tokio::spawn(async move {
+        for block in blocks_remaining {
+            let block_ca = db.insert_block(block.into()).await.unwrap();
+            db.finalize_block(block_ca).await.unwrap();
+            block_tx.notify();
+        }
+        // After writing, drop the new block tx, closing the stream.
+        std::mem::drop(block_tx);
+    });
+
+    let ((), res) = tokio::join!(server, write_remaining_blocks);
+    res.unwrap();
+}
+
+#[tokio::test]
+async fn test_subscribe_validation() {
+    use node::{
+        validate::{InvalidOutcome, ValidOutcome, ValidateFailure, ValidateOutcome},
+        validation_notify::{ValidatedBlock, ValidationTx},
+    };
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+    let validation_tx = ValidationTx::new();
+    let state = node_api::State {
+        validated_block: Some(validation_tx.new_listener()),
+        ..state_db_only(db)
+    };
+
+    with_test_server(state, |port| async move {
+        let response = reqwest_get(port, node_api::endpoint::subscribe_validation::PATH).await;
+        assert!(response.status().is_success());
+
+        // Broadcast a valid and an invalid outcome, then close.
+        let pred_addr = PredicateAddress {
+            contract: ContentAddress([1; 32]),
+            predicate: ContentAddress([2; 32]),
+        };
+        validation_tx.notify(ValidatedBlock {
+            block_address: ContentAddress([3; 32]),
+            block_number: 1,
+            outcome: ValidateOutcome::Valid(ValidOutcome { total_gas: 42 }),
+        });
+        validation_tx.notify(ValidatedBlock {
+            block_address: ContentAddress([4; 32]),
+            block_number: 2,
+            outcome: ValidateOutcome::Invalid(InvalidOutcome {
+                failure: ValidateFailure::MissingPredicate(pred_addr),
+                solution_set_index: 3,
+            }),
+        });
+        std::mem::drop(validation_tx);
+
+        // Create the stream from the response.
+        let bytes_stream = StreamReader::new(
+            response
+                .bytes_stream()
+                .map_err(|e| std::io::Error::other(format!("{}", e))),
+        );
+        let frame_stream = FramedRead::new(bytes_stream, SseDecoder::<serde_json::Value>::new());
+        let events: Vec<_> = frame_stream.map(Result::unwrap).collect().await;
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0]["block_number"], 1);
+        assert_eq!(
+            events[0]["block_address"],
+            ContentAddress([3; 32]).to_string()
+        );
+        assert_eq!(events[0]["outcome"]["valid"]["total_gas"], 42);
+
+        assert_eq!(events[1]["block_number"], 2);
+        let invalid = &events[1]["outcome"]["invalid"];
+        assert_eq!(invalid["solution_set_index"], 3);
+        assert_eq!(invalid["failure"]["code"], "missing_predicate");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_subscribe_validation_lagged() {
+    use node::{
+        validate::{ValidOutcome, ValidateOutcome},
+        validation_notify::{ValidatedBlock, ValidationTx},
+    };
+    use node_api::endpoint::{Lagged, LAGGED_EVENT};
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+    let validation_tx = ValidationTx::new();
+    let state = node_api::State {
+        validated_block: Some(validation_tx.new_listener()),
+        ..state_db_only(db)
+    };
+
+    with_test_server(state, |port| async move {
+        let response = reqwest_get(port, node_api::endpoint::subscribe_validation::PATH).await;
+        assert!(response.status().is_success());
+
+        // Broadcast more outcomes than are retained for the subscriber, then close.
+        const NUM_OUTCOMES: u64 = 1_000;
+        for block_number in 0..NUM_OUTCOMES {
+            validation_tx.notify(ValidatedBlock {
+                block_address: ContentAddress([0; 32]),
+                block_number: block_number as Word,
+                outcome: ValidateOutcome::Valid(ValidOutcome { total_gas: 0 }),
+            });
+        }
+        std::mem::drop(validation_tx);
+
+        // The subscriber is told how many outcomes it missed, followed by the rest.
+        let body = response.text().await.unwrap();
+        let mut lines = body.lines().filter(|line| !line.is_empty());
+        assert_eq!(
+            lines.next(),
+            Some(format!("event: {LAGGED_EVENT}").as_str())
+        );
+        let data = lines.next().unwrap().strip_prefix("data: ").unwrap();
+        let lagged: Lagged = serde_json::from_str(data).unwrap();
+        assert!(lagged.skipped > 0);
+        let outcomes: Vec<serde_json::Value> = lines
+            .map(|line| serde_json::from_str(line.strip_prefix("data: ").unwrap()).unwrap())
+            .collect();
+        assert_eq!(outcomes.len() as u64, NUM_OUTCOMES - lagged.skipped);
+        assert_eq!(outcomes[0]["block_number"], lagged.skipped);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_subscribe_ws() {
+    use node::{
+        validate::{ValidOutcome, ValidateOutcome},
+        validation_notify::{ValidatedBlock, ValidationTx},
+    };
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_conn_pool();
+
+    // The test blocks, each mutating the same key within their first solution set.
+    let (blocks, _, _) = node::test_utils::test_blocks(3);
+    let contract = blocks[0].solution_sets[0].solutions[0]
+        .predicate_to_solve
+        .contract
+        .clone();
+    let blocks: Vec<_> = blocks
+        .into_iter()
+        .map(|mut block| {
+            let solution = &mut block.solution_sets[0].solutions[0];
+            solution.predicate_to_solve.contract = contract.clone();
+            solution.state_mutations = vec![Mutation {
+                key: vec![7],
+                value: vec![block.header.number],
+            }];
+            block
+        })
+        .collect();
+    for block in &blocks {
+        let block_ca = db.insert_block(block.clone().into()).await.unwrap();
+        db.finalize_block(block_ca).await.unwrap();
+    }
+
+    // Without a new block listener, block and state subscriptions end once
+    // caught up.
+    let validation_tx = ValidationTx::new();
+    let state = node_api::State {
+        validated_block: Some(validation_tx.new_listener()),
+        ..state_db_only(db)
+    };
+
+    with_test_server(state, |port| async move {
+        let url = format!(
+            "ws://127.0.0.1:{port}{}",
+            node_api::endpoint::subscribe_ws::PATH
+        );
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        // Send a JSON request and await the next JSON response.
+        async fn send(ws: &mut WebSocket, request: serde_json::Value) {
+            ws.send(Message::text(request.to_string())).await.unwrap();
+        }
+        async fn recv(ws: &mut WebSocket) -> serde_json::Value {
+            let msg = ws.next().await.unwrap().unwrap();
+            serde_json::from_str(msg.to_text().unwrap()).unwrap()
+        }
+
+        // Subscribe to blocks from the 2nd block.
+        let request = serde_json::json!({
+            "type": "subscribe",
+            "id": 1,
+            "subscription": { "kind": "blocks", "start_block": 1 },
+        });
+        send(&mut ws, request).await;
+        assert_eq!(
+            recv(&mut ws).await,
+            serde_json::json!({ "type": "subscribed", "id": 1 })
+        );
+        for block in &blocks[1..] {
+            let expected = serde_json::json!({ "type": "block", "id": 1, "block": block });
+            assert_eq!(recv(&mut ws).await, expected);
+        }
+        assert_eq!(
+            recv(&mut ws).await,
+            serde_json::json!({ "type": "ended", "id": 1 })
+        );
+
+        // Subscribe to the key from the 1st block.
+        let request = serde_json::json!({
+            "type": "subscribe",
+            "id": 2,
+            "subscription": {
+                "kind": "state",
+                "contract_ca": contract,
+                "key": [7],
+                "start_block": 0,
+            },
+        });
+        send(&mut ws, request).await;
+        assert_eq!(recv(&mut ws).await["type"], "subscribed");
+        for block in &blocks {
+            let change = recv(&mut ws).await;
+            assert_eq!(change["type"], "state_change");
+            assert_eq!(change["id"], 2);
+            let change: StateChange = serde_json::from_value(change["change"].clone()).unwrap();
+            assert_eq!(change.block_number, block.header.number);
+            assert_eq!(change.value, vec![block.header.number]);
+        }
+        assert_eq!(recv(&mut ws).await["type"], "ended");
+
+        // Subscribe to validation, reusing the ID of a subscription that ended.
+        let subscribe_validation = serde_json::json!({
+            "type": "subscribe",
+            "id": 1,
+            "subscription": { "kind": "validation" },
+        });
+        send(&mut ws, subscribe_validation.clone()).await;
+        assert_eq!(recv(&mut ws).await["type"], "subscribed");
+
+        // IDs of open subscriptions may not be reused.
+        send(&mut ws, subscribe_validation.clone()).await;
+        let error = recv(&mut ws).await;
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["id"], 1);
+
+        validation_tx.notify(ValidatedBlock {
+            block_address: ContentAddress([3; 32]),
+            block_number: 1,
+            outcome: ValidateOutcome::Valid(ValidOutcome { total_gas: 42 }),
+        });
+        let validation = recv(&mut ws).await;
+        assert_eq!(validation["type"], "validation");
+        assert_eq!(validation["id"], 1);
+        assert_eq!(validation["validated_block"]["block_number"], 1);
+
+        // Unsubscribe, after which the ID is unknown.
+        let unsubscribe = serde_json::json!({ "type": "unsubscribe", "id": 1 });
+        send(&mut ws, unsubscribe.clone()).await;
+        assert_eq!(
+            recv(&mut ws).await,
+            serde_json::json!({ "type": "unsubscribed", "id": 1 })
+        );
+        send(&mut ws, unsubscribe).await;
+        assert_eq!(recv(&mut ws).await["type"], "error");
+
+        // Malformed requests are reported without an ID.
+        send(&mut ws, serde_json::json!({ "type": "bogus" })).await;
+        let error = recv(&mut ws).await;
+        assert_eq!(error["type"], "error");
+        assert!(error["id"].is_null());
+
+        ws.close(None).await.unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_subscribe_ws_limits() {
+    use node::validation_notify::ValidationTx;
+    use node_api::endpoint::subscribe_ws::{MAX_MESSAGE_SIZE, MAX_SUBSCRIPTIONS, PATH};
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let validation_tx = ValidationTx::new();
+    let state = node_api::State {
+        validated_block: Some(validation_tx.new_listener()),
+        ..state_db_only(test_conn_pool())
+    };
+
+    with_test_server(state, |port| async move {
+        let url = format!("ws://127.0.0.1:{port}{PATH}");
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        // Subscribe to validation and await the response.
+        async fn subscribe(ws: &mut WebSocket, id: usize) -> serde_json::Value {
+            let request = serde_json::json!({
+                "type": "subscribe",
+                "id": id,
+                "subscription": { "kind": "validation" },
+            });
+            ws.send(Message::text(request.to_string())).await.unwrap();
+            let msg = ws.next().await.unwrap().unwrap();
+            serde_json::from_str(msg.to_text().unwrap()).unwrap()
+        }
+
+        // Subscriptions beyond the per-connection cap are refused.
+        for id in 0..MAX_SUBSCRIPTIONS {
+            assert_eq!(subscribe(&mut ws, id).await["type"], "subscribed");
+        }
+        let error = subscribe(&mut ws, MAX_SUBSCRIPTIONS).await;
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["id"], MAX_SUBSCRIPTIONS);
+
+        // An oversized message closes the connection.
+        let oversized = "x".repeat(MAX_MESSAGE_SIZE + 1);
+        let _ = ws.send(Message::text(oversized)).await;
+        assert!(!matches!(ws.next().await, Some(Ok(Message::Text(_)))));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_serve_with_shutdown() {
+    use node::validation_notify::ValidationTx;
+    use node_api::endpoint::{list_failed_blocks, subscribe_validation, subscribe_ws, CLOSE_EVENT};
+    use tokio_tungstenite::tungstenite::{protocol::frame::coding::CloseCode, Message};
+
+    #[cfg(feature = "tracing")]
+    init_tracing_subscriber();
+
+    let db = test_single_conn_pool();
+    let validation_tx = ValidationTx::new();
+    let state = node_api::State {
+        validated_block: Some(validation_tx.new_listener()),
+        ..state_db_only(db.clone())
+    };
+
+    // Spawn a server that shuts down upon request.
+    let drain_timeout = Duration::from_millis(500);
+    let spawn_server = |state: node_api::State| async move {
+        let router = node_api::router(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let config = node_api::ServeConfig {
+            drain_timeout,
+            ..Default::default()
+        }
+        .with_shutdown(async move { shutdown_rx.await.unwrap() });
+        let server = tokio::spawn(async move {
+            let conn_limit = node_api::DEFAULT_CONNECTION_LIMIT;
+            node_api::serve_with_config(&router, &listener, conn_limit, config).await
+        });
+        (port, shutdown_tx, server)
+    };
+
+    // Subscriptions are closed, and the server returns once drained.
+    let (port, shutdown_tx, server) = spawn_server(state.clone()).await;
+    let sse = reqwest_get(port, subscribe_validation::PATH).await;
+    assert!(sse.status().is_success());
+    let url = format!("ws://127.0.0.1:{port}{}", subscribe_ws::PATH);
+    let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+    let request = serde_json::json!({
+        "type": "subscribe",
+        "id": 1,
+        "subscription": { "kind": "validation" },
+    });
+    ws.send(Message::text(request.to_string())).await.unwrap();
+    let msg = ws.next().await.unwrap().unwrap();
+    let response: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(response["type"], "subscribed");
+
+    shutdown_tx.send(()).unwrap();
+    let body = tokio::time::timeout(Duration::from_secs(5), sse.text())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(body.ends_with(&format!("event: {CLOSE_EVENT}\ndata: shutdown\n\n")));
+    match ws.next().await.unwrap().unwrap() {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Away),
+        msg => panic!("expected a close frame, got {msg:?}"),
+    }
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(client().get(get_url(port, "/")).send().await.is_err());
+
+    // In-flight requests that complete in time receive their response.
+    let (port, shutdown_tx, server) = spawn_server(state.clone()).await;
+    let conn = db.acquire().await.unwrap();
+    let path = format!("{}?start=0&end=1", list_failed_blocks::PATH);
+    let in_flight = tokio::spawn(async move { client().get(get_url(port, &path)).send().await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown_tx.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(conn);
+    let response = in_flight.await.unwrap().unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // In-flight requests that do not complete in time are aborted.
+    let (port, shutdown_tx, server) = spawn_server(state).await;
+    let conn = db.acquire().await.unwrap();
+    let path = format!("{}?start=0&end=1", list_failed_blocks::PATH);
+    let stalled = tokio::spawn(async move { client().get(get_url(port, &path)).send().await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let start = std::time::Instant::now();
+    shutdown_tx.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(start.elapsed() >= drain_timeout);
+    assert!(stalled.await.unwrap().is_err());
+    drop(conn);
 }
 
 // -------------------------------------------------------------------
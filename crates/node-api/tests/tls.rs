@@ -0,0 +1,179 @@
+use essential_node_api::{self as node_api, tls::Tls};
+use std::{path::Path, time::Duration};
+use util::{state_db_only, test_conn_pool};
+
+mod util;
+
+const TLS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/tls");
+
+/// A client trusting the test CA, reporting the certificate of each response's server.
+fn tls_client() -> reqwest::ClientBuilder {
+    let ca = std::fs::read(Path::new(TLS_DIR).join("ca.pem")).unwrap();
+    reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(&ca).unwrap())
+        .tls_info(true)
+}
+
+/// The DER encoding of the test certificate with the given name.
+fn cert_der(name: &str) -> Vec<u8> {
+    let pem = std::fs::read(Path::new(TLS_DIR).join(format!("cert-{name}.pem"))).unwrap();
+    let cert = rustls_pemfile::certs(&mut &pem[..])
+        .next()
+        .unwrap()
+        .unwrap();
+    cert.to_vec()
+}
+
+/// Copy the test certificate and key with the given name to the given paths.
+fn copy_cert(name: &str, cert_path: &Path, key_path: &Path) {
+    let dir = Path::new(TLS_DIR);
+    std::fs::copy(dir.join(format!("cert-{name}.pem")), cert_path).unwrap();
+    std::fs::copy(dir.join(format!("key-{name}.pem")), key_path).unwrap();
+}
+
+fn peer_cert(response: &reqwest::Response) -> Vec<u8> {
+    response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .unwrap()
+        .peer_certificate()
+        .unwrap()
+        .to_vec()
+}
+
+/// Spawn a server using the given protocol and TLS configuration, returning its port.
+async fn spawn_server(protocol: node_api::HttpProtocol, tls: Tls) -> u16 {
+    let router = node_api::router(state_db_only(test_conn_pool()));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let config = node_api::ServeConfig {
+        protocol,
+        tls: Some(tls),
+        ..Default::default()
+    };
+    tokio::spawn(async move {
+        let conn_limit = node_api::DEFAULT_CONNECTION_LIMIT;
+        node_api::serve_with_config(&router, &listener, conn_limit, config).await
+    });
+    port
+}
+
+fn url(port: u16) -> String {
+    let path = node_api::endpoint::health_check::PATH;
+    format!("https://localhost:{port}{path}")
+}
+
+#[tokio::test]
+async fn test_tls_alpn() {
+    let dir = Path::new(TLS_DIR);
+    let tls = Tls::load(dir.join("cert-a.pem"), dir.join("key-a.pem")).unwrap();
+
+    // HTTP/2 is negotiated when the client supports it, otherwise HTTP/1.1.
+    let port = spawn_server(node_api::HttpProtocol::Auto, tls.clone()).await;
+    let response = tls_client().build().unwrap().get(url(port)).send().await;
+    let response = response.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+    assert_eq!(peer_cert(&response), cert_der("a"));
+    let client = tls_client().http1_only().build().unwrap();
+    let response = client.get(url(port)).send().await.unwrap();
+    assert_eq!(response.version(), reqwest::Version::HTTP_11);
+
+    // Only the configured protocol is offered.
+    let port = spawn_server(node_api::HttpProtocol::Http1, tls.clone()).await;
+    let response = tls_client().build().unwrap().get(url(port)).send().await;
+    assert_eq!(response.unwrap().version(), reqwest::Version::HTTP_11);
+    let port = spawn_server(node_api::HttpProtocol::Http2, tls).await;
+    let response = tls_client().build().unwrap().get(url(port)).send().await;
+    assert_eq!(response.unwrap().version(), reqwest::Version::HTTP_2);
+
+    // Plain HTTP is refused.
+    let response = reqwest::get(format!("http://localhost:{port}/")).await;
+    assert!(response.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tls_handshake_timeout() {
+    use tokio::io::AsyncReadExt;
+
+    let dir = Path::new(TLS_DIR);
+    let tls = Tls::load(dir.join("cert-a.pem"), dir.join("key-a.pem")).unwrap();
+    let port = spawn_server(node_api::HttpProtocol::Auto, tls).await;
+
+    // A client that never begins the handshake is disconnected.
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    let start = tokio::time::Instant::now();
+    let read = stream.read(&mut [0; 1]).await.unwrap();
+    assert_eq!(read, 0);
+    assert!(start.elapsed() >= node_api::tls::HANDSHAKE_TIMEOUT);
+}
+
+#[tokio::test]
+async fn test_tls_load_errors() {
+    let dir = Path::new(TLS_DIR);
+    let res = Tls::load(dir.join("missing.pem"), dir.join("key-a.pem"));
+    assert!(matches!(res, Err(node_api::tls::TlsError::Read { .. })));
+    let res = Tls::load(dir.join("key-a.pem"), dir.join("key-a.pem"));
+    assert!(matches!(
+        res,
+        Err(node_api::tls::TlsError::NoCertificates(_))
+    ));
+    let res = Tls::load(dir.join("cert-a.pem"), dir.join("cert-a.pem"));
+    assert!(matches!(res, Err(node_api::tls::TlsError::NoPrivateKey(_))));
+    let res = Tls::load(dir.join("cert-a.pem"), dir.join("key-b.pem"));
+    assert!(matches!(res, Err(node_api::tls::TlsError::Config(_))));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_tls_reload_on_sighup() {
+    let tmp = tempfile::tempdir().unwrap();
+    let cert_path = tmp.path().join("cert.pem");
+    let key_path = tmp.path().join("key.pem");
+    copy_cert("a", &cert_path, &key_path);
+    let tls = Tls::load(&cert_path, &key_path).unwrap();
+    tokio::spawn(tls.reload_on_sighup().unwrap());
+    let port = spawn_server(node_api::HttpProtocol::Auto, tls.clone()).await;
+
+    // Open a connection with the original certificate.
+    let client = tls_client().build().unwrap();
+    let response = client.get(url(port)).send().await.unwrap();
+    assert_eq!(peer_cert(&response), cert_der("a"));
+
+    // Replace the certificate and signal the reload.
+    copy_cert("b", &cert_path, &key_path);
+    let status = std::process::Command::new("kill")
+        .args(["-HUP", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // New connections are served with the new certificate.
+    let reloaded = async {
+        loop {
+            let client = tls_client().build().unwrap();
+            let response = client.get(url(port)).send().await.unwrap();
+            if peer_cert(&response) == cert_der("b") {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), reloaded)
+        .await
+        .unwrap();
+
+    // The existing connection remains open.
+    let response = client.get(url(port)).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(peer_cert(&response), cert_der("a"));
+
+    // A failed reload keeps the current certificate.
+    std::fs::write(&key_path, "").unwrap();
+    assert!(tls.reload().is_err());
+    let client = tls_client().build().unwrap();
+    let response = client.get(url(port)).send().await.unwrap();
+    assert_eq!(peer_cert(&response), cert_der("b"));
+}
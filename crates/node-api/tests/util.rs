@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use essential_node::{
     self as node,
     db::{
@@ -6,6 +8,7 @@ use essential_node::{
     },
 };
 use essential_node_api as node_api;
+use essential_node_types::BigBang;
 use std::future::Future;
 
 const LOCALHOST: &str = "127.0.0.1";
@@ -29,6 +32,16 @@ pub fn test_conn_pool() -> ConnectionPool {
     ConnectionPool::with_tables(&conf).unwrap()
 }
 
+/// A pool with a single connection, so that holding it stalls requests that
+/// query the DB.
+pub fn test_single_conn_pool() -> ConnectionPool {
+    let conf = Config {
+        source: Source::Memory(uuid::Uuid::new_v4().into()),
+        conn_limit: 1,
+    };
+    ConnectionPool::with_tables(&conf).unwrap()
+}
+
 pub fn client() -> reqwest::Client {
     reqwest::Client::builder()
         .http2_prior_knowledge() // Enforce HTTP/2
@@ -36,6 +49,13 @@ pub fn client() -> reqwest::Client {
         .unwrap()
 }
 
+pub fn http1_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .http1_only() // Enforce HTTP/1.1
+        .build()
+        .unwrap()
+}
+
 async fn test_listener() -> tokio::net::TcpListener {
     tokio::net::TcpListener::bind(format!("{LOCALHOST}:0"))
         .await
@@ -43,10 +63,17 @@ async fn test_listener() -> tokio::net::TcpListener {
 }
 
 /// A function that waits until the server at the given port is ready to receive requests.
-async fn await_server_online(port: u16, timeout_duration: std::time::Duration) {
+async fn await_server_online(
+    port: u16,
+    protocol: node_api::HttpProtocol,
+    timeout_duration: std::time::Duration,
+) {
     let server_ready = async {
         let mut interval = tokio::time::interval(std::time::Duration::from_millis(100));
-        let client = client();
+        let client = match protocol {
+            node_api::HttpProtocol::Http1 => http1_client(),
+            node_api::HttpProtocol::Http2 | node_api::HttpProtocol::Auto => client(),
+        };
         let url = format!("http://{LOCALHOST}:{port}/");
         loop {
             interval.tick().await;
@@ -70,17 +97,35 @@ pub async fn with_test_server<Fut>(
 where
     Fut: Future,
 {
+    with_test_server_protocol(state, node_api::HttpProtocol::Auto, f).await
+}
+
+/// Like [`with_test_server`], but only accepts connections using the given
+/// HTTP `protocol`.
+pub async fn with_test_server_protocol<Fut>(
+    state: node_api::State,
+    protocol: node_api::HttpProtocol,
+    f: impl FnOnce(u16) -> Fut,
+) -> Fut::Output
+where
+    Fut: Future,
+{
+    let config = node_api::ServeConfig {
+        protocol,
+        open_connections: state.metrics.open_connections.clone(),
+        ..Default::default()
+    };
     let router = node_api::router(state);
     let listener = test_listener().await;
     let port = listener.local_addr().unwrap().port();
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
     let api_jh = tokio::spawn(async move {
         tokio::select! {
-            _ = node_api::serve(&router, &listener, node_api::DEFAULT_CONNECTION_LIMIT) => {},
+            _ = node_api::serve_with_config(&router, &listener, node_api::DEFAULT_CONNECTION_LIMIT, config) => {},
             _ = shutdown_rx => {},
         }
     });
-    await_server_online(port, std::time::Duration::from_secs(3)).await;
+    await_server_online(port, protocol, std::time::Duration::from_secs(3)).await;
     let output = f(port).await;
     shutdown_tx.send(()).unwrap();
     api_jh.await.unwrap();
@@ -102,8 +147,30 @@ pub async fn reqwest_get(port: u16, endpoint_path: &str) -> reqwest::Response {
 
 /// State that only has a DB connection pool and no new block TX (for non-subscription tests).
 pub fn state_db_only(conn_pool: node::db::ConnectionPool) -> node_api::State {
+    let big_bang = BigBang::default();
+    let run_conf = node::RunConfig {
+        relayer_source_endpoint: None,
+        relayer_ca_bundle: None,
+        run_validation: false,
+    };
+    let node_config = node_api::NodeConfig::new(&big_bang, &run_conf);
     node_api::State {
         conn_pool,
+        contract_registry: big_bang.contract_registry.contract,
+        program_registry: big_bang.program_registry.contract,
         new_block: None,
+        validated_block: None,
+        list_blocks_limits: Default::default(),
+        metrics: Default::default(),
+        node_liveness: None,
+        max_validation_lag: node_api::DEFAULT_MAX_VALIDATION_LAG,
+        max_relayer_staleness: node_api::DEFAULT_MAX_RELAYER_STALENESS,
+        node_config,
+        auth: None,
+        // Tests issue requests faster than the default rate limit allows.
+        limiter: node_api::limits::Limiter::new(node_api::limits::ClientLimits {
+            rate_limit: None,
+            ..Default::default()
+        }),
     }
 }
@@ -6,6 +6,7 @@ use essential_node_types::{block_notify::BlockTx, BigBang};
 use std::{
     net::{SocketAddr, SocketAddrV4},
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 #[cfg(test)]
@@ -26,6 +27,10 @@ pub struct Args {
     /// use of Ethereum (or Ethereum test-net) as an L1.
     #[arg(long)]
     relayer_source_endpoint: Option<String>,
+    /// Path to a PEM bundle of CA certificates to trust, in addition to the system's, when the
+    /// relayer syncs from an HTTPS endpoint.
+    #[arg(long)]
+    relayer_ca_bundle: Option<PathBuf>,
     /// Disable the validation stream.
     #[arg(long)]
     disable_validation: bool,
@@ -61,6 +66,91 @@ pub struct Args {
     /// The maximum number of TCP streams to be served simultaneously.
     #[arg(long, default_value_t = node_api::DEFAULT_CONNECTION_LIMIT)]
     tcp_conn_limit: usize,
+    /// The time in milliseconds allowed for open API connections to complete their in-flight
+    /// requests upon shutdown, before they are aborted and the DB is closed.
+    ///
+    /// SSE subscribers receive a final `close` event and WebSocket subscribers a close frame.
+    #[arg(long, default_value_t = node_api::DEFAULT_DRAIN_TIMEOUT.as_millis() as u64)]
+    drain_timeout_ms: u64,
+    /// The HTTP protocol versions accepted by the API server.
+    ///
+    /// Both HTTP/1.1 and HTTP/2 are accepted by default. WebSocket subscriptions require
+    /// HTTP/1.1, so they are unavailable with `http2`.
+    #[arg(long, default_value_t = HttpProtocol::Auto, value_enum)]
+    http_protocol: HttpProtocol,
+    /// Path to a PEM certificate chain with which to serve the API over HTTPS.
+    ///
+    /// Requires `--tls-key`. HTTP/2 is negotiated with clients via ALPN. The certificate chain
+    /// and key are reloaded upon `SIGHUP`.
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+    /// Path to the PEM private key for the `--tls-cert` certificate chain.
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+    /// Path to an `auth.yml` configuration of the bearer tokens accepted by the API.
+    ///
+    /// This specifies static tokens and HMAC signing keys along with the scopes they grant, and
+    /// whether read endpoints remain public. If no configuration is specified, all endpoints are
+    /// public.
+    ///
+    /// To learn more, see the API docs for the `essential_node_api::auth` module.
+    #[arg(long)]
+    auth_config: Option<PathBuf>,
+    /// The number of requests per second replenished to each client's rate limit.
+    ///
+    /// Clients are identified by IP address, with IPv6 addresses grouped by their `/64` prefix.
+    /// Each request within an `/rpc` batch counts toward the limit. Requests beyond the limit are
+    /// refused with `429 Too Many Requests` and a `Retry-After` header. Specify `0` to disable
+    /// rate limiting.
+    ///
+    /// Behind a reverse proxy, all clients share the proxy's address and so its limits, unless
+    /// `--trust-forwarded-for` is specified.
+    #[arg(long, default_value_t = node_api::DEFAULT_RATE_LIMIT_PER_SEC)]
+    rate_limit_per_sec: u32,
+    /// The number of requests each client may make in a burst before being rate limited.
+    #[arg(long, default_value_t = node_api::DEFAULT_RATE_LIMIT_BURST)]
+    rate_limit_burst: u32,
+    /// The time in milliseconds allowed for non-streaming endpoints to respond.
+    ///
+    /// Requests that exceed this are refused with `503 Service Unavailable`. Specify `0` to
+    /// disable the timeout.
+    #[arg(long, default_value_t = node_api::DEFAULT_REQUEST_TIMEOUT.as_millis() as u64)]
+    request_timeout_ms: u64,
+    /// The maximum number of concurrent subscriptions for each client, across all of its
+    /// connections.
+    ///
+    /// This includes each subscription opened over a `subscribe-ws` connection. Specify `0` to
+    /// disable the cap.
+    #[arg(long, default_value_t = node_api::DEFAULT_MAX_SUBSCRIPTIONS_PER_CLIENT)]
+    max_subscriptions_per_client: usize,
+    /// Identify clients by the last address of the `X-Forwarded-For` header, rather than by the
+    /// address of their connection.
+    ///
+    /// Only specify this when the API is served behind a reverse proxy that appends the address
+    /// of each client to the header, as otherwise clients may evade their limits by claiming
+    /// any address.
+    #[arg(long)]
+    trust_forwarded_for: bool,
+    /// The maximum number of block numbers covered by a single `list-blocks` response.
+    ///
+    /// Responses to larger ranges end early with a cursor for the next request.
+    #[arg(long, default_value_t = node_api::DEFAULT_LIST_BLOCKS_MAX_RANGE)]
+    list_blocks_max_range: u64,
+    /// The number of encoded bytes after which a `list-blocks` response ends early with a cursor
+    /// for the next request.
+    ///
+    /// Responses are buffered in memory before they are sent, so this also bounds the memory
+    /// used by each request.
+    #[arg(long, default_value_t = node_api::DEFAULT_LIST_BLOCKS_MAX_BYTES)]
+    list_blocks_max_bytes: usize,
+    /// The number of synced blocks that may await validation before the `/ready` endpoint reports
+    /// that the node is not ready.
+    #[arg(long, default_value_t = node_api::DEFAULT_MAX_VALIDATION_LAG)]
+    max_validation_lag: u64,
+    /// The time in seconds the relayer may go without syncing a block or receiving a keep-alive
+    /// from the source before the `/ready` endpoint reports that the node is not ready.
+    #[arg(long, default_value_t = node_api::DEFAULT_MAX_RELAYER_STALENESS.as_secs())]
+    max_relayer_staleness_secs: u64,
     /// Specify a path to the `big-bang.yml` configuration.
     ///
     /// This specifies the genesis configuration, which includes items like the contract registry
@@ -83,6 +173,28 @@ enum Db {
     Persistent,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum HttpProtocol {
+    /// Only accept HTTP/1.1 connections.
+    Http1,
+    /// Only accept HTTP/2 connections with prior knowledge.
+    ///
+    /// WebSocket subscriptions are unavailable as they require HTTP/1.1.
+    Http2,
+    /// Detect the protocol of each connection, accepting both HTTP/1.1 and HTTP/2.
+    Auto,
+}
+
+impl From<HttpProtocol> for node_api::HttpProtocol {
+    fn from(protocol: HttpProtocol) -> Self {
+        match protocol {
+            HttpProtocol::Http1 => Self::Http1,
+            HttpProtocol::Http2 => Self::Http2,
+            HttpProtocol::Auto => Self::Auto,
+        }
+    }
+}
+
 // TODO: Lift this into the node lib?
 fn default_db_path() -> Option<PathBuf> {
     dirs::data_dir().map(|mut path| {
@@ -132,6 +244,33 @@ fn load_big_bang_or_default(path: Option<&Path>) -> anyhow::Result<BigBang> {
         }
     }
 }
+/// Load the API authentication configuration from the yml file at the given path.
+fn load_auth(path: &Path) -> anyhow::Result<node_api::auth::Auth> {
+    let auth_str =
+        std::fs::read_to_string(path).context("failed to read auth configuration from path")?;
+    let config = serde_yaml::from_str(&auth_str)
+        .context("failed to deserialize auth configuration from YAML string")?;
+    node_api::auth::Auth::new(config).context("invalid auth configuration")
+}
+
+/// Construct the API's per-client limits from the parsed args, where `0` disables a limit.
+fn client_limits_from_args(args: &Args) -> node_api::limits::ClientLimits {
+    let rate_limit = (args.rate_limit_per_sec > 0).then_some(node_api::limits::RateLimit {
+        per_sec: args.rate_limit_per_sec,
+        burst: args.rate_limit_burst.max(1),
+    });
+    let request_timeout =
+        (args.request_timeout_ms > 0).then(|| Duration::from_millis(args.request_timeout_ms));
+    let max_subscriptions =
+        (args.max_subscriptions_per_client > 0).then_some(args.max_subscriptions_per_client);
+    node_api::limits::ClientLimits {
+        rate_limit,
+        request_timeout,
+        max_subscriptions,
+        trust_forwarded_for: args.trust_forwarded_for,
+    }
+}
+
 /// Run the essential node.
 pub async fn run(args: Args) -> anyhow::Result<()> {
     // Initialise tracing.
@@ -149,6 +288,10 @@ pub async fn run(args: Args) -> anyhow::Result<()> {
     }
     let node_db = node::db::ConnectionPool::with_tables(&node_db_conf)?;
 
+    // Load the API authentication configuration, if any.
+    let auth = args.auth_config.as_deref().map(load_auth).transpose()?;
+    let client_limits = client_limits_from_args(&args);
+
     // Load the big bang configuration, and ensure the big bang block exists.
     let big_bang = load_big_bang_or_default(args.big_bang.as_deref())?;
     node::ensure_big_bang_block(&node_db, &big_bang)
@@ -186,15 +329,20 @@ pub async fn run(args: Args) -> anyhow::Result<()> {
 
     let run_conf = RunConfig {
         relayer_source_endpoint: relayer_source_endpoint.clone(),
+        relayer_ca_bundle: args.relayer_ca_bundle.clone(),
         run_validation: !disable_validation,
     };
+    let node_config = node_api::NodeConfig::new(&big_bang, &run_conf);
     let node_handle = node::run(
         node_db.clone(),
         run_conf,
-        big_bang.contract_registry.contract,
-        big_bang.program_registry.contract,
+        big_bang.contract_registry.contract.clone(),
+        big_bang.program_registry.contract.clone(),
         block_tx,
     )?;
+    let validated_block = (!disable_validation).then(|| node_handle.new_validation_listener());
+    let relayer_restarts = node_handle.relayer_restarts();
+    let node_liveness = node_handle.liveness();
     let node_future = async move {
         if relayer_source_endpoint.is_none() && disable_validation {
             std::future::pending().await
@@ -220,25 +368,92 @@ pub async fn run(args: Args) -> anyhow::Result<()> {
     let api_state = node_api::State {
         new_block: Some(block_rx),
         conn_pool: api_db.clone(),
+        contract_registry: big_bang.contract_registry.contract,
+        program_registry: big_bang.program_registry.contract,
+        validated_block,
+        list_blocks_limits: node_api::ListBlocksLimits {
+            max_range: args.list_blocks_max_range,
+            max_bytes: args.list_blocks_max_bytes,
+        },
+        metrics: node_api::metrics::Metrics {
+            relayer_restarts,
+            ..Default::default()
+        },
+        node_liveness: Some(node_liveness),
+        max_validation_lag: args.max_validation_lag,
+        max_relayer_staleness: Duration::from_secs(args.max_relayer_staleness_secs),
+        node_config,
+        auth,
+        limiter: node_api::limits::Limiter::new(client_limits),
     };
+    let open_connections = api_state.metrics.open_connections.clone();
     let router = node_api::router(api_state);
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => Some(
+            node_api::tls::Tls::load(cert, key)
+                .context("failed to load TLS certificate chain and key")?,
+        ),
+        _ => None,
+    };
     let listener = tokio::net::TcpListener::bind(args.bind_address).await?;
     #[cfg(feature = "tracing")]
-    tracing::info!("Starting API server at {}", listener.local_addr()?);
-    let api = node_api::serve(&router, &listener, args.tcp_conn_limit);
+    tracing::info!(
+        "Starting API server at {} ({})",
+        listener.local_addr()?,
+        if tls.is_some() { "HTTPS" } else { "HTTP" },
+    );
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let config = node_api::ServeConfig {
+        protocol: args.http_protocol.into(),
+        tls: tls.clone(),
+        open_connections,
+        drain_timeout: Duration::from_millis(args.drain_timeout_ms),
+        ..Default::default()
+    }
+    .with_shutdown(async move {
+        let _ = shutdown_rx.await;
+    });
+    let api = node_api::serve_with_config(&router, &listener, args.tcp_conn_limit, config);
+    tokio::pin!(api);
+
+    // Reload the TLS certificate chain and key upon `SIGHUP`.
+    #[cfg(unix)]
+    let tls_reload = {
+        let reload = tls
+            .as_ref()
+            .map(node_api::tls::Tls::reload_on_sighup)
+            .transpose()
+            .context("failed to listen for SIGHUP")?;
+        async move {
+            match reload {
+                Some(reload) => reload.await,
+                None => std::future::pending().await,
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let tls_reload = std::future::pending::<()>();
 
     // Select the first future to complete to close.
     // TODO: We should select over relayer / validation critical error here.
     let ctrl_c = tokio::signal::ctrl_c();
-    tokio::select! {
-        _ = api => {},
-        _ = ctrl_c => {},
+    let api_closed = tokio::select! {
+        _ = &mut api => true,
+        _ = tls_reload => false,
+        _ = ctrl_c => false,
         r = node_future => {
             if let Err(e) = r {
                 #[cfg(feature = "tracing")]
                 tracing::error!("Critical error on relayer or validation stream: {e}")
             }
+            false
         },
+    };
+
+    // Stop serving the API, allowing open connections to drain before closing the DBs.
+    if !api_closed {
+        let _ = shutdown_tx.send(());
+        api.await;
     }
 
     node_db.close().map_err(|e| anyhow::anyhow!("{e}"))?;
@@ -45,9 +45,27 @@ async fn test_node() -> (impl std::future::Future<Output = ()>, u16) {
         ..Default::default()
     };
     let db = node::db::ConnectionPool::with_tables(&config).unwrap();
+    let big_bang = BigBang::default();
+    let run_conf = node::RunConfig {
+        relayer_source_endpoint: None,
+        relayer_ca_bundle: None,
+        run_validation: false,
+    };
+    let node_config = node_api::NodeConfig::new(&big_bang, &run_conf);
     let api_state = node_api::State {
         new_block: Some(block_rx),
         conn_pool: db.clone(),
+        contract_registry: big_bang.contract_registry.contract,
+        program_registry: big_bang.program_registry.contract,
+        validated_block: None,
+        list_blocks_limits: Default::default(),
+        metrics: Default::default(),
+        node_liveness: None,
+        max_validation_lag: node_api::DEFAULT_MAX_VALIDATION_LAG,
+        max_relayer_staleness: node_api::DEFAULT_MAX_RELAYER_STALENESS,
+        node_config,
+        auth: None,
+        limiter: Default::default(),
     };
     let router = node_api::router(api_state);
     let listener = tokio::net::TcpListener::bind("0.0.0.0:0").await.unwrap();
@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS mutation_key ON mutation (key);
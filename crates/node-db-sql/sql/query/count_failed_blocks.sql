@@ -0,0 +1,4 @@
+SELECT
+    COUNT(*) AS count
+FROM
+    failed_block;
@@ -0,0 +1,10 @@
+SELECT
+    EXISTS (
+        SELECT
+            1
+        FROM
+            failed_block
+            JOIN block ON failed_block.block_id = block.id
+        WHERE
+            block.block_address = :block_address
+    ) AS failed;
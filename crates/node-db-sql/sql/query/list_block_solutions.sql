@@ -0,0 +1,17 @@
+SELECT
+    block.block_address,
+    block_solution_set.solution_set_index,
+    solution_set.content_addr,
+    solution.solution_index,
+    solution.contract_addr
+FROM
+    block
+    JOIN finalized_block ON finalized_block.block_id = block.id
+    JOIN block_solution_set ON block_solution_set.block_id = block.id
+    JOIN solution_set ON solution_set.id = block_solution_set.solution_set_id
+    JOIN solution ON solution.solution_set_id = solution_set.id
+WHERE
+    block.number = :block_number
+ORDER BY
+    block_solution_set.solution_set_index ASC,
+    solution.solution_index ASC;
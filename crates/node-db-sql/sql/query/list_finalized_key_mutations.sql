@@ -0,0 +1,16 @@
+SELECT
+    block_solution_set.solution_set_index,
+    mutation.value
+FROM
+    finalized_block
+    JOIN block_solution_set ON block_solution_set.block_id = finalized_block.block_id
+    JOIN solution ON solution.solution_set_id = block_solution_set.solution_set_id
+    JOIN mutation ON mutation.solution_id = solution.id
+WHERE
+    finalized_block.block_number = :block_number
+    AND solution.contract_addr = :contract_ca
+    AND mutation.key = :key
+ORDER BY
+    block_solution_set.solution_set_index ASC,
+    solution.solution_index ASC,
+    mutation.mutation_index ASC;
@@ -0,0 +1,14 @@
+SELECT
+    block.block_address,
+    block.number,
+    block_solution_set.solution_set_index
+FROM
+    solution_set
+    JOIN block_solution_set ON block_solution_set.solution_set_id = solution_set.id
+    JOIN block ON block_solution_set.block_id = block.id
+WHERE
+    solution_set.content_addr = :content_addr
+ORDER BY
+    block.number ASC,
+    block.block_address ASC,
+    block_solution_set.solution_set_index ASC;
@@ -0,0 +1,31 @@
+SELECT
+    key,
+    value
+FROM
+    (
+        SELECT
+            mutation.key,
+            mutation.value,
+            ROW_NUMBER() OVER (
+                PARTITION BY mutation.key
+                ORDER BY
+                    finalized_block.block_number DESC,
+                    block_solution_set.solution_set_index DESC
+            ) AS row_num
+        FROM
+            mutation
+            JOIN solution ON solution.id = mutation.solution_id
+            JOIN block_solution_set ON block_solution_set.solution_set_id = solution.solution_set_id
+            JOIN finalized_block ON finalized_block.block_id = block_solution_set.block_id
+        WHERE
+            solution.contract_addr = :contract_ca
+            AND mutation.key >= :key_lower
+            AND finalized_block.block_number <= :block_number
+    )
+WHERE
+    row_num = 1
+    AND length(value) > 0
+ORDER BY
+    key ASC
+LIMIT
+    :page_size;
@@ -17,13 +17,14 @@ macro_rules! decl_const_sql_str {
     };
 }
 
-/// Table creation statements.
+/// Table and index creation statements.
 pub mod create {
     decl_const_sql_str!(BLOCK, "create/block.sql");
     decl_const_sql_str!(BLOCK_SOLUTION_SET, "create/block_solution_set.sql");
     decl_const_sql_str!(FAILED_BLOCK, "create/failed_block.sql");
     decl_const_sql_str!(FINALIZED_BLOCK, "create/finalized_block.sql");
     decl_const_sql_str!(MUTATION, "create/mutation.sql");
+    decl_const_sql_str!(MUTATION_KEY_INDEX, "create/mutation_key_index.sql");
     decl_const_sql_str!(PRED_DATA, "create/pred_data.sql");
     decl_const_sql_str!(SOLUTION, "create/solution.sql");
     decl_const_sql_str!(SOLUTION_SET, "create/solution_set.sql");
@@ -46,6 +47,7 @@ pub mod insert {
 
 /// Statements for making queries.
 pub mod query {
+    decl_const_sql_str!(COUNT_FAILED_BLOCKS, "query/count_failed_blocks.sql");
     decl_const_sql_str!(GET_BLOCK_HEADER, "query/get_block_header.sql");
     decl_const_sql_str!(GET_BLOCK, "query/get_block.sql");
     decl_const_sql_str!(GET_LATEST_BLOCK_NUMBER, "query/get_latest_block_number.sql");
@@ -66,9 +68,20 @@ pub mod query {
     decl_const_sql_str!(GET_SOLUTION_PRED_DATA, "query/get_solution_pred_data.sql");
     decl_const_sql_str!(GET_STATE, "query/get_state.sql");
     decl_const_sql_str!(GET_VALIDATION_PROGRESS, "query/get_validation_progress.sql");
+    decl_const_sql_str!(IS_BLOCK_FAILED, "query/is_block_failed.sql");
+    decl_const_sql_str!(IS_BLOCK_FINALIZED, "query/is_block_finalized.sql");
+    decl_const_sql_str!(LIST_BLOCK_SOLUTIONS, "query/list_block_solutions.sql");
     decl_const_sql_str!(LIST_BLOCKS, "query/list_blocks.sql");
     decl_const_sql_str!(LIST_BLOCKS_BY_TIME, "query/list_blocks_by_time.sql");
     decl_const_sql_str!(LIST_FAILED_BLOCKS, "query/list_failed_blocks.sql");
+    decl_const_sql_str!(
+        LIST_FINALIZED_KEY_MUTATIONS,
+        "query/list_finalized_key_mutations.sql"
+    );
+    decl_const_sql_str!(
+        LIST_SOLUTION_SET_BLOCKS,
+        "query/list_solution_set_blocks.sql"
+    );
     decl_const_sql_str!(LIST_UNCHECKED_BLOCKS, "query/list_unchecked_blocks.sql");
     decl_const_sql_str!(
         QUERY_STATE_AT_BLOCK_FINALIZED,
@@ -82,6 +95,14 @@ pub mod query {
         QUERY_STATE_BLOCK_ADDRESS,
         "query/query_state_block_address.sql"
     );
+    decl_const_sql_str!(
+        QUERY_STATE_PREFIX_AT_BLOCK_FINALIZED,
+        "query/query_state_prefix_at_block_finalized.sql"
+    );
+    decl_const_sql_str!(
+        QUERY_STATE_PREFIX_UNBOUNDED_AT_BLOCK_FINALIZED,
+        "query/query_state_prefix_unbounded_at_block_finalized.sql"
+    );
 }
 
 /// Statements for updating and deleting state.
@@ -135,3 +156,27 @@ pub mod table {
         VALIDATION_PROGRESS,
     ];
 }
+
+pub mod index {
+    use crate::create;
+
+    /// An index's name along with its create statement.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+    pub struct Index {
+        /// The name of the index as declared in the create statement.
+        pub name: &'static str,
+        /// The index's create statement.
+        pub create: &'static str,
+    }
+
+    impl Index {
+        const fn new(name: &'static str, create: &'static str) -> Self {
+            Self { name, create }
+        }
+    }
+
+    pub const MUTATION_KEY: Index = Index::new("mutation_key", create::MUTATION_KEY_INDEX);
+
+    /// All indexes in a list. Must be created after the tables in [`crate::table::ALL`].
+    pub const ALL: &[Index] = &[MUTATION_KEY];
+}
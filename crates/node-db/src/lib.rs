@@ -21,19 +21,26 @@ use essential_types::{
     solution::{Mutation, Solution, SolutionSet},
     ContentAddress, Hash, Key, PredicateAddress, Value, Word,
 };
-use futures::Stream;
+use futures::{Stream, StreamExt};
 #[cfg(feature = "pool")]
 pub use pool::ConnectionPool;
 pub use query_range::address;
 pub use query_range::finalized;
 use rusqlite::{named_params, params, Connection, OptionalExtension, Transaction};
-use std::{ops::Range, time::Duration};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    ops::{ControlFlow, Range},
+    time::Duration,
+};
 
 mod error;
 #[cfg(feature = "pool")]
 pub mod pool;
 mod query_range;
 
+/// The version of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Types that may be provided to [`subscribe_blocks`] to provide access to
 /// [`Connection`]s while streaming.
 pub trait AcquireConnection {
@@ -56,11 +63,14 @@ pub trait AwaitNewBlock {
     async fn await_new_block(&mut self) -> Option<()>;
 }
 
-/// Create all tables.
+/// Create all tables, along with their indexes.
 pub fn create_tables(tx: &Transaction) -> rusqlite::Result<()> {
     for table in sql::table::ALL {
         tx.execute(table.create, ())?;
     }
+    for index in sql::index::ALL {
+        tx.execute(index.create, ())?;
+    }
     Ok(())
 }
 
@@ -395,6 +405,34 @@ pub fn get_validation_progress(conn: &Connection) -> Result<Option<ContentAddres
     Ok(value)
 }
 
+/// Returns whether or not the block with the given address has been finalized.
+pub fn is_block_finalized(
+    conn: &Connection,
+    block_address: &ContentAddress,
+) -> rusqlite::Result<bool> {
+    conn.query_row(
+        sql::query::IS_BLOCK_FINALIZED,
+        named_params! {
+            ":block_address": block_address.0,
+        },
+        |row| row.get("finalized"),
+    )
+}
+
+/// Returns whether or not the block with the given address has failed validation.
+pub fn is_block_failed(
+    conn: &Connection,
+    block_address: &ContentAddress,
+) -> rusqlite::Result<bool> {
+    conn.query_row(
+        sql::query::IS_BLOCK_FAILED,
+        named_params! {
+            ":block_address": block_address.0,
+        },
+        |row| row.get("failed"),
+    )
+}
+
 /// Given a block address, returns the addresses of blocks that have the next block number.
 pub fn get_next_block_addresses(
     conn: &Connection,
@@ -420,65 +458,75 @@ pub fn get_next_block_addresses(
 
 /// Lists all blocks in the given range.
 pub fn list_blocks(tx: &Transaction, block_range: Range<Word>) -> Result<Vec<Block>, QueryError> {
+    let mut blocks = vec![];
+    for_each_block(tx, block_range, |block| {
+        blocks.push(block);
+        ControlFlow::Continue(())
+    })?;
+    Ok(blocks)
+}
+
+/// Visits each block in the given range in order of block number, without
+/// collecting them into memory.
+///
+/// Blocks are read one at a time as the query rows are stepped. Visiting stops
+/// early if `f` returns [`ControlFlow::Break`].
+pub fn for_each_block(
+    tx: &Transaction,
+    block_range: Range<Word>,
+    mut f: impl FnMut(Block) -> ControlFlow<()>,
+) -> Result<(), QueryError> {
     let mut stmt = tx.prepare(sql::query::LIST_BLOCKS)?;
-    let rows = stmt.query_map(
-        named_params! {
-            ":start_block": block_range.start,
-            ":end_block": block_range.end,
-        },
-        |row| {
-            let block_address: essential_types::Hash = row.get("block_address")?;
-            let block_number: Word = row.get("number")?;
-            let timestamp_secs: u64 = row.get("timestamp_secs")?;
-            let timestamp_nanos: u32 = row.get("timestamp_nanos")?;
-            let solution_set_addr: Hash = row.get("content_addr")?;
-            let timestamp = Duration::new(timestamp_secs, timestamp_nanos);
-            Ok((
-                block_address,
-                block_number,
-                timestamp,
-                ContentAddress(solution_set_addr),
-            ))
-        },
-    )?;
+    let mut rows = stmt.query(named_params! {
+        ":start_block": block_range.start,
+        ":end_block": block_range.end,
+    })?;
 
     // Query yields in order of block number and solution set index.
-    let mut blocks: Vec<Block> = vec![];
-    let mut last_block_address = None;
-    for res in rows {
-        let (block_address, block_number, timestamp, solution_set_addr): (
-            essential_types::Hash,
-            Word,
-            Duration,
-            ContentAddress,
-        ) = res?;
+    let mut block: Option<(essential_types::Hash, Block)> = None;
+    while let Some(row) = rows.next()? {
+        let block_address: essential_types::Hash = row.get("block_address")?;
+        let block_number: Word = row.get("number")?;
+        let timestamp_secs: u64 = row.get("timestamp_secs")?;
+        let timestamp_nanos: u32 = row.get("timestamp_nanos")?;
+        let solution_set_addr: Hash = row.get("content_addr")?;
+        let timestamp = Duration::new(timestamp_secs, timestamp_nanos);
 
-        // Fetch the block associated with the block number, inserting it first if new.
-        let block = match last_block_address {
-            Some(b) if b == block_address => blocks.last_mut().expect("last block must exist"),
+        // Visit the previous block once all of its solution sets are read.
+        match block {
+            Some((addr, _)) if addr == block_address => (),
             _ => {
-                last_block_address = Some(block_address);
-                blocks.push(Block {
+                let new_block = Block {
                     header: BlockHeader {
                         number: block_number,
                         timestamp,
                     },
                     solution_sets: vec![],
-                });
-                blocks.last_mut().expect("last block must exist")
+                };
+                if let Some((_, prev)) = block.replace((block_address, new_block)) {
+                    if f(prev).is_break() {
+                        return Ok(());
+                    }
+                }
             }
-        };
+        }
 
         // Add the solution set.
         // If there are performance issues, use statements in `get_solution_set` directly.
         // See https://github.com/essential-contributions/essential-node/issues/154.
-        let solution_set = get_solution_set(tx, &solution_set_addr)?;
-        block.solution_sets.push(solution_set);
+        let solution_set = get_solution_set(tx, &ContentAddress(solution_set_addr))?;
+        let (_, current) = block.as_mut().expect("block must exist");
+        current.solution_sets.push(solution_set);
     }
-    Ok(blocks)
+    if let Some((_, last)) = block {
+        let _ = f(last);
+    }
+    Ok(())
 }
 
 /// Lists blocks and their solution sets within a specific time range with pagination.
+///
+/// Pages are counted in blocks, so each block is returned whole.
 pub fn list_blocks_by_time(
     tx: &Transaction,
     range: Range<Duration>,
@@ -500,13 +548,13 @@ pub fn list_blocks_by_time(
             let block_number: Word = row.get("number")?;
             let timestamp_secs: u64 = row.get("timestamp_secs")?;
             let timestamp_nanos: u32 = row.get("timestamp_nanos")?;
-            let solution_set_addr: Hash = row.get("content_addr")?;
+            let solution_set_addr: Option<Hash> = row.get("content_addr")?;
             let timestamp = Duration::new(timestamp_secs, timestamp_nanos);
             Ok((
                 block_address,
                 block_number,
                 timestamp,
-                ContentAddress(solution_set_addr),
+                solution_set_addr.map(ContentAddress),
             ))
         },
     )?;
@@ -519,7 +567,7 @@ pub fn list_blocks_by_time(
             essential_types::Hash,
             Word,
             Duration,
-            ContentAddress,
+            Option<ContentAddress>,
         ) = res?;
 
         // Fetch the block associated with the block number, inserting it first if new.
@@ -538,11 +586,13 @@ pub fn list_blocks_by_time(
             }
         };
 
-        // Add the solution set.
+        // Add the solution set, if the block has any.
         // If there are performance issues, use statements in `get_solution_set` directly.
         // See https://github.com/essential-contributions/essential-node/issues/154.
-        let solution_set = get_solution_set(tx, &solution_set_addr)?;
-        block.solution_sets.push(solution_set);
+        if let Some(solution_set_addr) = solution_set_addr {
+            let solution_set = get_solution_set(tx, &solution_set_addr)?;
+            block.solution_sets.push(solution_set);
+        }
     }
     Ok(blocks)
 }
@@ -573,6 +623,41 @@ pub fn list_failed_blocks(
     Ok(failed_blocks)
 }
 
+/// Count the failed blocks, i.e. the number of recorded block and solution set failures.
+pub fn count_failed_blocks(conn: &Connection) -> Result<u64, QueryError> {
+    let mut stmt = conn.prepare(sql::query::COUNT_FAILED_BLOCKS)?;
+    let count = stmt.query_row([], |row| row.get("count"))?;
+    Ok(count)
+}
+
+/// List the blocks that include the solution set with the given content address.
+///
+/// Returns each inclusion as (block address, block number, solution set index),
+/// ordered by block number.
+pub fn list_solution_set_blocks(
+    conn: &Connection,
+    solution_set_addr: &ContentAddress,
+) -> Result<Vec<(ContentAddress, Word, u64)>, QueryError> {
+    let mut stmt = conn.prepare(sql::query::LIST_SOLUTION_SET_BLOCKS)?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":content_addr": solution_set_addr.0,
+        },
+        |row| {
+            let block_address: Hash = row.get("block_address")?;
+            let block_number: Word = row.get("number")?;
+            let solution_set_index: u64 = row.get("solution_set_index")?;
+            Ok((
+                ContentAddress(block_address),
+                block_number,
+                solution_set_index,
+            ))
+        },
+    )?;
+    let blocks = rows.collect::<Result<Vec<_>, _>>()?;
+    Ok(blocks)
+}
+
 /// Lists all unchecked blocks in the given range.
 pub fn list_unchecked_blocks(
     tx: &Transaction,
@@ -692,6 +777,211 @@ pub fn subscribe_blocks(
     })
 }
 
+/// A solution's block address, solution set index, solution set address, solution index
+/// and contract address, as listed by [`list_block_solutions`].
+pub type BlockSolution = (ContentAddress, u64, ContentAddress, u64, ContentAddress);
+
+/// Lists the solutions within the finalized block with the given block number.
+///
+/// Returns the block address, solution set index, solution set address, solution index
+/// and contract address of each solution, in order of solution set index and solution index.
+pub fn list_block_solutions(
+    conn: &Connection,
+    block_number: Word,
+) -> Result<Vec<BlockSolution>, QueryError> {
+    let mut stmt = conn.prepare(sql::query::LIST_BLOCK_SOLUTIONS)?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":block_number": block_number,
+        },
+        |row| {
+            let block_addr: Hash = row.get("block_address")?;
+            let solution_set_index: u64 = row.get("solution_set_index")?;
+            let solution_set_addr: Hash = row.get("content_addr")?;
+            let solution_index: u64 = row.get("solution_index")?;
+            let contract_addr: Hash = row.get("contract_addr")?;
+            Ok((
+                ContentAddress(block_addr),
+                solution_set_index,
+                ContentAddress(solution_set_addr),
+                solution_index,
+                ContentAddress(contract_addr),
+            ))
+        },
+    )?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Get the number of the latest block, or `None` if there are no blocks.
+pub fn get_latest_block_number(conn: &Connection) -> Result<Option<Word>, QueryError> {
+    let mut stmt = conn.prepare(sql::query::GET_LATEST_BLOCK_NUMBER)?;
+    let number = stmt.query_row([], |row| row.get::<_, Option<Word>>("number"))?;
+    Ok(number)
+}
+
+/// Lists the mutations to the given key within the finalized block with the given number.
+///
+/// Returns the solution set index and value of each mutation in the order they were applied.
+pub fn list_finalized_key_mutations(
+    conn: &Connection,
+    contract_ca: &ContentAddress,
+    key: &Key,
+    block_number: Word,
+) -> Result<Vec<(u64, Value)>, QueryError> {
+    let mut stmt = conn.prepare(sql::query::LIST_FINALIZED_KEY_MUTATIONS)?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":contract_ca": contract_ca.0,
+            ":key": blob_from_words(key),
+            ":block_number": block_number,
+        },
+        |row| {
+            let solution_set_index: u64 = row.get("solution_set_index")?;
+            let value: Vec<u8> = row.get("value")?;
+            Ok((solution_set_index, words_from_blob(&value)))
+        },
+    )?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Subscribe to all changes to the given key within finalized blocks from the
+/// given starting block number.
+///
+/// Each change is yielded as the block number, solution set index and new value.
+///
+/// Connections are acquired and new blocks awaited in the same manner as
+/// [`subscribe_blocks`], though the stream only progresses past a block once
+/// it has been finalized.
+pub fn subscribe_state(
+    contract_ca: ContentAddress,
+    key: Key,
+    start_block: Word,
+    acquire_conn: impl AcquireConnection,
+    await_new_block: impl AwaitNewBlock,
+) -> impl Stream<Item = Result<(Word, u64, Value), QueryError>> {
+    // List the key's mutations within a block if it is finalized.
+    let list_changes = move |conn: &mut Connection, block_number: Word| {
+        let tx = conn.transaction()?;
+        let latest = match get_latest_finalized_block_address(&tx)? {
+            Some(addr) => get_block_header(&tx, &addr)?.map(|header| header.number),
+            None => None,
+        };
+        match latest {
+            Some(latest) if latest >= block_number => (),
+            _ => return Ok(None),
+        }
+        let mutations = list_finalized_key_mutations(&tx, &contract_ca, &key, block_number)?;
+        drop(tx);
+        let changes = mutations
+            .into_iter()
+            .map(|(ss_ix, value)| (block_number, ss_ix, value))
+            .collect();
+        Ok(Some(changes))
+    };
+    subscribe_by_block(start_block, acquire_conn, await_new_block, list_changes)
+}
+
+/// Subscribe to all solutions that solve a predicate of one of the given
+/// contracts, within finalized blocks from the given starting block number.
+///
+/// Each solution is yielded along with the number and address of its block and
+/// the index of its solution set within the block.
+///
+/// Connections are acquired and new blocks awaited in the same manner as
+/// [`subscribe_blocks`], though the stream only progresses past a block once
+/// it has been finalized.
+pub fn subscribe_contract_solutions(
+    contracts: Vec<ContentAddress>,
+    start_block: Word,
+    acquire_conn: impl AcquireConnection,
+    await_new_block: impl AwaitNewBlock,
+) -> impl Stream<Item = Result<(Word, ContentAddress, u64, Solution), QueryError>> {
+    let contracts: HashSet<ContentAddress> = contracts.into_iter().collect();
+
+    // List the matching solutions within a block if it is finalized.
+    let list_solutions = move |conn: &mut Connection, block_number: Word| {
+        let tx = conn.transaction()?;
+        let latest = match get_latest_finalized_block_address(&tx)? {
+            Some(addr) => get_block_header(&tx, &addr)?.map(|header| header.number),
+            None => None,
+        };
+        match latest {
+            Some(latest) if latest >= block_number => (),
+            _ => return Ok(None),
+        }
+        let mut solutions = vec![];
+        let mut solution_sets: HashMap<ContentAddress, SolutionSet> = HashMap::new();
+        for (block_addr, ss_ix, ss_addr, solution_ix, contract) in
+            list_block_solutions(&tx, block_number)?
+        {
+            if !contracts.contains(&contract) {
+                continue;
+            }
+            // Only fetch each solution set once, and only if it contains a match.
+            let solution_set = match solution_sets.entry(ss_addr) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let solution_set = get_solution_set(&tx, entry.key())?;
+                    entry.insert(solution_set)
+                }
+            };
+            let solution = solution_set.solutions[solution_ix as usize].clone();
+            solutions.push((block_number, block_addr, ss_ix, solution));
+        }
+        drop(tx);
+        Ok(Some(solutions))
+    };
+    subscribe_by_block(start_block, acquire_conn, await_new_block, list_solutions)
+}
+
+/// Stream the items produced by `list_items` for each block from the given
+/// starting block number.
+///
+/// The `list_items` function returns `None` in the case that the block is not
+/// yet ready, in which case `await_new_block` is awaited before trying again.
+fn subscribe_by_block<T>(
+    start_block: Word,
+    acquire_conn: impl AcquireConnection,
+    await_new_block: impl AwaitNewBlock,
+    list_items: impl Fn(&mut Connection, Word) -> Result<Option<Vec<T>>, QueryError>,
+) -> impl Stream<Item = Result<T, QueryError>> {
+    let init = (start_block, acquire_conn, await_new_block, list_items);
+    let blocks = futures::stream::unfold(
+        init,
+        move |(block_ix, acq_conn, mut new_block, list_items)| async move {
+            loop {
+                // Acquire a connection and query for the current block's items.
+                let mut conn = acq_conn.acquire_connection().await?;
+                let res = list_items(conn.as_mut(), block_ix);
+                // Drop the connection ASAP in case it needs returning to a pool.
+                std::mem::drop(conn);
+                match res {
+                    // If some error occurred, emit the error.
+                    Err(err) => {
+                        return Some((Err(err), (block_ix, acq_conn, new_block, list_items)))
+                    }
+                    // If the block is not yet ready, await the next.
+                    Ok(None) => new_block.await_new_block().await?,
+                    // If the block is ready, emit its items.
+                    Ok(Some(items)) => {
+                        let next_ix = block_ix + 1;
+                        return Some((Ok(items), (next_ix, acq_conn, new_block, list_items)));
+                    }
+                }
+            }
+        },
+    );
+
+    // Flatten each block's items into individual items.
+    blocks.flat_map(|res| {
+        let items: Vec<_> = match res {
+            Ok(items) => items.into_iter().map(Ok).collect(),
+            Err(err) => vec![Err(err)],
+        };
+        futures::stream::iter(items)
+    })
+}
+
 /// Short-hand for constructing a transaction, providing it as an argument to
 /// the given function, then committing the transaction before returning.
 pub fn with_tx<T, E>(
@@ -6,7 +6,10 @@
 use crate::{with_tx, AcquireConnection, AwaitNewBlock, QueryError};
 use core::ops::Range;
 use essential_node_types::{block_notify::BlockRx, Block};
-use essential_types::{solution::SolutionSet, ContentAddress, Key, Value, Word};
+use essential_types::{
+    solution::{Solution, SolutionSet},
+    ContentAddress, Key, Value, Word,
+};
 use futures::Stream;
 use rusqlite_pool::tokio::{AsyncConnectionHandle, AsyncConnectionPool};
 use std::{path::PathBuf, sync::Arc, time::Duration};
@@ -121,6 +124,19 @@ impl ConnectionPool {
         self.0.try_acquire().map(ConnectionHandle)
     }
 
+    /// The total number of connections managed by the pool.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// The number of connections currently available for acquisition.
+    ///
+    /// The number of connections in use is the [`capacity`][Self::capacity]
+    /// minus this.
+    pub fn available(&self) -> usize {
+        self.0.available()
+    }
+
     /// Close a connection pool, returning a `ConnectionCloseErrors` in the case of any errors.
     pub fn close(&self) -> Result<(), ConnectionCloseErrors> {
         let res = self.0.close();
@@ -218,6 +234,15 @@ impl ConnectionPool {
             .await
     }
 
+    /// List the blocks that include the solution set with the given content address.
+    pub async fn list_solution_set_blocks(
+        &self,
+        ca: ContentAddress,
+    ) -> Result<Vec<(ContentAddress, Word, u64)>, AcquireThenQueryError> {
+        self.acquire_then(move |h| crate::list_solution_set_blocks(h, &ca))
+            .await
+    }
+
     /// Fetches the state value for the given contract content address and key pair.
     pub async fn query_state(
         &self,
@@ -325,6 +350,31 @@ impl ConnectionPool {
         .await
     }
 
+    /// Lists the most recent state `(key, value)` pairs for the given contract
+    /// whose keys start with the given prefix within a range of blocks inclusive. `..=block`.
+    ///
+    /// See [`crate::finalized::query_state_prefix_inclusive_block`].
+    pub async fn query_state_prefix_finalized_inclusive_block(
+        &self,
+        contract_ca: ContentAddress,
+        key_prefix: Vec<Word>,
+        block_number: Word,
+        cursor: Option<Key>,
+        page_size: i64,
+    ) -> Result<Vec<(Key, Value)>, AcquireThenQueryError> {
+        self.acquire_then(move |h| {
+            crate::finalized::query_state_prefix_inclusive_block(
+                h,
+                &contract_ca,
+                &key_prefix,
+                block_number,
+                cursor.as_ref(),
+                page_size,
+            )
+        })
+        .await
+    }
+
     /// Get the validation progress, returning the last block hash.
     pub async fn get_validation_progress(
         &self,
@@ -361,6 +411,8 @@ impl ConnectionPool {
     }
 
     /// Lists blocks and their solution sets within a specific time range with pagination.
+    ///
+    /// Pages are counted in blocks, so each block is returned whole.
     pub async fn list_blocks_by_time(
         &self,
         range: Range<Duration>,
@@ -375,6 +427,24 @@ impl ConnectionPool {
         .await
     }
 
+    /// List failed blocks as (block number, solution set address) within the given range.
+    pub async fn list_failed_blocks(
+        &self,
+        block_range: Range<Word>,
+    ) -> Result<Vec<(Word, ContentAddress)>, AcquireThenQueryError> {
+        self.acquire_then(move |h| crate::list_failed_blocks(h, block_range))
+            .await
+    }
+
+    /// Lists all unchecked blocks in the given range.
+    pub async fn list_unchecked_blocks(
+        &self,
+        block_range: Range<Word>,
+    ) -> Result<Vec<Block>, AcquireThenQueryError> {
+        self.acquire_then(move |h| with_tx(h, |tx| crate::list_unchecked_blocks(tx, block_range)))
+            .await
+    }
+
     /// Subscribe to all blocks from the given starting block number.
     pub fn subscribe_blocks(
         &self,
@@ -383,6 +453,33 @@ impl ConnectionPool {
     ) -> impl Stream<Item = Result<Block, QueryError>> {
         crate::subscribe_blocks(start_block, self.clone(), await_new_block)
     }
+
+    /// Subscribe to all changes to the given key within finalized blocks from
+    /// the given starting block number.
+    ///
+    /// See [`crate::subscribe_state`].
+    pub fn subscribe_state(
+        &self,
+        contract_ca: ContentAddress,
+        key: Key,
+        start_block: Word,
+        await_new_block: impl AwaitNewBlock,
+    ) -> impl Stream<Item = Result<(Word, u64, Value), QueryError>> {
+        crate::subscribe_state(contract_ca, key, start_block, self.clone(), await_new_block)
+    }
+
+    /// Subscribe to all solutions that solve a predicate of one of the given
+    /// contracts, within finalized blocks from the given starting block number.
+    ///
+    /// See [`crate::subscribe_contract_solutions`].
+    pub fn subscribe_contract_solutions(
+        &self,
+        contracts: Vec<ContentAddress>,
+        start_block: Word,
+        await_new_block: impl AwaitNewBlock,
+    ) -> impl Stream<Item = Result<(Word, ContentAddress, u64, Solution), QueryError>> {
+        crate::subscribe_contract_solutions(contracts, start_block, self.clone(), await_new_block)
+    }
 }
 
 impl Config {
@@ -97,3 +97,77 @@ pub fn query_state_exclusive_solution_set(
         None => query_state_exclusive_block(conn, contract_ca, key, block_number),
     }
 }
+
+/// List the most recent `(key, value)` pairs in a contract's state whose key
+/// starts with the given `key_prefix`, as set at or before the given block number.
+///
+/// This is inclusive of the block's state (..=block_number).
+///
+/// Pairs are ordered by the byte representation of their key. Only keys
+/// greater than the `cursor` key are returned, up to `page_size` pairs.
+/// Keys whose most recent value is empty (i.e. deleted) are omitted.
+pub fn query_state_prefix_inclusive_block(
+    conn: &Connection,
+    contract_ca: &ContentAddress,
+    key_prefix: &[Word],
+    block_number: Word,
+    cursor: Option<&Key>,
+    page_size: i64,
+) -> Result<Vec<(Key, Value)>, QueryError> {
+    // Scan the range of keys starting with the prefix, beginning after the cursor.
+    let key_prefix = blob_from_words(key_prefix);
+    let key_lower = match cursor {
+        Some(cursor) => {
+            let mut after_cursor = blob_from_words(cursor);
+            after_cursor.push(0);
+            after_cursor.max(key_prefix.clone())
+        }
+        None => key_prefix.clone(),
+    };
+    let map_row = |row: &rusqlite::Row| {
+        let key: Vec<u8> = row.get("key")?;
+        let value: Vec<u8> = row.get("value")?;
+        Ok((words_from_blob(&key), words_from_blob(&value)))
+    };
+    let rows = match prefix_upper_bound(&key_prefix) {
+        Some(key_upper) => {
+            let mut stmt = conn.prepare(sql::query::QUERY_STATE_PREFIX_AT_BLOCK_FINALIZED)?;
+            let rows = stmt.query_map(
+                named_params! {
+                    ":contract_ca": contract_ca.0,
+                    ":key_lower": key_lower,
+                    ":key_upper": key_upper,
+                    ":block_number": block_number,
+                    ":page_size": page_size,
+                },
+                map_row,
+            )?;
+            rows.collect::<Result<_, _>>()?
+        }
+        None => {
+            let mut stmt =
+                conn.prepare(sql::query::QUERY_STATE_PREFIX_UNBOUNDED_AT_BLOCK_FINALIZED)?;
+            let rows = stmt.query_map(
+                named_params! {
+                    ":contract_ca": contract_ca.0,
+                    ":key_lower": key_lower,
+                    ":block_number": block_number,
+                    ":page_size": page_size,
+                },
+                map_row,
+            )?;
+            rows.collect::<Result<_, _>>()?
+        }
+    };
+    Ok(rows)
+}
+
+/// The least blob greater than every blob starting with the given prefix.
+///
+/// Returns `None` if there is no such blob, i.e. the prefix is empty or all `0xFF`.
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|&byte| byte != u8::MAX)?;
+    let mut upper = prefix[..=last].to_vec();
+    upper[last] += 1;
+    Some(upper)
+}
@@ -23,4 +23,16 @@ fn create_tables() {
             table.name,
         );
     }
+
+    // Verify that each index exists.
+    for index in node_db::sql::index::ALL {
+        let query = format!(
+            "SELECT name FROM sqlite_master WHERE type='index' AND name='{}';",
+            index.name,
+        );
+        let result: String = conn
+            .query_row(&query, (), |row| row.get(0))
+            .unwrap_or_else(|_| panic!("Index {} does not exist", index.name));
+        assert_eq!(result, index.name);
+    }
 }
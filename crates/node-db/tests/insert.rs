@@ -232,6 +232,7 @@ fn test_failed_block() {
     assert_eq!(r.len(), 2);
     assert_eq!(&blocks[0], &r[0]);
     assert_eq!(&blocks[1], &r[1]);
+    assert_eq!(node_db::count_failed_blocks(&conn).unwrap(), 0);
 
     // Insert failed block.
     let block_address = content_addr(&blocks[0]);
@@ -248,6 +249,7 @@ fn test_failed_block() {
     node_db::insert_failed_block(&conn, &block_address, &solution_set_addr).unwrap();
     let failed_blocks = node_db::list_failed_blocks(&conn, 0..(NUM_BLOCKS + 10)).unwrap();
     assert_eq!(failed_blocks.len(), 1);
+    assert_eq!(node_db::count_failed_blocks(&conn).unwrap(), 1);
     assert_eq!(failed_blocks[0].0, blocks[0].header.number);
     assert_eq!(failed_blocks[0].1, solution_set_addr);
 
@@ -268,6 +270,7 @@ fn test_failed_block() {
     assert_eq!(failed_blocks.len(), 2);
     assert_eq!(failed_blocks[1].0, blocks[1].header.number);
     assert_eq!(failed_blocks[1].1, solution_set_addr);
+    assert_eq!(node_db::count_failed_blocks(&conn).unwrap(), 2);
 }
 
 #[test]
@@ -1,7 +1,7 @@
 use essential_hash::content_addr;
 use essential_node_db::{self as node_db};
 use essential_node_types::{Block, BlockHeader};
-use essential_types::{ContentAddress, Word};
+use essential_types::{solution::Mutation, ContentAddress, Key, Word};
 use std::time::Duration;
 use util::{test_block, test_blocks_with_vars, test_conn};
 
@@ -71,6 +71,51 @@ fn test_get_repeated_solution_set() {
     assert_eq!(block2, fetched_block2);
 }
 
+#[test]
+fn test_list_solution_set_blocks() {
+    // The test solution sets and blocks.
+    let solution_set = util::test_solution_set(42);
+    let solution_set2 = util::test_solution_set(43);
+    let block = Block {
+        header: BlockHeader {
+            number: 1,
+            timestamp: Duration::from_secs(1),
+        },
+        solution_sets: vec![solution_set.clone()],
+    };
+    let block2 = Block {
+        header: BlockHeader {
+            number: 2,
+            timestamp: Duration::from_secs(2),
+        },
+        solution_sets: vec![solution_set2.clone(), solution_set.clone()],
+    };
+
+    // Create an in-memory SQLite database.
+    let mut conn = test_conn();
+
+    // Create the necessary tables and insert the blocks.
+    let tx = conn.transaction().unwrap();
+    node_db::create_tables(&tx).unwrap();
+    node_db::insert_block(&tx, &block).unwrap();
+    node_db::insert_block(&tx, &block2).unwrap();
+
+    // The first solution set is included in both blocks.
+    let inclusions = node_db::list_solution_set_blocks(&tx, &content_addr(&solution_set)).unwrap();
+    assert_eq!(
+        inclusions,
+        vec![(content_addr(&block), 1, 0), (content_addr(&block2), 2, 1)]
+    );
+
+    // The second solution set is only included in the second block.
+    let inclusions = node_db::list_solution_set_blocks(&tx, &content_addr(&solution_set2)).unwrap();
+    assert_eq!(inclusions, vec![(content_addr(&block2), 2, 0)]);
+
+    // Unknown solution sets are not included in any block.
+    let inclusions = node_db::list_solution_set_blocks(&tx, &ContentAddress([0; 32])).unwrap();
+    assert!(inclusions.is_empty());
+}
+
 #[test]
 fn test_block_solution_set_ordering() {
     // The test solution set and blocks.
@@ -141,6 +186,46 @@ fn test_get_validation_progress() {
     assert_eq!(fetched_block_address, block_address);
 }
 
+#[test]
+fn test_is_block_finalized_and_failed() {
+    // The test blocks.
+    let blocks = util::test_blocks(3);
+    let block_addrs: Vec<_> = blocks.iter().map(content_addr).collect();
+
+    // Create an in-memory SQLite database.
+    let mut conn = test_conn();
+
+    // Create the necessary tables, insert the blocks and finalize the first.
+    node_db::with_tx(&mut conn, |tx| {
+        node_db::create_tables(tx).unwrap();
+        for block in &blocks {
+            node_db::insert_block(tx, block).unwrap();
+        }
+        node_db::finalize_block(tx, &block_addrs[0])
+    })
+    .unwrap();
+
+    // Mark the second block as failed.
+    let solution_set_addr = content_addr(&blocks[1].solution_sets[0]);
+    node_db::insert_failed_block(&conn, &block_addrs[1], &solution_set_addr).unwrap();
+
+    let finalized: Vec<_> = block_addrs
+        .iter()
+        .map(|ca| node_db::is_block_finalized(&conn, ca).unwrap())
+        .collect();
+    let failed: Vec<_> = block_addrs
+        .iter()
+        .map(|ca| node_db::is_block_failed(&conn, ca).unwrap())
+        .collect();
+    assert_eq!(finalized, vec![true, false, false]);
+    assert_eq!(failed, vec![false, true, false]);
+
+    // Unknown blocks are neither finalized nor failed.
+    let unknown = ContentAddress([0xFF; 32]);
+    assert!(!node_db::is_block_finalized(&conn, &unknown).unwrap());
+    assert!(!node_db::is_block_failed(&conn, &unknown).unwrap());
+}
+
 #[test]
 fn test_list_blocks() {
     // The test blocks.
@@ -161,6 +246,44 @@ fn test_list_blocks() {
     assert_eq!(blocks, fetched_blocks);
 }
 
+#[test]
+fn test_for_each_block() {
+    use std::ops::ControlFlow;
+
+    // The test blocks.
+    let blocks = util::test_blocks(10);
+
+    // Create an in-memory SQLite database.
+    let mut conn = test_conn();
+    let tx = conn.transaction().unwrap();
+    node_db::create_tables(&tx).unwrap();
+    for block in &blocks {
+        node_db::insert_block(&tx, block).unwrap();
+    }
+
+    // Visit all blocks within the range.
+    let mut visited = vec![];
+    node_db::for_each_block(&tx, 2..8, |block| {
+        visited.push(block);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(&blocks[2..8], &visited);
+
+    // Stop visiting early.
+    let mut visited = vec![];
+    node_db::for_each_block(&tx, 0..10, |block| {
+        visited.push(block);
+        if visited.len() == 3 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+    .unwrap();
+    assert_eq!(&blocks[..3], &visited);
+}
+
 #[test]
 fn test_list_blocks_by_time() {
     // The test blocks.
@@ -435,6 +558,103 @@ fn test_query_at_finalized() {
     );
 }
 
+#[test]
+fn test_query_state_prefix() {
+    let contract_addr = ContentAddress([42; 32]);
+    let mutation = |key: &[Word], value: &[Word]| Mutation {
+        key: key.to_vec(),
+        value: value.to_vec(),
+    };
+    let mutations = [
+        vec![
+            mutation(&[0, 1], &[1]),
+            mutation(&[0, 2], &[2]),
+            mutation(&[1, 1], &[3]),
+            mutation(&[0, 3], &[4]),
+        ],
+        vec![
+            mutation(&[0, 2], &[22]),
+            mutation(&[0, 3], &[]),
+            mutation(&[-1, 1], &[6]),
+        ],
+        vec![mutation(&[0, 4], &[5])],
+    ];
+    let blocks: Vec<_> = mutations
+        .into_iter()
+        .enumerate()
+        .map(|(i, mutations)| {
+            let mut block = test_block(i as Word, Duration::from_secs(i as u64));
+            let solution = &mut block.solution_sets[0].solutions[0];
+            solution.predicate_to_solve.contract = contract_addr.clone();
+            solution.state_mutations = mutations;
+            block
+        })
+        .collect();
+
+    // Create an in-memory SQLite database.
+    let mut conn = test_conn();
+    let tx = conn.transaction().unwrap();
+    node_db::create_tables(&tx).unwrap();
+
+    // Insert all blocks, finalizing all but the last.
+    for (i, block) in blocks.iter().enumerate() {
+        node_db::insert_block(&tx, block).unwrap();
+        if i < blocks.len() - 1 {
+            let block_address = essential_hash::content_addr(block);
+            node_db::finalize_block(&tx, &block_address).unwrap();
+        }
+    }
+
+    let query = |prefix: &[Word], block_number: Word, cursor: Option<&Key>, page_size: i64| {
+        node_db::finalized::query_state_prefix_inclusive_block(
+            &tx,
+            &contract_addr,
+            prefix,
+            block_number,
+            cursor,
+            page_size,
+        )
+        .unwrap()
+    };
+    let entry = |key: &[Word], value: &[Word]| (key.to_vec(), value.to_vec());
+
+    // State as of the first block.
+    let expected = vec![
+        entry(&[0, 1], &[1]),
+        entry(&[0, 2], &[2]),
+        entry(&[0, 3], &[4]),
+    ];
+    assert_eq!(query(&[0], 0, None, 10), expected);
+
+    // An empty prefix lists all keys.
+    let mut all = expected.clone();
+    all.push(entry(&[1, 1], &[3]));
+    assert_eq!(query(&[], 0, None, 10), all);
+
+    // Later values replace earlier ones and deleted keys are omitted.
+    // The last block is not finalized, so is not included.
+    let expected = vec![entry(&[0, 1], &[1]), entry(&[0, 2], &[22])];
+    assert_eq!(query(&[0], 1, None, 10), expected);
+    assert_eq!(query(&[0], 2, None, 10), expected);
+
+    // Page through the keys using the last key as the cursor.
+    let page = query(&[0], 1, None, 1);
+    assert_eq!(page, expected[..1]);
+    let page = query(&[0], 1, Some(&page[0].0), 1);
+    assert_eq!(page, expected[1..]);
+    let page = query(&[0], 1, Some(&page[0].0), 1);
+    assert!(page.is_empty());
+
+    // No keys for an unknown prefix or before the first block is finalized.
+    assert!(query(&[2], 1, None, 10).is_empty());
+    assert!(query(&[0], -1, None, 10).is_empty());
+
+    // A prefix of all `0xFF` bytes has no upper bound.
+    let expected = vec![entry(&[-1, 1], &[6])];
+    assert_eq!(query(&[-1], 1, None, 10), expected);
+    assert_eq!(query(&[], 1, Some(&vec![1, 1]), 10), expected);
+}
+
 #[test]
 fn test_query_state_block_address() {
     // Test block that we'll insert.
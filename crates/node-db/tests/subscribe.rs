@@ -1,5 +1,6 @@
 use essential_node_db::{self as node_db};
 use essential_node_types::block_notify::BlockTx;
+use essential_types::{solution::Mutation, ContentAddress, Key, Word};
 use futures::StreamExt;
 use util::test_conn_pool;
 
@@ -58,3 +59,214 @@ async fn subscribe_blocks() {
 
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn subscribe_state() {
+    let contract_ca = ContentAddress([42; 32]);
+    let key: Key = vec![7];
+
+    // The test blocks, each mutating the key within their first solution set,
+    // with every third block also mutating the key within its last solution set.
+    let blocks: Vec<_> = util::test_blocks(20)
+        .into_iter()
+        .map(|mut block| {
+            let n = block.header.number;
+            let n_sets = block.solution_sets.len();
+            for (ix, set) in block.solution_sets.iter_mut().enumerate() {
+                let solution = &mut set.solutions[0];
+                solution.predicate_to_solve.contract = contract_ca.clone();
+                solution.state_mutations = vec![Mutation {
+                    key: if ix == 0 || (ix == n_sets - 1 && n % 3 == 0) {
+                        key.clone()
+                    } else {
+                        vec![8]
+                    },
+                    value: vec![n, ix as Word],
+                }];
+            }
+            block
+        })
+        .collect();
+
+    // The expected changes to the key as (block number, solution set index, value).
+    let expected: Vec<_> = blocks
+        .iter()
+        .flat_map(|block| {
+            block
+                .solution_sets
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, set)| {
+                    let mutation = &set.solutions[0].state_mutations[0];
+                    (mutation.key == key).then(|| {
+                        let n = block.header.number;
+                        (n, ix as u64, mutation.value.clone())
+                    })
+                })
+        })
+        .collect();
+
+    // A DB connection pool.
+    let conn_pool = test_conn_pool();
+
+    // Channel for notifying of new blocks
+    let new_block_tx = BlockTx::new();
+    let new_block_rx = new_block_tx.new_listener();
+
+    // Write and finalize the first 10 blocks. We'll write the rest later.
+    let mut conn = conn_pool.acquire().await.unwrap();
+    let tx = conn.transaction().unwrap();
+    node_db::create_tables(&tx).unwrap();
+    for block in &blocks[..10] {
+        let block_ca = node_db::insert_block(&tx, block).unwrap();
+        node_db::finalize_block(&tx, &block_ca).unwrap();
+    }
+    tx.commit().unwrap();
+
+    std::mem::drop(conn);
+
+    // Subscribe to the key's state from the 4th block.
+    let start_block = 3;
+    let stream = node_db::subscribe_state(
+        contract_ca,
+        key,
+        start_block,
+        conn_pool.clone(),
+        new_block_rx,
+    );
+    let mut stream = std::pin::pin!(stream);
+
+    // The changes within the 4th to 10th blocks should be available already.
+    let (available, remaining): (Vec<_>, Vec<_>) = expected
+        .into_iter()
+        .filter(|(n, _, _)| *n >= start_block)
+        .partition(|(n, _, _)| *n < 10);
+    let fetched: Vec<_> = stream
+        .by_ref()
+        .take(available.len())
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(available, fetched);
+
+    // Write the remaining blocks asynchronously, only finalizing each after a notification.
+    let blocks_remaining = blocks[10..].to_vec();
+    let jh = tokio::spawn(async move {
+        for block in blocks_remaining {
+            let mut conn = conn_pool.acquire().await.unwrap();
+            let block_ca =
+                node_db::with_tx(&mut conn, |tx| node_db::insert_block(tx, &block)).unwrap();
+            new_block_tx.notify();
+            node_db::finalize_block(&conn, &block_ca).unwrap();
+            new_block_tx.notify();
+        }
+        // After writing, drop the new block tx, closing the stream.
+        std::mem::drop(new_block_tx);
+    });
+
+    // The stream should yield the remaining changes and then complete after the
+    // `new_block_tx` drops.
+    let fetched: Vec<_> = stream.map(Result::unwrap).collect().await;
+    assert_eq!(remaining, fetched);
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_contract_solutions() {
+    // The test blocks, where every other solution set solves one of two
+    // contracts of interest.
+    let contracts = [ContentAddress([1; 32]), ContentAddress([2; 32])];
+    let mut ix = 0;
+    let blocks: Vec<_> = util::test_blocks(20)
+        .into_iter()
+        .map(|mut block| {
+            for set in &mut block.solution_sets {
+                if ix % 2 == 0 {
+                    let contract = contracts[(ix / 2) % contracts.len()].clone();
+                    set.solutions[0].predicate_to_solve.contract = contract;
+                }
+                ix += 1;
+            }
+            block
+        })
+        .collect();
+
+    // The expected solutions as (block number, block address, solution set index, solution).
+    let mut expected = vec![];
+    for block in &blocks {
+        let block_addr = essential_hash::content_addr(block);
+        for (ix, set) in block.solution_sets.iter().enumerate() {
+            for solution in &set.solutions {
+                if contracts.contains(&solution.predicate_to_solve.contract) {
+                    let solution = solution.clone();
+                    expected.push((block.header.number, block_addr.clone(), ix as u64, solution));
+                }
+            }
+        }
+    }
+    assert!(!expected.is_empty());
+
+    // A DB connection pool.
+    let conn_pool = test_conn_pool();
+
+    // Channel for notifying of new blocks
+    let new_block_tx = BlockTx::new();
+    let new_block_rx = new_block_tx.new_listener();
+
+    // Write the first 10 blocks to the DB. We'll write the rest later.
+    let mut conn = conn_pool.acquire().await.unwrap();
+    let tx = conn.transaction().unwrap();
+    node_db::create_tables(&tx).unwrap();
+    for block in &blocks[..10] {
+        let block_addr = node_db::insert_block(&tx, block).unwrap();
+        node_db::finalize_block(&tx, &block_addr).unwrap();
+    }
+    tx.commit().unwrap();
+
+    std::mem::drop(conn);
+
+    // Subscribe to the contracts' solutions.
+    let start_block = 0;
+    let stream = node_db::subscribe_contract_solutions(
+        contracts.to_vec(),
+        start_block,
+        conn_pool.clone(),
+        new_block_rx,
+    );
+    let mut stream = std::pin::pin!(stream);
+
+    // The solutions within the first 10 blocks should be available already.
+    let (available, remaining): (Vec<_>, Vec<_>) =
+        expected.into_iter().partition(|(n, _, _, _)| *n < 10);
+    let fetched: Vec<_> = stream
+        .by_ref()
+        .take(available.len())
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(available, fetched);
+
+    // Write the remaining blocks asynchronously.
+    let blocks_remaining = blocks[10..].to_vec();
+    let jh = tokio::spawn(async move {
+        for block in blocks_remaining {
+            let mut conn = conn_pool.acquire().await.unwrap();
+            node_db::with_tx(&mut conn, |tx| {
+                let block_addr = node_db::insert_block(tx, &block)?;
+                node_db::finalize_block(tx, &block_addr)
+            })
+            .unwrap();
+            new_block_tx.notify();
+        }
+        // After writing, drop the new block tx, closing the stream.
+        std::mem::drop(new_block_tx);
+    });
+
+    // The stream should yield the remaining solutions and then complete after
+    // the `new_block_tx` drops.
+    let fetched: Vec<_> = stream.map(Result::unwrap).collect().await;
+    assert_eq!(remaining, fetched);
+
+    jh.await.unwrap();
+}
@@ -5,6 +5,7 @@ use essential_types::{SolutionSet, Word};
 use serde::{Deserialize, Serialize};
 
 pub mod addr;
+pub mod binary;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,109 @@
+//! A compact binary encoding for blocks, as an alternative to JSON.
+//!
+//! Each block is encoded with [`postcard`] as its header followed by its
+//! solution sets. Within a stream, each encoded block is framed by a prefix
+//! containing its length in bytes as a big-endian `u32`.
+//!
+//! Within an unbounded stream of blocks, empty frames are keep-alives and carry
+//! no block.
+//!
+//! Frames longer than [`MAX_FRAME_LEN`] are rejected when decoding.
+
+use super::{Block, Header};
+use essential_types::solution::SolutionSet;
+use thiserror::Error;
+
+/// The media type used to negotiate the framed binary encoding of blocks.
+pub const MEDIA_TYPE: &str = "application/vnd.essential.block+postcard";
+
+/// The size in bytes of the length prefix of each frame.
+pub const LEN_PREFIX_SIZE: usize = core::mem::size_of::<u32>();
+
+/// The maximum length in bytes of a frame's contents accepted when decoding.
+pub const MAX_FRAME_LEN: usize = 32 * 1024 * 1024;
+
+/// An empty frame, used as a keep-alive within a stream of blocks.
+pub const KEEP_ALIVE_FRAME: [u8; LEN_PREFIX_SIZE] = [0; LEN_PREFIX_SIZE];
+
+#[doc(inline)]
+pub use postcard::Error;
+
+/// An error decoding frames.
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    /// The frame's length prefix exceeds [`MAX_FRAME_LEN`].
+    #[error("frame length {0} exceeds the maximum of {MAX_FRAME_LEN}")]
+    FrameTooLong(usize),
+    /// The frame's contents could not be decoded.
+    #[error("failed to decode frame: {0}")]
+    Postcard(#[from] Error),
+}
+
+/// Encode the block without a length prefix.
+pub fn to_bytes(block: &Block) -> Result<Vec<u8>, Error> {
+    postcard::to_allocvec(&(&block.header, &block.solution_sets))
+}
+
+/// Decode a block encoded with [`to_bytes`].
+pub fn from_bytes(bytes: &[u8]) -> Result<Block, Error> {
+    let (header, solution_sets): (Header, Vec<SolutionSet>) = postcard::from_bytes(bytes)?;
+    Ok(Block {
+        header,
+        solution_sets,
+    })
+}
+
+/// An item decoded from a stream of frames.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Frame {
+    /// A block within the stream.
+    Block(Block),
+    /// An empty keep-alive frame.
+    KeepAlive,
+}
+
+/// Encode the block along with its length prefix.
+pub fn encode_frame(block: &Block) -> Result<Vec<u8>, Error> {
+    frame(to_bytes(block)?)
+}
+
+/// Decode the frame at the start of the given buffer.
+///
+/// Returns the block or keep-alive along with the total length of its frame,
+/// or `None` if the buffer does not yet contain the entire frame.
+pub fn decode_frame(buf: &[u8]) -> Result<Option<(Frame, usize)>, DecodeError> {
+    let Some((bytes, frame_len)) = split_frame(buf)? else {
+        return Ok(None);
+    };
+    if bytes.is_empty() {
+        return Ok(Some((Frame::KeepAlive, frame_len)));
+    }
+    Ok(Some((Frame::Block(from_bytes(bytes)?), frame_len)))
+}
+
+/// Prefix the given bytes with their length.
+fn frame(bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let len = u32::try_from(bytes.len()).map_err(|_| Error::SerializeBufferFull)?;
+    let mut frame = Vec::with_capacity(LEN_PREFIX_SIZE + bytes.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend(bytes);
+    Ok(frame)
+}
+
+/// Split the bytes of the frame at the start of the buffer from its prefix.
+///
+/// Returns the bytes along with the total length of the frame, or `None` if
+/// the buffer does not yet contain the entire frame.
+fn split_frame(buf: &[u8]) -> Result<Option<(&[u8], usize)>, DecodeError> {
+    let Some(prefix) = buf.get(..LEN_PREFIX_SIZE) else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(prefix.try_into().expect("prefix is 4 bytes")) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(DecodeError::FrameTooLong(len));
+    }
+    let frame_len = LEN_PREFIX_SIZE + len;
+    Ok(buf
+        .get(LEN_PREFIX_SIZE..frame_len)
+        .map(|bytes| (bytes, frame_len)))
+}
@@ -40,3 +40,72 @@ fn test_block_addr() {
     let addr = addr::from_header_and_solution_set_addrs(&block.header, set_addrs);
     assert_ne!(content_addr, addr);
 }
+
+#[test]
+fn test_binary_frame_roundtrip() {
+    let solution_set = SolutionSet {
+        solutions: vec![Solution {
+            predicate_to_solve: PredicateAddress {
+                contract: ContentAddress([1; 32]),
+                predicate: ContentAddress([2; 32]),
+            },
+            predicate_data: vec![vec![1, -2, i64::MAX]],
+            state_mutations: vec![essential_types::solution::Mutation {
+                key: vec![3],
+                value: vec![4, 5],
+            }],
+        }],
+    };
+    let blocks: Vec<_> = (0..3)
+        .map(|number| Block {
+            header: Header {
+                number,
+                timestamp: Duration::from_millis(number as u64 * 1500),
+            },
+            solution_sets: vec![solution_set.clone(); number as usize],
+        })
+        .collect();
+
+    // Concatenate the frames as in a stream, with keep-alives in between.
+    let buf: Vec<u8> = blocks
+        .iter()
+        .flat_map(|block| {
+            let mut frame = binary::encode_frame(block).unwrap();
+            frame.extend(binary::KEEP_ALIVE_FRAME);
+            frame
+        })
+        .collect();
+
+    let mut decoded = vec![];
+    let mut rest = &buf[..];
+    while let Some((frame, len)) = binary::decode_frame(rest).unwrap() {
+        decoded.push(frame);
+        rest = &rest[len..];
+    }
+    assert!(rest.is_empty());
+    let expected: Vec<_> = blocks
+        .iter()
+        .flat_map(|block| {
+            [
+                binary::Frame::Block(block.clone()),
+                binary::Frame::KeepAlive,
+            ]
+        })
+        .collect();
+    assert_eq!(decoded, expected);
+
+    // Partial frames need more data.
+    let frame = binary::encode_frame(&blocks[2]).unwrap();
+    assert!(binary::decode_frame(&frame[..frame.len() - 1])
+        .unwrap()
+        .is_none());
+    assert!(binary::decode_frame(&frame[..2]).unwrap().is_none());
+
+    // Oversized frames are rejected before their contents arrive.
+    let len = u32::try_from(binary::MAX_FRAME_LEN + 1).unwrap();
+    let prefix = len.to_be_bytes();
+    assert!(matches!(
+        binary::decode_frame(&prefix),
+        Err(binary::DecodeError::FrameTooLong(_))
+    ));
+}
@@ -21,6 +21,9 @@ pub mod block;
 #[cfg(feature = "block-notify")]
 pub mod block_notify;
 
+/// The version of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// The default big-bang configuration.
 pub const DEFAULT_BIG_BANG: &str = include_str!("../big-bang.yml");
 
@@ -120,6 +123,17 @@ pub mod contract_registry {
     const CONTRACTS_PREFIX: Word = 0;
     const PREDICATES_PREFIX: Word = 1;
 
+    /// A key prefix shared by all of a contract's entries, i.e. its `salt` and the keys
+    /// associating each of its predicates with the contract.
+    ///
+    /// The returned key is formatted as: `[0, <contract-ca>]`
+    pub fn contract_key_prefix(contract_ca: &ContentAddress) -> Key {
+        Some(CONTRACTS_PREFIX)
+            .into_iter()
+            .chain(padded_words_from_bytes(&contract_ca.0))
+            .collect()
+    }
+
     /// A key that may be used to refer to a contract's `salt` in state.
     ///
     /// The returned key is formatted as: `[0, <contract-ca>, 0]`
@@ -6,6 +6,7 @@ use essential_types::{predicate::PredicateDecodeError, ContentAddress, Predicate
 use thiserror::Error;
 use tokio::sync::AcquireError;
 
+#[allow(dead_code)]
 #[derive(Debug, Error)]
 #[error("Connection pool creation failed: {0}")]
 pub struct ConnPoolNewError(#[from] pub rusqlite::Error);
@@ -47,16 +48,25 @@ pub enum RecoverableError {
     PredicateNotFound(PredicateAddress),
 }
 
+/// An error that prevented a block from being validated.
+///
+/// Note that this does not indicate that the block is invalid. See
+/// [`ValidateOutcome`][crate::validate::ValidateOutcome] for validation results.
 #[derive(Debug, Error)]
 pub enum ValidationError {
+    /// Failed to query the predicates required by a solution set.
     #[error(transparent)]
     SolutionSetPredicates(#[from] SolutionSetPredicatesError),
+    /// A DB query failed.
     #[error(transparent)]
     Query(#[from] QueryError),
+    /// The DB connection pool has been closed.
     #[error("database connection pool closed")]
     DbPoolClosed(#[from] tokio::sync::AcquireError),
+    /// A rusqlite error occurred.
     #[error("recoverable database error {0}")]
     Rusqlite(#[from] rusqlite::Error),
+    /// A blocking DB task failed to join.
     #[error("failed to join handle")]
     Join(#[from] tokio::task::JoinError),
 }
@@ -75,6 +85,8 @@ pub enum CriticalError {
     DbPoolClosed(#[from] tokio::sync::AcquireError),
     #[error(transparent)]
     Relayer(#[from] essential_relayer::Error),
+    #[error("failed to read the relayer CA bundle: {0}")]
+    ReadCaBundle(std::io::Error),
     #[error("last progress cannot be none")]
     LastProgressNone,
 }
@@ -113,25 +125,34 @@ pub enum PredicatesProgramsError {
     MissingProgram(ContentAddress),
 }
 
+/// Failed to query or decode a predicate from the contract registry.
 #[derive(Debug, Error)]
 pub enum QueryPredicateError {
+    /// A DB query failed.
     #[error(transparent)]
     Query(#[from] QueryError),
+    /// The predicate entry is empty.
     #[error("the queried predicate is missing the word that encodes its length")]
     MissingLenBytes,
+    /// The length word is not a valid length.
     #[error("the queried predicate length was invalid")]
     InvalidLenBytes,
+    /// The predicate bytes could not be decoded.
     #[error("failed to decode the queried predicate: {0:?}")]
     Decode(#[from] PredicateDecodeError),
 }
 
+/// Failed to query or decode a program from the program registry.
 #[derive(Debug, Error)]
 pub enum QueryProgramError {
+    /// A DB query failed.
     #[error(transparent)]
     Query(#[from] QueryError),
+    /// The program entry is empty.
     #[error("the queried program is missing the word that encodes its length")]
     MissingLenBytes,
-    #[error("the queried predicate length was invalid")]
+    /// The length word is not a valid length.
+    #[error("the queried program length was invalid")]
     InvalidLenBytes,
 }
 
@@ -1,9 +1,23 @@
-use crate::error::{CriticalError, NodeHandleJoinError};
+use crate::{
+    error::{CriticalError, NodeHandleJoinError},
+    validation_notify::{ValidationRx, ValidationTx},
+};
 
 /// Handle for closing or joining the relayer and validation streams.
 pub struct Handle {
     relayer: Option<essential_relayer::Handle>,
     validation: Option<crate::handles::validation::Handle<CriticalError>>,
+    validation_notify: ValidationTx,
+}
+
+/// Reports whether the node's relayer and validation streams are still running.
+///
+/// Unlike the [`Handle`], this may be cloned and held by any number of
+/// observers without affecting the streams.
+#[derive(Clone, Debug)]
+pub struct Liveness {
+    relayer: Option<essential_relayer::Liveness>,
+    validation: Option<tokio::task::AbortHandle>,
 }
 
 impl Handle {
@@ -11,13 +25,42 @@ impl Handle {
     pub(crate) fn new(
         relayer: Option<essential_relayer::Handle>,
         validation: Option<crate::handles::validation::Handle<CriticalError>>,
+        validation_notify: ValidationTx,
     ) -> Self {
         Self {
             relayer,
             validation,
+            validation_notify,
+        }
+    }
+
+    /// Create a new listener for the outcome of each block validated by the
+    /// validation stream.
+    ///
+    /// The listener only receives outcomes for blocks validated after it was created.
+    pub fn new_validation_listener(&self) -> ValidationRx {
+        self.validation_notify.new_listener()
+    }
+
+    /// A [`Liveness`] for observing whether the streams are still running.
+    pub fn liveness(&self) -> Liveness {
+        Liveness {
+            relayer: self
+                .relayer
+                .as_ref()
+                .map(essential_relayer::Handle::liveness),
+            validation: self.validation.as_ref().map(|v| v.abort_handle()),
         }
     }
 
+    /// The counts of the relayer stream's restarts, or `None` if the relayer
+    /// is not running.
+    pub fn relayer_restarts(&self) -> Option<crate::RelayerRestarts> {
+        self.relayer
+            .as_ref()
+            .map(essential_relayer::Handle::restarts)
+    }
+
     /// Close the relayer and validation streams.
     ///
     /// If this future is dropped then all three streams will be closed.
@@ -25,6 +68,7 @@ impl Handle {
         let Self {
             relayer,
             validation,
+            ..
         } = self;
         if let Some(relayer) = relayer {
             relayer.close().await?;
@@ -45,6 +89,7 @@ impl Handle {
         let Self {
             relayer,
             validation,
+            ..
         } = self;
 
         let relayer_future = async move {
@@ -73,3 +118,32 @@ impl Handle {
         Ok(())
     }
 }
+
+impl Liveness {
+    /// Whether the relayer stream is still running, or `None` if the relayer
+    /// was not run.
+    pub fn relayer(&self) -> Option<bool> {
+        self.relayer
+            .as_ref()
+            .map(essential_relayer::Liveness::is_alive)
+    }
+
+    /// The time elapsed since the relayer stream last synced a block or
+    /// received a keep-alive, or `None` if the relayer was not run.
+    ///
+    /// Unlike [`Liveness::relayer`], this reveals a relayer that keeps
+    /// restarting without making progress.
+    pub fn relayer_since_progress(&self) -> Option<std::time::Duration> {
+        self.relayer
+            .as_ref()
+            .map(essential_relayer::Liveness::since_progress)
+    }
+
+    /// Whether the validation stream is still running, or `None` if validation
+    /// was not run.
+    ///
+    /// The stream finishes upon being closed or encountering a critical error.
+    pub fn validation(&self) -> Option<bool> {
+        self.validation.as_ref().map(|v| !v.is_finished())
+    }
+}
@@ -18,6 +18,11 @@ impl<E> Handle<E> {
         }
     }
 
+    /// An abort handle for the stream's task, used to observe whether it has finished.
+    pub fn abort_handle(&self) -> tokio::task::AbortHandle {
+        self.join.abort_handle()
+    }
+
     pub async fn close(self) -> Result<(), E> {
         let _ = self.close.close.send(());
         flatten_result(self.join.await)
@@ -9,14 +9,18 @@
 //! - Performs validation.
 
 use error::{BigBangError, CriticalError};
+pub use error::{QueryPredicateError, QueryProgramError, ValidationError};
 pub use essential_node_db as db;
 use essential_node_types::{block_notify::BlockTx, BigBang};
 use essential_relayer::Relayer;
+pub use essential_relayer::Restarts as RelayerRestarts;
 use essential_types::ContentAddress;
-pub use handles::node::Handle;
+pub use handles::node::{Handle, Liveness};
+use std::path::PathBuf;
 pub use validate::validate_dry_run;
 pub use validate::validate_solution_set_dry_run;
 use validation::validation_stream;
+use validation_notify::ValidationTx;
 
 mod error;
 mod handles;
@@ -25,6 +29,10 @@ mod handles;
 pub mod test_utils;
 pub mod validate;
 mod validation;
+pub mod validation_notify;
+
+/// The version of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Options for running the node.
 #[derive(Clone, Debug)]
@@ -32,6 +40,9 @@ pub struct RunConfig {
     /// Node endpoint to sync blocks from.
     /// If `None` then the relayer stream will not run.
     pub relayer_source_endpoint: Option<String>,
+    /// Path to a PEM bundle of CA certificates trusted by the relayer, in
+    /// addition to the system's, when syncing from a TLS endpoint.
+    pub relayer_ca_bundle: Option<PathBuf>,
     /// If `false` then the validation stream will not run.
     pub run_validation: bool,
 }
@@ -117,27 +128,40 @@ pub fn run(
     let RunConfig {
         run_validation,
         relayer_source_endpoint,
+        relayer_ca_bundle,
     } = conf;
 
     // Run relayer.
     let relayer_handle = if let Some(relayer_source_endpoint) = relayer_source_endpoint {
-        let relayer = Relayer::new(relayer_source_endpoint.as_str())?;
+        let relayer = match relayer_ca_bundle {
+            None => Relayer::new(relayer_source_endpoint.as_str())?,
+            Some(path) => {
+                let ca_bundle = std::fs::read(path).map_err(CriticalError::ReadCaBundle)?;
+                Relayer::with_ca_bundle(relayer_source_endpoint.as_str(), &ca_bundle)?
+            }
+        };
         Some(relayer.run(conn_pool.clone(), block_notify.clone())?)
     } else {
         None
     };
 
     // Run validation stream.
+    let validation_notify = ValidationTx::new();
     let validation_handle = if run_validation {
         Some(validation_stream(
             conn_pool.clone(),
             contract_registry,
             program_registry,
             block_notify.new_listener(),
+            validation_notify.clone(),
         )?)
     } else {
         None
     };
 
-    Ok(Handle::new(relayer_handle, validation_handle))
+    Ok(Handle::new(
+        relayer_handle,
+        validation_handle,
+        validation_notify,
+    ))
 }
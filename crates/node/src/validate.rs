@@ -24,6 +24,7 @@ use essential_types::{
     ContentAddress, Key, PredicateAddress, Value, Word,
 };
 use futures::FutureExt;
+use serde::Serialize;
 use std::{collections::HashMap, pin::Pin, sync::Arc};
 
 #[cfg(test)]
@@ -82,7 +83,8 @@ struct CascadeTransaction<'a> {
 }
 
 /// Result of validating a block.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ValidateOutcome {
     /// The block is valid.
     Valid(ValidOutcome),
@@ -92,7 +94,7 @@ pub enum ValidateOutcome {
 
 /// Outcome of a valid block.
 /// Cumulative gas and utilities of all solutions in the block.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ValidOutcome {
     /// Total gas consumed by all solutions in the block.
     pub total_gas: Gas,
@@ -100,7 +102,7 @@ pub struct ValidOutcome {
 
 /// Outcome of an invalid block.
 /// Contains the failure reason and the index of the solution that caused the failure.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct InvalidOutcome {
     /// The reason for the block to be invalid.
     pub failure: ValidateFailure,
@@ -110,6 +112,10 @@ pub struct InvalidOutcome {
 
 /// Reasons for a block to be invalid.
 /// Contains the error that caused the block to be invalid.
+///
+/// Serializes to an object with a stable `code` identifying the kind of
+/// failure (see [`ValidateFailure::code`]), a human readable `message`, and
+/// the offending `predicate` or `program` address where applicable.
 #[derive(Debug)]
 pub enum ValidateFailure {
     /// A solution specified a predicate that does not exist within the contract registry.
@@ -127,6 +133,64 @@ pub enum ValidateFailure {
     GasOverflow,
 }
 
+impl ValidateFailure {
+    /// A stable code identifying the kind of failure.
+    ///
+    /// Unlike the message, codes are guaranteed not to change between versions.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingPredicate(_) => "missing_predicate",
+            Self::InvalidPredicate(_) => "invalid_predicate",
+            Self::MissingProgram(_) => "missing_program",
+            Self::InvalidProgram(_) => "invalid_program",
+            Self::PredicatesError(_) => "predicates_error",
+            Self::GasOverflow => "gas_overflow",
+        }
+    }
+}
+
+impl std::fmt::Display for ValidateFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingPredicate(addr) => write!(
+                f,
+                "predicate not found in contract registry: (contract: {}, predicate: {})",
+                addr.contract, addr.predicate
+            ),
+            Self::InvalidPredicate(addr) => write!(
+                f,
+                "failed to decode predicate: (contract: {}, predicate: {})",
+                addr.contract, addr.predicate
+            ),
+            Self::MissingProgram(addr) => {
+                write!(f, "program not found in program registry: {addr}")
+            }
+            Self::InvalidProgram(addr) => write!(f, "invalid program format: {addr}"),
+            Self::PredicatesError(err) => write!(f, "{err}"),
+            Self::GasOverflow => write!(f, "total gas exceeds the maximum gas limit"),
+        }
+    }
+}
+
+impl Serialize for ValidateFailure {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("ValidateFailure", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        match self {
+            Self::MissingPredicate(addr) | Self::InvalidPredicate(addr) => {
+                s.serialize_field("predicate", addr)?
+            }
+            Self::MissingProgram(addr) | Self::InvalidProgram(addr) => {
+                s.serialize_field("program", addr)?
+            }
+            Self::PredicatesError(_) | Self::GasOverflow => (),
+        }
+        s.end()
+    }
+}
+
 /// Validates a solution without adding it to the database.
 /// Creates a block at the next block number and current timestamp with the given solution set
 /// and validates it.
@@ -139,13 +203,16 @@ pub async fn validate_solution_set_dry_run(
     program_registry: &ContentAddress,
     solution_set: SolutionSet,
 ) -> Result<ValidateOutcome, ValidationError> {
-    let mut conn = conn_pool.acquire().await?;
-    let tx = conn.transaction()?;
-    let number = match db::get_latest_finalized_block_address(&tx)? {
-        Some(address) => db::get_block_header(&tx, &address)?
-            .map(|header| header.number)
-            .unwrap_or(1),
-        None => 1,
+    // Scope the connection so that it is returned to the pool before validating.
+    let number = {
+        let mut conn = conn_pool.acquire().await?;
+        let tx = conn.transaction()?;
+        match db::get_latest_finalized_block_address(&tx)? {
+            Some(address) => db::get_block_header(&tx, &address)?
+                .map(|header| header.number)
+                .unwrap_or(1),
+            None => 1,
+        }
     };
     let block = Block {
         header: BlockHeader {
@@ -156,7 +223,6 @@ pub async fn validate_solution_set_dry_run(
         },
         solution_sets: vec![solution_set],
     };
-    drop(tx);
     validate_dry_run(conn_pool, contract_registry, program_registry, &block).await
 }
 
@@ -556,6 +622,16 @@ fn query_predicate(
         return Ok(None);
     };
 
+    let predicate = decode_predicate(&pred_words)?;
+    Ok(Some(predicate))
+}
+
+/// Decode a predicate from its entry within the contract registry state.
+///
+/// The entry is expected to contain the length of the encoded predicate in
+/// bytes, followed by the word-padded encoded predicate. See
+/// [`BigBang::contract_registry`][essential_node_types::BigBang::contract_registry].
+pub fn decode_predicate(pred_words: &[Word]) -> Result<Predicate, QueryPredicateError> {
     // Read the length from the front.
     let Some(&pred_len_bytes) = pred_words.first() else {
         return Err(QueryPredicateError::MissingLenBytes);
@@ -571,8 +647,7 @@ fn query_predicate(
         .take(pred_len_bytes)
         .collect();
 
-    let predicate = Predicate::decode(&pred_bytes)?;
-    Ok(Some(predicate))
+    Ok(Predicate::decode(&pred_bytes)?)
 }
 
 /// Retrieve all programs required by the predicates.
@@ -639,6 +714,16 @@ fn query_program(
         return Ok(None);
     };
 
+    let program = decode_program(&prog_words)?;
+    Ok(Some(program))
+}
+
+/// Decode a program from its entry within the program registry state.
+///
+/// The entry is expected to contain the length of the program in bytes,
+/// followed by the word-padded program bytecode. See
+/// [`BigBang::program_registry`][essential_node_types::BigBang::program_registry].
+pub fn decode_program(prog_words: &[Word]) -> Result<Program, QueryProgramError> {
     // Read the length from the front.
     let Some(&prog_len_bytes) = prog_words.first() else {
         return Err(QueryProgramError::MissingLenBytes);
@@ -654,8 +739,7 @@ fn query_program(
         .take(prog_len_bytes)
         .collect();
 
-    let program = Program(prog_bytes);
-    Ok(Some(program))
+    Ok(Program(prog_bytes))
 }
 
 fn query_state(
@@ -124,6 +124,31 @@ async fn predicate_not_found() {
     }
 }
 
+#[tokio::test]
+async fn serialize_outcome() {
+    let conn_pool = test_conn_pool();
+    let (block, _, _) = test_invalid_block(0, Duration::from_secs(0));
+    let big_bang = test_big_bang();
+    let contract_registry = big_bang.contract_registry.contract;
+    let program_registry = big_bang.program_registry.contract;
+    let outcome =
+        validate::validate_dry_run(&conn_pool, &contract_registry, &program_registry, &block)
+            .await
+            .unwrap();
+    let json = serde_json::to_value(&outcome).unwrap();
+    let invalid = &json["invalid"];
+    assert_eq!(invalid["solution_set_index"], 0);
+    assert_eq!(invalid["failure"]["code"], "missing_predicate");
+    assert!(invalid["failure"]["message"].is_string());
+    let expected =
+        serde_json::to_value(&block.solution_sets[0].solutions[0].predicate_to_solve).unwrap();
+    assert_eq!(invalid["failure"]["predicate"], expected);
+
+    let json =
+        serde_json::to_value(ValidateOutcome::Valid(ValidOutcome { total_gas: 42 })).unwrap();
+    assert_eq!(json, serde_json::json!({ "valid": { "total_gas": 42 } }));
+}
+
 #[tokio::test]
 async fn program_not_found() {
     let conn_pool = test_conn_pool_with_big_bang().await;
@@ -6,6 +6,7 @@ use crate::{
     error::{CriticalError, InternalError, RecoverableError, ValidationError},
     handles::validation::Handle,
     validate::{self, InvalidOutcome, ValidOutcome, ValidateOutcome},
+    validation_notify::{ValidatedBlock, ValidationTx},
 };
 use essential_hash::content_addr;
 use essential_node_db::QueryError;
@@ -21,6 +22,8 @@ mod tests;
 /// The stream is spawned and run in the background.
 /// The watch channel listens to notifications when a new block is added to the database.
 ///
+/// The outcome of each validated block is broadcast via `validation_tx`.
+///
 /// Returns a handle that can be used to clone or join the stream.
 ///
 /// Recoverable errors will be logged and the stream will be restarted.
@@ -31,6 +34,7 @@ pub fn validation_stream(
     contract_registry: ContentAddress,
     program_registry: ContentAddress,
     mut block_rx: BlockRx,
+    validation_tx: ValidationTx,
 ) -> Result<Handle<CriticalError>, CriticalError> {
     let (shutdown, stream_close) = watch::channel(());
 
@@ -43,6 +47,7 @@ pub fn validation_stream(
                         conn_pool.clone(),
                         &contract_registry,
                         &program_registry,
+                        &validation_tx,
                     )
                     .await
                     {
@@ -88,6 +93,7 @@ async fn validate_next_block(
     conn_pool: ConnectionPool,
     contract_registry: &ContentAddress,
     program_registry: &ContentAddress,
+    validation_tx: &ValidationTx,
 ) -> Result<bool, InternalError> {
     let progress = get_last_progress(&conn_pool)
         .await?
@@ -106,13 +112,12 @@ async fn validate_next_block(
         block.header.number
     );
 
+    let block_number = block.header.number;
     let res = validate::validate(&conn_pool, contract_registry, program_registry, &block).await?;
 
-    let more_blocks_available = match res {
+    let more_blocks_available = match &res {
         // Validation was successful.
-        ValidateOutcome::Valid(ValidOutcome {
-            total_gas: _total_gas,
-        }) => {
+        ValidateOutcome::Valid(ValidOutcome { .. }) => {
             let block_address = block_address.clone();
             let r: Result<bool, InternalError> = conn_pool
                 .acquire_then(move |conn| {
@@ -142,19 +147,19 @@ async fn validate_next_block(
         }
         // Validation failed.
         ValidateOutcome::Invalid(InvalidOutcome {
-            failure: _failure,
-            solution_set_index,
+            solution_set_index, ..
         }) => {
             // Insert the failed solution set into the database.
             let failed_solution_set = content_addr(
                 block
                     .solution_sets
-                    .get(solution_set_index)
+                    .get(*solution_set_index)
                     .expect("Failed solution set must exist."),
             );
+            let block_addr = block_address.clone();
             let r: Result<bool, InternalError> = conn_pool
                 .acquire_then(move |conn| {
-                    db::insert_failed_block(conn, &block_address, &failed_solution_set)
+                    db::insert_failed_block(conn, &block_addr, &failed_solution_set)
                         .map_err(ValidationError::from)
                         .map(|_| Ok(false))
                 })
@@ -164,6 +169,13 @@ async fn validate_next_block(
         }
     }?;
 
+    // Notify listeners of the outcome now that it has been recorded.
+    validation_tx.notify(ValidatedBlock {
+        block_address,
+        block_number,
+        outcome: res,
+    });
+
     Ok(more_blocks_available)
 }
 
@@ -37,6 +37,8 @@ async fn can_validate() {
 
     let block_tx = BlockTx::new();
     let block_rx = block_tx.new_listener();
+    let validation_tx = ValidationTx::new();
+    let mut validation_rx = validation_tx.new_listener();
 
     let big_bang = test_big_bang();
     let contract_registry = big_bang.contract_registry.contract;
@@ -46,6 +48,7 @@ async fn can_validate() {
         contract_registry,
         program_registry,
         block_rx,
+        validation_tx,
     )
     .unwrap();
 
@@ -74,6 +77,14 @@ async fn can_validate() {
     // Assert validation progress is block 3
     assert_validation_progress_is_some(&conn, &block_addrs[3]);
 
+    // Each block's outcome is broadcast in order.
+    for (block, block_addr) in blocks.iter().zip(&block_addrs) {
+        let validated = validation_rx.recv().await.unwrap();
+        assert_eq!(&validated.block_address, block_addr);
+        assert_eq!(validated.block_number, block.header.number);
+        assert!(matches!(validated.outcome, ValidateOutcome::Valid(_)));
+    }
+
     handle.close().await.unwrap();
 }
 
@@ -86,6 +97,8 @@ async fn test_invalid_block_validation() {
 
     let block_tx = BlockTx::new();
     let block_rx = block_tx.new_listener();
+    let validation_tx = ValidationTx::new();
+    let mut validation_rx = validation_tx.new_listener();
 
     let big_bang = test_big_bang();
     let contract_registry = big_bang.contract_registry.contract;
@@ -95,6 +108,7 @@ async fn test_invalid_block_validation() {
         contract_registry,
         program_registry,
         block_rx,
+        validation_tx,
     )
     .unwrap();
 
@@ -116,6 +130,14 @@ async fn test_invalid_block_validation() {
         content_addr(&block.solution_sets[0])
     );
 
+    // The failure is broadcast along with the offending solution set.
+    let validated = validation_rx.recv().await.unwrap();
+    assert_eq!(validated.block_address, content_addr(&block));
+    match &validated.outcome {
+        ValidateOutcome::Invalid(invalid) => assert_eq!(invalid.solution_set_index, 0),
+        ValidateOutcome::Valid(_) => panic!("expected invalid outcome"),
+    }
+
     handle.close().await.unwrap();
 }
 
@@ -149,6 +171,7 @@ async fn can_process_valid_and_invalid_blocks() {
         contract_registry,
         program_registry,
         block_rx,
+        ValidationTx::new(),
     )
     .unwrap();
 
@@ -0,0 +1,78 @@
+//! Broadcast of validation outcomes from the validation stream.
+
+use crate::validate::ValidateOutcome;
+use essential_types::{ContentAddress, Word};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError, Receiver, Sender};
+
+/// The number of outcomes retained for listeners that fall behind.
+const CAPACITY: usize = 256;
+
+/// The outcome of validating a block within the validation stream.
+#[derive(Debug, Serialize)]
+pub struct ValidatedBlock {
+    /// The address of the validated block.
+    pub block_address: ContentAddress,
+    /// The number of the validated block.
+    pub block_number: Word,
+    /// The outcome of validation.
+    pub outcome: ValidateOutcome,
+}
+
+/// Wrapper around `broadcast::Sender` to notify of validated blocks.
+#[derive(Clone)]
+pub struct ValidationTx(Sender<Arc<ValidatedBlock>>);
+
+/// Wrapper around `broadcast::Receiver` to listen to validated blocks.
+///
+/// Cloning a `ValidationRx` produces a new listener that only receives
+/// outcomes sent after the clone.
+pub struct ValidationRx(Receiver<Arc<ValidatedBlock>>);
+
+impl ValidationTx {
+    /// Create a new [`ValidationTx`] to notify listeners of validated blocks.
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(CAPACITY);
+        Self(tx)
+    }
+
+    /// Notify listeners that a block has been validated.
+    ///
+    /// Note this is best effort and will still send even if there are currently no listeners.
+    pub fn notify(&self, validated: ValidatedBlock) {
+        let _ = self.0.send(Arc::new(validated));
+    }
+
+    /// Create a new [`ValidationRx`] to listen for validated blocks.
+    pub fn new_listener(&self) -> ValidationRx {
+        ValidationRx(self.0.subscribe())
+    }
+
+    /// Get the number of receivers listening for validated blocks.
+    pub fn receiver_count(&self) -> usize {
+        self.0.receiver_count()
+    }
+}
+
+impl Default for ValidationTx {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValidationRx {
+    /// Receive the next validated block.
+    ///
+    /// Returns [`RecvError::Lagged`] if the listener fell behind and missed
+    /// outcomes, or [`RecvError::Closed`] once the sender has been dropped.
+    pub async fn recv(&mut self) -> Result<Arc<ValidatedBlock>, RecvError> {
+        self.0.recv().await
+    }
+}
+
+impl Clone for ValidationRx {
+    fn clone(&self) -> Self {
+        Self(self.0.resubscribe())
+    }
+}
@@ -39,10 +39,11 @@ async fn test_run() {
     let block_tx = BlockTx::new();
     let run_conf = RunConfig {
         relayer_source_endpoint: Some(node_server.address),
+        relayer_ca_bundle: None,
         run_validation: true,
     };
     let big_bang = BigBang::default();
-    let _handle = node::run(
+    let handle = node::run(
         db.clone(),
         run_conf,
         big_bang.contract_registry.contract.clone(),
@@ -51,6 +52,11 @@ async fn test_run() {
     )
     .unwrap();
 
+    // Both streams are running.
+    let liveness = handle.liveness();
+    assert_eq!(liveness.relayer(), Some(true));
+    assert_eq!(liveness.validation(), Some(true));
+
     // Create test blocks
     let test_blocks_count = 4;
     let test_blocks = test_blocks_with_contracts(1, test_blocks_count + 1);
@@ -107,6 +113,15 @@ async fn test_run() {
     // Check block, state and validation progress
     let mut conn = db.acquire().await.unwrap();
     assert_submit_block_effects(&mut conn, vec![test_blocks[3].clone()]);
+
+    // The relayer made progress syncing the blocks.
+    let since_progress = liveness.relayer_since_progress().unwrap();
+    assert!(since_progress < tokio::time::Duration::from_secs(1));
+
+    // Both streams finish once closed.
+    handle.close().await.unwrap();
+    assert_eq!(liveness.relayer(), Some(false));
+    assert_eq!(liveness.validation(), Some(false));
 }
 
 pub fn client() -> reqwest::Client {
@@ -152,9 +167,27 @@ async fn test_node() -> (NodeServer, BlockTx) {
     node::ensure_big_bang_block(&db, &big_bang).await.unwrap();
     let source_block_tx = BlockTx::new();
     let source_block_rx = source_block_tx.new_listener();
+    let big_bang = BigBang::default();
+    let run_conf = RunConfig {
+        relayer_source_endpoint: None,
+        relayer_ca_bundle: None,
+        run_validation: false,
+    };
+    let node_config = essential_node_api::NodeConfig::new(&big_bang, &run_conf);
     let state = essential_node_api::State {
         conn_pool: db,
+        contract_registry: big_bang.contract_registry.contract,
+        program_registry: big_bang.program_registry.contract,
         new_block: Some(source_block_rx),
+        validated_block: None,
+        list_blocks_limits: Default::default(),
+        metrics: Default::default(),
+        node_liveness: None,
+        max_validation_lag: essential_node_api::DEFAULT_MAX_VALIDATION_LAG,
+        max_relayer_staleness: essential_node_api::DEFAULT_MAX_RELAYER_STALENESS,
+        node_config,
+        auth: None,
+        limiter: Default::default(),
     };
     let node_server = setup_node_as_server(state).await;
 
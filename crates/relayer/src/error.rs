@@ -37,6 +37,12 @@ pub enum Error {
     /// An error occurred while building the http client.
     #[error("an error occurred while building the http client: {0}")]
     HttpClientBuild(reqwest::Error),
+    /// Failed to parse the PEM bundle of CA certificates.
+    #[error("an error occurred while parsing the CA bundle: {0}")]
+    CaBundle(reqwest::Error),
+    /// The PEM bundle of CA certificates contained no certificates.
+    #[error("the CA bundle contains no certificates")]
+    EmptyCaBundle,
     /// Failed to acquire then use a DB connection from the pool.
     #[error("failed to acquire or use a DB connection: {0}")]
     DbPoolRusqlite(#[from] essential_node_db::pool::AcquireThenRusqliteError),
@@ -66,6 +72,20 @@ pub(crate) enum RecoverableError {
     Rusqlite(rusqlite::Error),
 }
 
+impl RecoverableError {
+    /// A short, stable name for the kind of error, used to label restarts.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::Stream(_) => "stream",
+            Self::BadServerResponse(_) => "bad_server_response",
+            Self::HttpClient(_) => "http_client",
+            Self::NonSequentialBlock(..) => "non_sequential_block",
+            Self::StreamError(_) => "stream_error",
+            Self::Rusqlite(_) => "rusqlite",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 /// An error occurred while syncing data.
 pub enum DataSyncError {
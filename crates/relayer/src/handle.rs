@@ -1,4 +1,4 @@
-use crate::Result;
+use crate::{Heartbeat, Restarts, Result};
 use tokio::sync::watch;
 
 #[cfg(test)]
@@ -8,6 +8,19 @@ mod tests;
 pub struct Handle {
     join_blocks: tokio::task::JoinHandle<Result<()>>,
     close: Close,
+    restarts: Restarts,
+    heartbeat: Heartbeat,
+}
+
+/// Reports whether the relayer stream is still running, and when it last made
+/// progress.
+///
+/// Unlike the [`Handle`], this may be cloned and held by any number of
+/// observers without affecting the stream.
+#[derive(Clone, Debug)]
+pub struct Liveness {
+    task: tokio::task::AbortHandle,
+    heartbeat: Heartbeat,
 }
 
 /// Struct which when dropped will close the relayer.
@@ -20,19 +33,40 @@ impl Handle {
     pub fn new(
         join_blocks: tokio::task::JoinHandle<Result<()>>,
         close_blocks: watch::Sender<()>,
+        restarts: Restarts,
+        heartbeat: Heartbeat,
     ) -> Self {
         Self {
             join_blocks,
             close: Close { close_blocks },
+            restarts,
+            heartbeat,
+        }
+    }
+
+    /// A [`Liveness`] for observing whether the relayer stream is still running.
+    pub fn liveness(&self) -> Liveness {
+        Liveness {
+            task: self.join_blocks.abort_handle(),
+            heartbeat: self.heartbeat.clone(),
         }
     }
 
+    /// The counts of the relayer's stream restarts.
+    ///
+    /// The returned counts continue to update while the relayer runs.
+    pub fn restarts(&self) -> Restarts {
+        self.restarts.clone()
+    }
+
     /// Close the Relayer streams and join them.
     ///
     /// If this future isn't polled the streams will continue to run.
     /// However, if the future is dropped the streams will be closed.
     pub async fn close(self) -> Result<()> {
-        let Self { join_blocks, close } = self;
+        let Self {
+            join_blocks, close, ..
+        } = self;
         // Close the streams.
         close.close();
 
@@ -53,13 +87,31 @@ impl Handle {
     ///
     /// If this future is dropped then both streams will close.
     pub async fn join(self) -> Result<()> {
-        let Self { join_blocks, close } = self;
+        let Self {
+            join_blocks, close, ..
+        } = self;
         let r = join_blocks.await;
         close.close();
         flatten_result(r)
     }
 }
 
+impl Liveness {
+    /// Returns `true` if the relayer stream has not yet finished.
+    ///
+    /// The stream finishes upon being closed or encountering a critical error.
+    pub fn is_alive(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// The time elapsed since the relayer stream last made progress.
+    ///
+    /// See [`Heartbeat`] for what counts as progress.
+    pub fn since_progress(&self) -> std::time::Duration {
+        self.heartbeat.elapsed()
+    }
+}
+
 impl Close {
     fn close(&self) {
         let _ = self.close_blocks.send(());
@@ -62,8 +62,11 @@ async fn test_handle_close_ok() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
+    let liveness = handle.liveness();
+    assert!(liveness.is_alive());
     handle.close().await.unwrap();
+    assert!(!liveness.is_alive());
 }
 
 #[tokio::test]
@@ -79,7 +82,7 @@ async fn test_handle_close_immediate_ok() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     handle.close().await.unwrap();
 }
 
@@ -97,7 +100,7 @@ async fn test_drop_handle() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     assert_eq!(
         b_closed.try_recv().unwrap_err(),
         oneshot::error::TryRecvError::Empty
@@ -120,7 +123,7 @@ async fn test_drop_handle_join() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     assert_eq!(
         b_closed.try_recv().unwrap_err(),
         oneshot::error::TryRecvError::Empty
@@ -145,7 +148,7 @@ async fn test_select_handle_join() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     assert_eq!(
         b_closed.try_recv().unwrap_err(),
         oneshot::error::TryRecvError::Empty
@@ -178,7 +181,7 @@ async fn test_drop_handle_close() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     assert_eq!(
         b_closed.try_recv().unwrap_err(),
         oneshot::error::TryRecvError::Empty
@@ -201,7 +204,7 @@ async fn test_panic_task_close() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     let _ = handle.close().await;
 }
 
@@ -217,7 +220,7 @@ async fn test_handle_close_err() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     let e = handle.close().await.unwrap_err();
 
     assert!(matches!(e, CriticalError::Overflow));
@@ -236,7 +239,7 @@ async fn test_handle_close_immediate_err() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     let e = handle.close().await.unwrap_err();
 
     assert!(matches!(e, CriticalError::Overflow));
@@ -254,7 +257,7 @@ async fn test_handle_join_ok() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     tokio::time::timeout(std::time::Duration::from_millis(50), handle.join())
         .await
         .unwrap_err();
@@ -273,7 +276,7 @@ async fn test_handle_join_immediate_ok() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     handle.join().await.unwrap();
 }
 
@@ -290,7 +293,7 @@ async fn test_handle_join_immediate_err() {
         }
     });
 
-    let handle = Handle::new(b, close_blocks);
+    let handle = Handle::new(b, close_blocks, Default::default(), Default::default());
     let e = handle.join().await.unwrap_err();
 
     assert!(matches!(e, CriticalError::Overflow));
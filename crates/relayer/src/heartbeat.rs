@@ -0,0 +1,35 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The time at which the relayer's stream last made progress, i.e. synced a
+/// block or received a keep-alive from the source.
+///
+/// Unlike liveness, this reveals a stream that keeps restarting without
+/// syncing. Clones share the same time, so a clone may be used to observe the
+/// progress of a running relayer.
+#[derive(Clone, Debug)]
+pub struct Heartbeat(Arc<Mutex<Instant>>);
+
+impl Heartbeat {
+    /// Record that the stream made progress.
+    pub(crate) fn beat(&self) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Instant::now();
+    }
+
+    /// The time elapsed since the stream last made progress, or since the
+    /// relayer started if it has made none.
+    pub fn elapsed(&self) -> Duration {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .elapsed()
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+}
@@ -12,8 +12,10 @@ pub use error::Result;
 use essential_node_db::{self as db, ConnectionPool};
 use essential_node_types::block_notify::BlockTx;
 use futures::StreamExt;
-pub use handle::Handle;
-use reqwest::{ClientBuilder, Url};
+pub use handle::{Handle, Liveness};
+pub use heartbeat::Heartbeat;
+use reqwest::{Certificate, ClientBuilder, Url};
+pub use restarts::Restarts;
 use std::future::Future;
 use sync::stream_blocks;
 use sync::sync_blocks;
@@ -21,6 +23,8 @@ use tokio::sync::watch;
 
 mod error;
 mod handle;
+mod heartbeat;
+mod restarts;
 mod sync;
 #[cfg(test)]
 mod tests;
@@ -35,8 +39,26 @@ pub struct Relayer {
 impl Relayer {
     /// Create a new relayer client from a node endpoint.
     pub fn new(endpoint: impl TryInto<Url>) -> Result<Self> {
+        Self::with_client_builder(endpoint, ClientBuilder::new())
+    }
+
+    /// Create a new relayer client from a node endpoint, trusting the CA
+    /// certificates in the given PEM bundle in addition to those of the
+    /// system's certificate store when connecting over TLS.
+    pub fn with_ca_bundle(endpoint: impl TryInto<Url>, ca_bundle_pem: &[u8]) -> Result<Self> {
+        let certs = Certificate::from_pem_bundle(ca_bundle_pem).map_err(CriticalError::CaBundle)?;
+        if certs.is_empty() {
+            return Err(CriticalError::EmptyCaBundle);
+        }
+        let builder = certs
+            .into_iter()
+            .fold(ClientBuilder::new(), ClientBuilder::add_root_certificate);
+        Self::with_client_builder(endpoint, builder)
+    }
+
+    fn with_client_builder(endpoint: impl TryInto<Url>, builder: ClientBuilder) -> Result<Self> {
         let endpoint = endpoint.try_into().map_err(|_| CriticalError::UrlParse)?;
-        let client = ClientBuilder::new()
+        let client = builder
             .http2_prior_knowledge()
             .build()
             .map_err(CriticalError::HttpClientBuild)?;
@@ -51,19 +73,23 @@ impl Relayer {
     ///
     /// The two watch channels are used to notify the caller when new data has been synced.
     pub fn run(self, pool: ConnectionPool, new_block: BlockTx) -> Result<Handle> {
+        let heartbeat = Heartbeat::default();
+
         // The blocks callback. This is a closure that will be called
         // every time the blocks stream is restarted.
+        let stream_heartbeat = heartbeat.clone();
         let blocks = move |shutdown: watch::Receiver<()>| {
             let pool = pool.clone();
             let relayer = self.clone();
             let notify = new_block.clone();
+            let heartbeat = stream_heartbeat.clone();
             async move {
                 // Run the blocks stream
-                relayer.run_blocks(pool, shutdown, notify).await
+                relayer.run_blocks(pool, shutdown, notify, heartbeat).await
             }
         };
 
-        run(blocks)
+        run(blocks, heartbeat)
     }
 
     /// Run the blocks stream.
@@ -73,6 +99,7 @@ impl Relayer {
         conn: ConnectionPool,
         mut shutdown: watch::Receiver<()>,
         notify: BlockTx,
+        heartbeat: Heartbeat,
     ) -> InternalResult<()> {
         #[cfg(feature = "tracing")]
         tracing::info!("Stream starting");
@@ -83,7 +110,7 @@ impl Relayer {
             .map_err(CriticalError::from)?;
 
         // Create the stream of blocks.
-        let stream = stream_blocks(&self.endpoint, &self.client, &progress).await?;
+        let stream = stream_blocks(&self.endpoint, &self.client, &progress, &heartbeat).await?;
 
         // Setup a future that will close the stream when the shutdown signal is received.
         let close = async move {
@@ -93,7 +120,14 @@ impl Relayer {
         };
 
         // Run the stream of blocks.
-        sync_blocks(conn, &progress, notify, stream.take_until(close)).await
+        sync_blocks(
+            conn,
+            &progress,
+            notify,
+            &heartbeat,
+            stream.take_until(close),
+        )
+        .await
     }
 }
 
@@ -101,15 +135,17 @@ impl Relayer {
 ///
 /// Handles errors and returns a handle that can be used to close or join the streams.
 ///
-/// Recoverable errors will be logged and the stream will be restarted.
+/// Recoverable errors will be logged, counted and the stream will be restarted.
 /// Critical errors will cause the stream to end.
-fn run<B, BFut>(mut blocks: B) -> Result<Handle>
+fn run<B, BFut>(mut blocks: B, heartbeat: Heartbeat) -> Result<Handle>
 where
     B: FnMut(watch::Receiver<()>) -> BFut + Send + 'static,
     BFut: Future<Output = InternalResult<()>> + Send,
 {
     // Create a channels to signal the streams to shutdown.
     let (close_blocks, blocks_shutdown) = watch::channel(());
+    let restarts = Restarts::default();
+    let stream_restarts = restarts.clone();
 
     let f = async move {
         loop {
@@ -121,7 +157,7 @@ where
                 Err(e) => {
                     // Return error if it's critical or
                     // continue if it's recoverable
-                    handle_error(e).await?;
+                    handle_error(e, &stream_restarts).await?;
                 }
             }
         }
@@ -135,11 +171,11 @@ where
 
     let join_blocks = tokio::spawn(f);
 
-    Ok(Handle::new(join_blocks, close_blocks))
+    Ok(Handle::new(join_blocks, close_blocks, restarts, heartbeat))
 }
 
-/// Exit on critical errors, log recoverable errors
-async fn handle_error(e: InternalError) -> Result<()> {
+/// Exit on critical errors, log and count recoverable errors
+async fn handle_error(e: InternalError, restarts: &Restarts) -> Result<()> {
     let e = map_recoverable_errors(e);
     match e {
         InternalError::Critical(e) => {
@@ -152,6 +188,7 @@ async fn handle_error(e: InternalError) -> Result<()> {
         }
         #[cfg(feature = "tracing")]
         InternalError::Recoverable(e) => {
+            restarts.record(e.kind());
             // Slow down loop if source is unreachable
             if matches!(e, error::RecoverableError::HttpClient(_)) {
                 // TODO: Make exponential backoff.
@@ -162,7 +199,10 @@ async fn handle_error(e: InternalError) -> Result<()> {
             Ok(())
         }
         #[cfg(not(feature = "tracing"))]
-        InternalError::Recoverable(_) => Ok(()),
+        InternalError::Recoverable(e) => {
+            restarts.record(e.kind());
+            Ok(())
+        }
     }
 }
 
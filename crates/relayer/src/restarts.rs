@@ -0,0 +1,29 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Counts of the relayer's stream restarts, by the kind of recoverable error
+/// that caused each restart.
+///
+/// Clones share the same counts, so a clone may be used to observe the
+/// restarts of a running relayer.
+#[derive(Clone, Debug, Default)]
+pub struct Restarts(Arc<Mutex<BTreeMap<&'static str, u64>>>);
+
+impl Restarts {
+    /// Record a restart caused by the given kind of recoverable error.
+    pub(crate) fn record(&self, kind: &'static str) {
+        let mut counts = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        *counts.entry(kind).or_default() += 1;
+    }
+
+    /// The number of restarts so far for each kind of recoverable error that
+    /// has caused at least one restart.
+    pub fn counts(&self) -> BTreeMap<&'static str, u64> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
@@ -7,7 +7,7 @@ use futures::Stream;
 pub(crate) use streams::stream_blocks;
 
 use crate::error::{CriticalError, InternalResult, RecoverableError};
-use crate::DataSyncError;
+use crate::{DataSyncError, Heartbeat};
 
 mod streams;
 
@@ -51,6 +51,7 @@ pub async fn sync_blocks<S>(
     pool: ConnectionPool,
     progress: &Option<BlockProgress>,
     notify: BlockTx,
+    heartbeat: &Heartbeat,
     stream: S,
 ) -> InternalResult<()>
 where
@@ -91,6 +92,7 @@ where
 
             let notify = notify.clone();
             let pool = pool.clone();
+            let heartbeat = heartbeat.clone();
             async move {
                 // If the block is not sequential, return an error.
                 if !sequential_block {
@@ -111,6 +113,7 @@ where
 
                 // Best effort to notify of new block
                 notify.notify();
+                heartbeat.beat();
                 Ok(())
             }
         })
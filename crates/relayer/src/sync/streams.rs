@@ -6,10 +6,13 @@
 //! but that needs to be balanced with over engineering temporary code.
 
 use super::BlockProgress;
-use crate::error::{CriticalError, InternalError, InternalResult, RecoverableError};
-use essential_node_types::Block;
-use futures::{Stream, TryStreamExt};
-use reqwest::{Client, Url};
+use crate::{
+    error::{CriticalError, InternalError, InternalResult, RecoverableError},
+    Heartbeat,
+};
+use essential_node_types::{block::binary, Block};
+use futures::{Stream, StreamExt, TryStreamExt};
+use reqwest::{header, Client, Url};
 use std::marker::PhantomData;
 use tokio_util::{
     bytes::{self, Buf},
@@ -22,6 +25,7 @@ pub(crate) async fn stream_blocks(
     url: &Url,
     client: &Client,
     progress: &Option<BlockProgress>,
+    heartbeat: &Heartbeat,
 ) -> InternalResult<impl Stream<Item = InternalResult<Block>>> {
     // Get the last block number that was synced.
     let last_block_number = progress
@@ -38,9 +42,11 @@ pub(crate) async fn stream_blocks(
     url.query_pairs_mut()
         .append_pair("start_block", &last_block_number.to_string());
 
-    // Send the request to the node.
+    // Send the request to the node, preferring the compact binary encoding.
+    let accept = format!("{}, text/event-stream", binary::MEDIA_TYPE);
     let response = client
         .get(url)
+        .header(header::ACCEPT, accept)
         .send()
         .await
         .map_err(RecoverableError::from)?;
@@ -50,25 +56,66 @@ pub(crate) async fn stream_blocks(
         return Err(RecoverableError::BadServerResponse(response.status()).into());
     }
 
+    // Older nodes may only support SSE.
+    let is_binary = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type == binary::MEDIA_TYPE);
+
     // Create the stream from the response.
+    #[allow(clippy::io_other_error)]
     let stream = StreamReader::new(
         response
             .bytes_stream()
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{}", e))),
     );
 
-    // Decode the stream from the node.
-    let stream = FramedRead::new(stream, SseDecoder::<Block>::new());
+    // Decode the stream from the node, counting keep-alives as progress.
+    let heartbeat = heartbeat.clone();
+    let stream = if is_binary {
+        FramedRead::new(stream, BinaryDecoder(heartbeat)).left_stream()
+    } else {
+        FramedRead::new(stream, SseDecoder::<Block>::new(heartbeat)).right_stream()
+    };
 
     Ok(stream)
 }
 
+/// Decoder for the node's length-prefixed binary block stream.
+struct BinaryDecoder(Heartbeat);
+
+impl Decoder for BinaryDecoder {
+    type Item = Block;
+    type Error = InternalError;
+
+    fn decode(&mut self, buf: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match binary::decode_frame(buf) {
+                Ok(Some((frame, len))) => {
+                    buf.advance(len);
+                    match frame {
+                        binary::Frame::Block(block) => return Ok(Some(block)),
+                        // Skip keep-alives.
+                        binary::Frame::KeepAlive => {
+                            self.0.beat();
+                            continue;
+                        }
+                    }
+                }
+                // Need more data
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(RecoverableError::StreamError(e.to_string()).into()),
+            }
+        }
+    }
+}
+
 /// Decoder for the node SSE stream.
-struct SseDecoder<T>(PhantomData<T>);
+struct SseDecoder<T>(Heartbeat, PhantomData<T>);
 
 impl<T> SseDecoder<T> {
-    fn new() -> Self {
-        Self(PhantomData)
+    fn new(heartbeat: Heartbeat) -> Self {
+        Self(heartbeat, PhantomData)
     }
 }
 
@@ -110,6 +157,7 @@ where
                     Err(_) => {
                         // Check if it's just a Keep-alive signal.
                         if s == ":" {
+                            self.0.beat();
                             Ok(None)
                         } else {
                             // This is a stream error.
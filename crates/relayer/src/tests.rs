@@ -5,9 +5,11 @@ use std::sync::{atomic::AtomicUsize, Arc};
 
 #[tokio::test]
 async fn test_run_critical_err() {
-    let handle =
-        run(|_s| futures::future::ready::<InternalResult<()>>(Err(CriticalError::Overflow.into())))
-            .unwrap();
+    let handle = run(
+        |_s| futures::future::ready::<InternalResult<()>>(Err(CriticalError::Overflow.into())),
+        Default::default(),
+    )
+    .unwrap();
     let e = handle.join().await.unwrap_err();
     assert!(matches!(e, CriticalError::Overflow));
 }
@@ -16,40 +18,48 @@ async fn test_run_critical_err() {
 async fn test_run_recoverable_err() {
     let count = Arc::new(AtomicUsize::new(0));
     let c = count.clone();
-    let handle = run(move |_shutdown| {
-        if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
-            futures::future::ready::<InternalResult<()>>(Err(RecoverableError::NonSequentialBlock(
-                0, 2,
-            )
-            .into()))
-        } else {
-            futures::future::ready::<InternalResult<()>>(Ok(()))
-        }
-    })
+    let handle = run(
+        move |_shutdown| {
+            if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                futures::future::ready::<InternalResult<()>>(Err(
+                    RecoverableError::NonSequentialBlock(0, 2).into(),
+                ))
+            } else {
+                futures::future::ready::<InternalResult<()>>(Ok(()))
+            }
+        },
+        Default::default(),
+    )
     .unwrap();
+    let restarts = handle.restarts();
     handle.join().await.unwrap();
     assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 2);
+    let counts = restarts.counts();
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts["non_sequential_block"], 1);
 }
 
 #[tokio::test]
 async fn test_run_recoverable_close() {
     let count = Arc::new(AtomicUsize::new(0));
     let c = count.clone();
-    let handle = run(move |mut shutdown| {
-        if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
-            futures::future::ready::<InternalResult<()>>(Err(RecoverableError::NonSequentialBlock(
-                0, 2,
-            )
-            .into()))
-            .boxed()
-        } else {
-            async move {
-                let _ = shutdown.changed().await;
-                Ok(())
+    let handle = run(
+        move |mut shutdown| {
+            if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                futures::future::ready::<InternalResult<()>>(Err(
+                    RecoverableError::NonSequentialBlock(0, 2).into(),
+                ))
+                .boxed()
+            } else {
+                async move {
+                    let _ = shutdown.changed().await;
+                    Ok(())
+                }
+                .boxed()
             }
-            .boxed()
-        }
-    })
+        },
+        Default::default(),
+    )
     .unwrap();
     tokio::time::sleep(std::time::Duration::from_millis(50)).await;
     handle.close().await.unwrap();
@@ -60,14 +70,17 @@ async fn test_run_recoverable_close() {
 async fn test_run_join() {
     let count = Arc::new(());
     let c = count.clone();
-    let handle = run(move |mut shutdown| {
-        let c = c.clone();
-        async move {
-            let _c = c;
-            let _ = shutdown.changed().await;
-            Ok(())
-        }
-    })
+    let handle = run(
+        move |mut shutdown| {
+            let c = c.clone();
+            async move {
+                let _c = c;
+                let _ = shutdown.changed().await;
+                Ok(())
+            }
+        },
+        Default::default(),
+    )
     .unwrap();
     tokio::time::timeout(std::time::Duration::from_millis(100), handle.join())
         .await
@@ -81,14 +94,17 @@ async fn test_run_join() {
 async fn test_run_close() {
     let count = Arc::new(());
     let c = count.clone();
-    let handle = run(move |mut shutdown| {
-        let c = c.clone();
-        async move {
-            let _c = c;
-            let _ = shutdown.changed().await;
-            Ok(())
-        }
-    })
+    let handle = run(
+        move |mut shutdown| {
+            let c = c.clone();
+            async move {
+                let _c = c;
+                let _ = shutdown.changed().await;
+                Ok(())
+            }
+        },
+        Default::default(),
+    )
     .unwrap();
     handle.close().await.unwrap();
     Arc::try_unwrap(count).unwrap();
@@ -98,14 +114,17 @@ async fn test_run_close() {
 async fn test_run_join_immediate() {
     let count = Arc::new(());
     let c = count.clone();
-    let handle = run(move |shutdown| {
-        let c = c.clone();
-        async move {
-            let _c = c;
-            let _s = shutdown;
-            Ok(())
-        }
-    })
+    let handle = run(
+        move |shutdown| {
+            let c = c.clone();
+            async move {
+                let _c = c;
+                let _s = shutdown;
+                Ok(())
+            }
+        },
+        Default::default(),
+    )
     .unwrap();
     handle.join().await.unwrap();
     Arc::try_unwrap(count).unwrap();
@@ -115,21 +134,23 @@ async fn test_run_join_immediate() {
 async fn test_run_multiple_recoverable() {
     let count = Arc::new(AtomicUsize::new(0));
     let c = count.clone();
-    let handle = run(move |mut shutdown| {
-        if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 10 {
-            futures::future::ready::<InternalResult<()>>(Err(RecoverableError::NonSequentialBlock(
-                0, 2,
-            )
-            .into()))
-            .boxed()
-        } else {
-            async move {
-                let _ = shutdown.changed().await;
-                Ok(())
+    let handle = run(
+        move |mut shutdown| {
+            if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 10 {
+                futures::future::ready::<InternalResult<()>>(Err(
+                    RecoverableError::NonSequentialBlock(0, 2).into(),
+                ))
+                .boxed()
+            } else {
+                async move {
+                    let _ = shutdown.changed().await;
+                    Ok(())
+                }
+                .boxed()
             }
-            .boxed()
-        }
-    })
+        },
+        Default::default(),
+    )
     .unwrap();
     while count.load(std::sync::atomic::Ordering::SeqCst) < 10 {
         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
@@ -144,3 +165,30 @@ async fn test_run_multiple_recoverable() {
     // Closed
     Arc::try_unwrap(count).unwrap();
 }
+
+#[tokio::test]
+async fn test_run_recoverable_without_progress() {
+    let heartbeat = Heartbeat::default();
+    let handle = run(
+        |mut shutdown| async move {
+            tokio::select! {
+                _ = shutdown.changed() => Ok(()),
+                _ = tokio::time::sleep(std::time::Duration::from_millis(1)) => {
+                    Err(RecoverableError::NonSequentialBlock(0, 2).into())
+                }
+            }
+        },
+        heartbeat.clone(),
+    )
+    .unwrap();
+    let liveness = handle.liveness();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    // Restarting does not count as progress.
+    assert!(liveness.is_alive());
+    assert!(liveness.since_progress() >= std::time::Duration::from_millis(50));
+
+    heartbeat.beat();
+    assert!(liveness.since_progress() < std::time::Duration::from_millis(50));
+    handle.close().await.unwrap();
+}
@@ -4,7 +4,7 @@ use essential_node::db::{
     ConnectionPool,
 };
 use essential_node_db as node_db;
-use essential_node_types::{block_notify::BlockTx, Block, BlockHeader};
+use essential_node_types::{block_notify::BlockTx, BigBang, Block, BlockHeader};
 use essential_relayer::{DataSyncError, Relayer};
 use essential_types::{
     contract::Contract,
@@ -17,6 +17,8 @@ use tokio::{sync::oneshot::Sender, task::JoinHandle};
 
 const LOCALHOST: &str = "127.0.0.1";
 
+const TLS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../node-api/tests/tls");
+
 struct NodeServer {
     address: String,
     jh: JoinHandle<()>,
@@ -28,7 +30,7 @@ struct NodeServer {
 async fn test_sync() {
     let relayer_conn = new_conn_pool();
 
-    let (node_server, source_block_tx) = test_node().await;
+    let (node_server, source_block_tx) = test_node(None).await;
     let source_db = node_server.conn_pool.clone();
 
     let mut test_conn = relayer_conn.acquire().await.unwrap();
@@ -119,7 +121,7 @@ async fn test_sync() {
     tear_down_server(node_server).await;
     drop(source_db);
 
-    let (node_server, ..) = test_node().await;
+    let (node_server, ..) = test_node(None).await;
 
     let relayer = Relayer::new(node_server.address.as_str()).unwrap();
     let block_tx = BlockTx::new();
@@ -140,6 +142,45 @@ async fn test_sync() {
     );
 }
 
+#[tokio::test]
+async fn test_sync_tls() {
+    let dir = std::path::Path::new(TLS_DIR);
+    let tls =
+        essential_node_api::tls::Tls::load(dir.join("cert-a.pem"), dir.join("key-a.pem")).unwrap();
+    let (node_server, source_block_tx) = test_node(Some(tls)).await;
+    let (_, blocks) = test_structs();
+    node_server
+        .conn_pool
+        .insert_block(blocks[0].clone())
+        .await
+        .unwrap();
+    source_block_tx.notify();
+
+    // Sync from the TLS endpoint, trusting the test CA.
+    let ca_bundle = std::fs::read(dir.join("ca.pem")).unwrap();
+    let relayer = Relayer::with_ca_bundle(node_server.address.as_str(), &ca_bundle).unwrap();
+    let relayer_conn = new_conn_pool();
+    let block_tx = BlockTx::new();
+    let mut block_rx = block_tx.new_listener();
+    let relayer_handle = relayer.run(relayer_conn.clone(), block_tx).unwrap();
+
+    tokio::time::timeout(std::time::Duration::from_secs(10), block_rx.changed())
+        .await
+        .unwrap()
+        .unwrap();
+    let mut conn = relayer_conn.acquire().await.unwrap();
+    let result = node_db::with_tx_dropped(&mut conn, |tx| db::list_blocks(tx, 0..100)).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(Arc::new(result[0].clone()), blocks[0]);
+
+    relayer_handle.close().await.unwrap();
+    tear_down_server(node_server).await;
+
+    // A CA bundle containing no certificates is rejected.
+    let res = Relayer::with_ca_bundle("https://localhost/", b"not a certificate");
+    assert!(matches!(res, Err(essential_relayer::Error::EmptyCaBundle)));
+}
+
 // Create a new AsyncConnectionPool with a unique in-memory database.
 fn new_conn_pool() -> db::ConnectionPool {
     let conf = Config {
@@ -163,19 +204,30 @@ async fn test_listener() -> tokio::net::TcpListener {
 }
 
 // Spawn a test server with given ConnectionPool and block notify channel.
-async fn setup_node_as_server(state: essential_node_api::State) -> NodeServer {
+async fn setup_node_as_server(
+    state: essential_node_api::State,
+    tls: Option<essential_node_api::tls::Tls>,
+) -> NodeServer {
     let conn_pool = state.conn_pool.clone();
+    let config = essential_node_api::ServeConfig {
+        tls: tls.clone(),
+        ..Default::default()
+    };
     let router = essential_node_api::router(state);
     let listener = test_listener().await;
     let port = listener.local_addr().unwrap().port();
+    let address = match tls {
+        None => format!("http://{LOCALHOST}:{port}/"),
+        // The test certificates are issued for `localhost`.
+        Some(_) => format!("https://localhost:{port}/"),
+    };
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
     let jh = tokio::spawn(async move {
         tokio::select! {
-            _ = essential_node_api::serve(&router, &listener, essential_node_api::DEFAULT_CONNECTION_LIMIT) => {},
+            _ = essential_node_api::serve_with_config(&router, &listener, essential_node_api::DEFAULT_CONNECTION_LIMIT, config) => {},
             _ = shutdown_rx => {},
         }
     });
-    let address = format!("http://{LOCALHOST}:{port}/");
     NodeServer {
         address,
         jh,
@@ -184,9 +236,10 @@ async fn setup_node_as_server(state: essential_node_api::State) -> NodeServer {
     }
 }
 
-// Setup node as server with a unique database of default configuration.
+// Setup node as server with a unique database of default configuration,
+// served over TLS if a configuration is given.
 // Returns server and block notify channel.
-async fn test_node() -> (NodeServer, BlockTx) {
+async fn test_node(tls: Option<essential_node_api::tls::Tls>) -> (NodeServer, BlockTx) {
     let conf = Config {
         source: Source::Memory(uuid::Uuid::new_v4().into()),
         ..Default::default()
@@ -194,11 +247,29 @@ async fn test_node() -> (NodeServer, BlockTx) {
     let db = ConnectionPool::with_tables(&conf).unwrap();
     let source_block_tx = BlockTx::new();
     let source_block_rx = source_block_tx.new_listener();
+    let big_bang = BigBang::default();
+    let run_conf = essential_node::RunConfig {
+        relayer_source_endpoint: None,
+        relayer_ca_bundle: None,
+        run_validation: false,
+    };
+    let node_config = essential_node_api::NodeConfig::new(&big_bang, &run_conf);
     let state = essential_node_api::State {
         conn_pool: db,
+        contract_registry: big_bang.contract_registry.contract,
+        program_registry: big_bang.program_registry.contract,
         new_block: Some(source_block_rx),
+        validated_block: None,
+        list_blocks_limits: Default::default(),
+        metrics: Default::default(),
+        node_liveness: None,
+        max_validation_lag: essential_node_api::DEFAULT_MAX_VALIDATION_LAG,
+        max_relayer_staleness: essential_node_api::DEFAULT_MAX_RELAYER_STALENESS,
+        node_config,
+        auth: None,
+        limiter: Default::default(),
     };
-    let node_server = setup_node_as_server(state).await;
+    let node_server = setup_node_as_server(state, tls).await;
 
     (node_server, source_block_tx)
 }
@@ -51,6 +51,11 @@ impl AsyncConnectionPool {
         self.pool.capacity()
     }
 
+    /// The number of connections currently available for acquisition.
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
     /// Returns `true` if the inner idle queue is full, i.e. all `Connection`s
     /// are available for use.
     pub fn all_connections_ready(&self) -> bool {
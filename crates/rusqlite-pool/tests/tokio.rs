@@ -57,11 +57,14 @@ async fn test_acquire_async_connection() {
     let new_conn = || new_mem_conn("test_acquire_async_connection");
     let pool = AsyncConnectionPool::new(3, new_conn).unwrap();
 
+    assert_eq!(pool.available(), 3);
     let handle = pool.acquire().await.unwrap();
     assert!(!pool.all_connections_ready());
+    assert_eq!(pool.available(), 2);
 
     drop(handle);
     assert!(pool.all_connections_ready());
+    assert_eq!(pool.available(), 3);
 }
 
 #[tokio::test]